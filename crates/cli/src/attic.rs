@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::messages::write_formatted;
+use crate::time::today;
+
+/// Add removed messages to an attic file below today's date, e.g.
+/// `{"2024-05-01": {"Cart": {"oldTitle": "Your basket"}}}`
+///
+/// Messages removed earlier the same day are kept
+pub fn add_to_attic(path: &Path, removed: Map<String, Value>) -> Result<()> {
+    if removed.is_empty() {
+        return Ok(());
+    }
+    let existing = fs::read_to_string(path).ok();
+    let mut attic: Map<String, Value> = match &existing {
+        Some(content) => serde_json::from_str(content)
+            .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?,
+        None => Map::new(),
+    };
+
+    let day = attic
+        .entry(today())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Value::Object(day) = day {
+        merge(day, removed);
+    }
+
+    write_formatted(path, |format| format.to_string(&attic))
+}
+
+fn merge(attic: &mut Map<String, Value>, removed: Map<String, Value>) {
+    for (key, value) in removed {
+        match (attic.get_mut(&key), value) {
+            (Some(Value::Object(attic)), Value::Object(removed)) => merge(attic, removed),
+            (_, value) => {
+                attic.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_add_to_attic() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.attic.json");
+        let removed = |value: Value| value.as_object().unwrap().clone();
+
+        add_to_attic(&path, removed(json!({ "Cart": { "old": "Old" } }))).unwrap();
+        add_to_attic(&path, removed(json!({ "Cart": { "older": "Older" } }))).unwrap();
+        let attic: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            attic,
+            json!({ today(): { "Cart": { "old": "Old", "older": "Older" } } })
+        );
+    }
+}
@@ -0,0 +1,653 @@
+use anyhow::{anyhow, Result};
+use next_intl_resolver::Extraction;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::diagnostics::warn_outdated_keys;
+use crate::diff::FileChange;
+use crate::format::CatalogFormat;
+use crate::layout::{self, LOCALE_PLACEHOLDER, NAMESPACE_PLACEHOLDER};
+use crate::lock::Lock;
+use crate::messages::{MessageHandler, NamespaceConflict, PolicyViolation, OBSOLETE_KEY};
+
+/// The messages of one locale and where they are written
+pub struct Catalog {
+    pub locale: Option<String>,
+    /// Output path, may still contain a `{namespace}` placeholder
+    pub path: PathBuf,
+    pub handler: MessageHandler,
+}
+
+/// The catalogs of every locale, all merged with the same extracted messages
+pub struct Catalogs {
+    catalogs: Vec<Catalog>,
+    /// Locale whose messages fill the ones missing in the other locales, with a prefix
+    default_locale: Option<(String, String)>,
+    /// Lock file with the hashes of the default locale's messages, to find outdated translations
+    lock_path: Option<PathBuf>,
+}
+
+impl Catalogs {
+    /// Open the catalog of every locale, `{locale}` in the output path is replaced by each of
+    /// them. Missing catalog files are created on write
+    ///
+    /// `configure` is applied to the message handler of every catalog, with its locale
+    pub fn open<F>(
+        output_path: &Path,
+        locales: &[String],
+        format: CatalogFormat,
+        configure: F,
+    ) -> Result<Self>
+    where
+        F: Fn(MessageHandler, Option<&str>) -> Result<MessageHandler>,
+    {
+        let has_locale = layout::has_placeholder(output_path, LOCALE_PLACEHOLDER);
+        let paths = match (has_locale, locales.is_empty()) {
+            (false, true) => vec![(None, output_path.to_path_buf())],
+            (true, false) => locales
+                .iter()
+                .map(|locale| {
+                    let path = layout::fill(output_path, LOCALE_PLACEHOLDER, locale);
+                    (Some(locale.clone()), path)
+                })
+                .collect(),
+            (true, true) => {
+                return Err(anyhow!(
+                    "The output path contains {LOCALE_PLACEHOLDER}, pass the locales with --locale"
+                ))
+            }
+            (false, false) => {
+                return Err(anyhow!(
+                    "--locale needs a {LOCALE_PLACEHOLDER} placeholder in the output path"
+                ))
+            }
+        };
+
+        let mut catalogs = Vec::new();
+        for (locale, path) in paths {
+            if !layout::has_placeholder(&path, NAMESPACE_PLACEHOLDER) && !path.exists() {
+                info!("Output file does not exist yet, creating: {:?}", path);
+            }
+            let handler = configure(MessageHandler::open(&path, format)?, locale.as_deref())?;
+            catalogs.push(Catalog {
+                locale,
+                path,
+                handler,
+            });
+        }
+        Ok(Self {
+            catalogs,
+            default_locale: None,
+            lock_path: None,
+        })
+    }
+
+    /// Fill the messages that are missing in the other locales with the messages of this locale,
+    /// string values get the prefix, e.g. `[TODO] `
+    pub fn with_default_locale(mut self, locale: String, prefix: String) -> Result<Self> {
+        if !self
+            .catalogs
+            .iter()
+            .any(|catalog| catalog.locale.as_ref() == Some(&locale))
+        {
+            return Err(anyhow!(
+                "The default locale {locale} is not one of the locales"
+            ));
+        }
+        self.default_locale = Some((locale, prefix));
+        Ok(self)
+    }
+
+    /// Keep the hashes of the default locale's messages in a lock file, and warn about the
+    /// translations of messages that changed since, until the translations are changed too
+    ///
+    /// The lock file also remembers which values were generated, so the values edited by hand
+    /// are never replaced by placeholders
+    pub fn with_lock(mut self, lock_path: PathBuf) -> Result<Self> {
+        if self.default_locale.is_none() {
+            return Err(anyhow!("A lock file needs a default locale"));
+        }
+        let lock = Lock::load(&lock_path)?;
+        for catalog in &mut self.catalogs {
+            if let Some(locale) = &catalog.locale {
+                let edited_keys = lock.edited_keys(locale, catalog.handler.source_messages());
+                catalog.handler.set_edited_keys(edited_keys);
+            }
+        }
+        self.lock_path = Some(lock_path);
+        Ok(self)
+    }
+
+    /// The full keys of the default locale's messages that are missing or empty in the catalog
+    /// of every other locale, by locale. Locales without missing messages are left out
+    pub fn missing_keys(&self) -> BTreeMap<String, Vec<String>> {
+        let Some((default_locale, _)) = &self.default_locale else {
+            return BTreeMap::new();
+        };
+        let Some(source) = self
+            .catalogs
+            .iter()
+            .find(|catalog| catalog.locale.as_ref() == Some(default_locale))
+        else {
+            return BTreeMap::new();
+        };
+        let mut missing_keys = BTreeMap::new();
+        for catalog in &self.catalogs {
+            let Some(locale) = catalog
+                .locale
+                .as_ref()
+                .filter(|&locale| locale != default_locale)
+            else {
+                continue;
+            };
+            let mut keys = Vec::new();
+            collect_missing_keys(
+                source.handler.source_messages(),
+                Some(catalog.handler.source_messages()),
+                "",
+                &mut keys,
+            );
+            if !keys.is_empty() {
+                missing_keys.insert(locale.clone(), keys);
+            }
+        }
+        missing_keys
+    }
+
+    /// Add messages of one locale to the catalog, e.g. the ones of another library, so there
+    /// must be a single catalog
+    pub fn add_source_messages(
+        &mut self,
+        messages: Map<String, Value>,
+        descriptions: Map<String, Value>,
+    ) -> Result<()> {
+        let [catalog] = self.catalogs.as_mut_slice() else {
+            return Err(anyhow!(
+                "Messages of one locale can't be added to the catalogs of several locales, pass an output path without {LOCALE_PLACEHOLDER}"
+            ));
+        };
+        catalog.handler.add_source_messages(messages, descriptions);
+        Ok(())
+    }
+
+    /// Move the messages of a namespace to another namespace in the catalog of every locale
+    pub fn move_namespace(&mut self, from: &str, to: &str) {
+        for catalog in &mut self.catalogs {
+            catalog.handler.move_namespace(from, to);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Catalog> {
+        self.catalogs.iter()
+    }
+
+    pub fn add_extraction(&mut self, extraction: Extraction, file_path: String) {
+        let Some((last, rest)) = self.catalogs.split_last_mut() else {
+            return;
+        };
+        for catalog in rest {
+            catalog
+                .handler
+                .add_extraction(extraction.clone(), file_path.clone());
+        }
+        last.handler.add_extraction(extraction, file_path);
+    }
+
+    pub fn add_default_message(&mut self, namespace: &str, key: &str, default_message: String) {
+        for catalog in &mut self.catalogs {
+            catalog
+                .handler
+                .add_default_message(namespace, key, default_message.clone());
+        }
+    }
+
+    /// The conflicts of the extracted messages, which are the same for every locale
+    pub fn get_conflicts(&self) -> &[NamespaceConflict] {
+        self.catalogs
+            .first()
+            .map(|catalog| catalog.handler.get_conflicts())
+            .unwrap_or_default()
+    }
+
+    /// The extracted messages whose keys break the key policy, the same for every locale
+    pub fn get_policy_violations(&self) -> &[PolicyViolation] {
+        self.catalogs
+            .first()
+            .map(|catalog| catalog.handler.get_policy_violations())
+            .unwrap_or_default()
+    }
+
+    pub fn remove_messages_for_file(&mut self, file_path: &str) {
+        for catalog in &mut self.catalogs {
+            catalog.handler.remove_messages_for_file(file_path);
+        }
+    }
+
+    pub fn write_merged_messages(&mut self) -> Result<()> {
+        self.set_fallbacks();
+        for catalog in &self.catalogs {
+            catalog.handler.write_merged_messages(&catalog.path)?;
+        }
+        if let Some(lock_path) = &self.lock_path {
+            self.update_lock(lock_path)?;
+        }
+        Ok(())
+    }
+
+    /// The catalog files that writing the merged messages would change, without writing them
+    pub fn changed_files(&mut self) -> Result<Vec<FileChange>> {
+        self.set_fallbacks();
+        self.unformatted_files()
+    }
+
+    /// The catalog files that aren't written in the configured style, e.g. unsorted after
+    /// editing them by hand. Without extracted messages nothing else would change
+    pub fn unformatted_files(&self) -> Result<Vec<FileChange>> {
+        let mut changes = Vec::new();
+        for catalog in &self.catalogs {
+            for (path, new) in catalog.handler.rendered_catalogs(&catalog.path)? {
+                let old = fs::read_to_string(&path).ok();
+                if old.as_deref() != Some(new.as_str()) {
+                    changes.push(FileChange { path, old, new });
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Fill the messages missing in the other locales with the ones of the default locale
+    fn set_fallbacks(&mut self) {
+        if let Some((locale, prefix)) = &self.default_locale {
+            let fallback = self
+                .catalogs
+                .iter()
+                .find(|catalog| catalog.locale.as_ref() == Some(locale))
+                .map(|catalog| catalog.handler.merge_messages())
+                .unwrap_or_default();
+            for catalog in &mut self.catalogs {
+                if catalog.locale.as_ref() != Some(locale) {
+                    catalog
+                        .handler
+                        .set_fallback(fallback.clone(), prefix.clone());
+                }
+            }
+        }
+    }
+
+    fn update_lock(&self, lock_path: &Path) -> Result<()> {
+        let Some((default_locale, _)) = &self.default_locale else {
+            return Ok(());
+        };
+        let mut lock = Lock::load(lock_path)?;
+        let mut source = Default::default();
+        let mut translations = Vec::new();
+        for catalog in &self.catalogs {
+            let Some(locale) = catalog.locale.as_deref() else {
+                continue;
+            };
+            let messages = catalog.handler.merge_messages();
+            lock.update_generated(locale, &messages, &catalog.handler.generated_keys());
+            if locale == default_locale {
+                source = messages;
+            } else {
+                translations.push((locale, messages));
+            }
+        }
+        for (locale, keys) in lock.update(&source, &translations) {
+            warn_outdated_keys(&locale, default_locale, &keys);
+        }
+        lock.write(lock_path)
+    }
+}
+
+/// The full keys of the messages that have no value in the translations, or an empty one
+fn collect_missing_keys(
+    source: &Map<String, Value>,
+    translations: Option<&Map<String, Value>>,
+    prefix: &str,
+    keys: &mut Vec<String>,
+) {
+    for (key, value) in source {
+        if key == OBSOLETE_KEY {
+            continue;
+        }
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        let translation = translations.and_then(|translations| translations.get(key));
+        match value {
+            Value::Object(nested) => collect_missing_keys(
+                nested,
+                translation.and_then(Value::as_object),
+                &full_key,
+                keys,
+            ),
+            _ => {
+                let empty = match translation {
+                    None | Some(Value::Null) => true,
+                    Some(Value::String(translation)) => translation.trim().is_empty(),
+                    Some(_) => false,
+                };
+                if empty {
+                    keys.push(full_key);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::RemovedKeys;
+    use crate::order::SortOrder;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_locale_catalogs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("de")).unwrap();
+        fs::write(
+            dir.path().join("de/Cart.json"),
+            r#"{ "title": "Warenkorb" }"#,
+        )
+        .unwrap();
+
+        let template = dir.path().join("{locale}/{namespace}.json");
+        let locales = ["en".to_string(), "de".to_string()];
+        let mut catalogs =
+            Catalogs::open(&template, &locales, CatalogFormat::Json, |handler, _| {
+                Ok(handler)
+            })
+            .unwrap();
+        for catalog in catalogs.iter() {
+            assert!(catalog.path.ends_with("{namespace}.json"));
+        }
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Cart".to_string(),
+                HashSet::from(["title".to_string()]),
+            )]),
+            ..Default::default()
+        };
+        catalogs.add_extraction(extraction, "a.ts".to_string());
+        catalogs.write_merged_messages().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("en/Cart.json")).unwrap(),
+            "{\n  \"title\": \"Cart.title\"\n}"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("de/Cart.json")).unwrap(),
+            "{\n  \"title\": \"Warenkorb\"\n}"
+        );
+
+        assert!(
+            Catalogs::open(&template, &[], CatalogFormat::Json, |handler, _| Ok(
+                handler
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_default_locale() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("en.json"),
+            r#"{ "Cart": { "title": "Cart" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("de.json"),
+            r#"{ "Cart": { "empty": "Leer" } }"#,
+        )
+        .unwrap();
+
+        let template = dir.path().join("{locale}.json");
+        let locales = ["en".to_string(), "de".to_string()];
+        let mut catalogs =
+            Catalogs::open(&template, &locales, CatalogFormat::Json, |handler, _| {
+                Ok(handler)
+            })
+            .unwrap()
+            .with_default_locale("en".to_string(), "[TODO] ".to_string())
+            .unwrap();
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Cart".to_string(),
+                HashSet::from(["title".to_string(), "empty".to_string()]),
+            )]),
+            ..Default::default()
+        };
+        catalogs.add_extraction(extraction, "a.ts".to_string());
+        catalogs.write_merged_messages().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("de.json")).unwrap(),
+            "{\n  \"Cart\": {\n    \"empty\": \"Leer\",\n    \"title\": \"[TODO] Cart\"\n  }\n}"
+        );
+        assert!(
+            Catalogs::open(&template, &locales, CatalogFormat::Json, |handler, _| Ok(
+                handler
+            ))
+            .unwrap()
+            .with_default_locale("fr".to_string(), String::new())
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_lock() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let lock_path = dir.path().join("messages.lock.json");
+        fs::write(
+            dir.path().join("en.json"),
+            r#"{ "Cart": { "title": "Cart" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("de.json"),
+            r#"{ "Cart": { "title": "Warenkorb" } }"#,
+        )
+        .unwrap();
+
+        let template = dir.path().join("{locale}.json");
+        let locales = ["en".to_string(), "de".to_string()];
+        let run = || {
+            let mut catalogs =
+                Catalogs::open(&template, &locales, CatalogFormat::Json, |handler, _| {
+                    Ok(handler)
+                })
+                .unwrap()
+                .with_default_locale("en".to_string(), String::new())
+                .unwrap()
+                .with_lock(lock_path.clone())
+                .unwrap();
+            let extraction = Extraction {
+                translations: HashMap::from([(
+                    "Cart".to_string(),
+                    HashSet::from(["title".to_string()]),
+                )]),
+                ..Default::default()
+            };
+            catalogs.add_extraction(extraction, "a.ts".to_string());
+            catalogs.write_merged_messages().unwrap();
+            let lock: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(&lock_path).unwrap()).unwrap();
+            lock["outdated"].clone()
+        };
+
+        assert_eq!(run(), serde_json::json!({}));
+        fs::write(
+            dir.path().join("en.json"),
+            r#"{ "Cart": { "title": "Basket" } }"#,
+        )
+        .unwrap();
+        assert!(run()["de"].get("Cart.title").is_some());
+    }
+
+    #[test]
+    fn test_edited_values_are_kept() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let lock_path = dir.path().join("messages.lock.json");
+        fs::write(dir.path().join("en.json"), "{}").unwrap();
+        fs::write(dir.path().join("de.json"), "{}").unwrap();
+
+        let template = dir.path().join("{locale}.json");
+        let locales = ["en".to_string(), "de".to_string()];
+        let run = |namespace: &str| {
+            let mut catalogs =
+                Catalogs::open(&template, &locales, CatalogFormat::Json, |handler, _| {
+                    Ok(handler)
+                })
+                .unwrap()
+                .with_default_locale("en".to_string(), String::new())
+                .unwrap()
+                .with_lock(lock_path.clone())
+                .unwrap();
+            let extraction = Extraction {
+                translations: HashMap::from([(
+                    namespace.to_string(),
+                    HashSet::from(["title".to_string(), "empty".to_string()]),
+                )]),
+                ..Default::default()
+            };
+            catalogs.add_extraction(extraction, "a.ts".to_string());
+            catalogs.write_merged_messages().unwrap();
+            let de: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(dir.path().join("de.json")).unwrap())
+                    .unwrap();
+            de
+        };
+
+        run("Cart");
+        fs::write(
+            dir.path().join("de.json"),
+            r#"{ "Cart": { "title": "Warenkorb", "empty": "Cart.empty" } }"#,
+        )
+        .unwrap();
+
+        // The translated value moves along, the placeholder is generated again
+        assert_eq!(
+            run("Shop.Cart"),
+            serde_json::json!({ "Shop": { "Cart": {
+                "empty": "Shop.Cart.empty",
+                "title": "Warenkorb"
+            } } })
+        );
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let template = dir.path().join("{locale}.json");
+        fs::write(
+            dir.path().join("en.json"),
+            "{\n  \"Cart\": {\n    \"title\": \"Cart\"\n  }\n}",
+        )
+        .unwrap();
+        let locales = ["en".to_string(), "de".to_string()];
+        let mut catalogs =
+            Catalogs::open(&template, &locales, CatalogFormat::Json, |handler, _| {
+                Ok(handler)
+            })
+            .unwrap();
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Cart".to_string(),
+                HashSet::from(["title".to_string()]),
+            )]),
+            ..Default::default()
+        };
+        catalogs.add_extraction(extraction, "a.ts".to_string());
+
+        // Only the missing catalog would change, and nothing is written
+        let changes = catalogs.changed_files().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, dir.path().join("de.json"));
+        assert_eq!(changes[0].old, None);
+        assert!(!dir.path().join("de.json").exists());
+
+        catalogs.write_merged_messages().unwrap();
+        assert!(catalogs.changed_files().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_missing_keys() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("en.json"),
+            r#"{ "Cart": { "title": "Cart", "empty": "Empty", "total": "Total" }, "Nav": { "home": "Home" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("de.json"),
+            r#"{ "Cart": { "title": "Warenkorb", "empty": " " } }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("fr.json"),
+            r#"{ "Cart": { "title": "Panier", "empty": "Vide", "total": "Total" }, "Nav": { "home": "Accueil" } }"#,
+        )
+        .unwrap();
+
+        let template = dir.path().join("{locale}.json");
+        let locales = ["en".to_string(), "de".to_string(), "fr".to_string()];
+        let catalogs = Catalogs::open(&template, &locales, CatalogFormat::Json, |handler, _| {
+            Ok(handler)
+        })
+        .unwrap()
+        .with_default_locale("en".to_string(), String::new())
+        .unwrap();
+        assert_eq!(
+            catalogs.missing_keys(),
+            BTreeMap::from([(
+                "de".to_string(),
+                vec![
+                    "Cart.empty".to_string(),
+                    "Cart.total".to_string(),
+                    "Nav.home".to_string()
+                ]
+            )])
+        );
+    }
+
+    #[test]
+    fn test_unformatted_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let template = dir.path().join("{locale}.json");
+        fs::write(
+            dir.path().join("en.json"),
+            "{\n  \"Nav\": {\n    \"home\": \"Home\"\n  },\n  \"Cart\": {\n    \"title\": \"Cart\"\n  }\n}\n",
+        )
+        .unwrap();
+        let open = |sort_order| {
+            Catalogs::open(
+                &template,
+                &["en".to_string()],
+                CatalogFormat::Json,
+                |handler, _| {
+                    Ok(handler
+                        .with_sort_order(sort_order)
+                        .with_removed_keys(RemovedKeys::Keep))
+                },
+            )
+            .unwrap()
+        };
+
+        assert!(open(SortOrder::Existing)
+            .unformatted_files()
+            .unwrap()
+            .is_empty());
+        let changes = open(SortOrder::Alphabetical).unformatted_files().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, dir.path().join("en.json"));
+        assert_eq!(changes[0].summary().split(": ").nth(1), Some("+3 -3 lines"));
+    }
+}
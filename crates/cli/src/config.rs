@@ -0,0 +1,455 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Command;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::toml;
+
+/// Prefix of the environment variables that set options, e.g. `NEXT_INTL_EXTRACTOR_OUTPUT_PATH`
+pub const ENV_PREFIX: &str = "NEXT_INTL_EXTRACTOR_";
+
+/// Configuration files that are picked up from the working directory, in order of preference
+pub const CONFIG_FILES: [&str; 3] = [
+    "next-intl-extractor.config.json",
+    "next-intl-extractor.config.toml",
+    "next-intl-extractor.config.js",
+];
+
+/// The first configuration file that exists in a directory
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The nearest configuration file in a directory above a file, up to but not including the
+/// working directory, e.g. `packages/web/next-intl-extractor.config.json` for
+/// `packages/web/app/page.tsx`
+pub fn nearest(file: &Path) -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok();
+    file.ancestors()
+        .skip(1)
+        .take_while(|dir| {
+            !dir.as_os_str().is_empty()
+                && *dir != Path::new(".")
+                && Some(*dir) != current_dir.as_deref()
+        })
+        .find_map(find)
+}
+
+/// Split the files into the ones of the working directory and the ones of the packages below
+/// it with a configuration file of their own, by configuration file
+pub fn group_by_package(files: Vec<PathBuf>) -> (Vec<PathBuf>, BTreeMap<PathBuf, Vec<PathBuf>>) {
+    let mut own = Vec::new();
+    let mut packages: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for file in files {
+        match nearest(&file) {
+            Some(config_path) => packages.entry(config_path).or_default().push(file),
+            None => own.push(file),
+        }
+    }
+    (own, packages)
+}
+
+/// Load the options of a configuration file, JavaScript files are evaluated with Node.js and
+/// must export the options as default export
+pub fn load(path: &Path) -> Result<Map<String, Value>> {
+    let value = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?
+        }
+        Some("toml") => {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+            toml::parse(&content)
+                .with_context(|| format!("Failed to parse TOML from: {}", path.display()))?
+                .into_value()
+        }
+        Some("js" | "mjs" | "cjs") => evaluate(path)?,
+        _ => {
+            return Err(anyhow!(
+                "Unsupported config file {}, use a .json, .toml or .js file",
+                path.display()
+            ))
+        }
+    };
+    match value {
+        Value::Object(options) => Ok(options),
+        _ => Err(anyhow!("Config file {} is not an object", path.display())),
+    }
+}
+
+/// Print the default export of a JavaScript module as JSON with Node.js
+fn evaluate(path: &Path) -> Result<Value> {
+    let path = fs::canonicalize(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let output = process::Command::new("node")
+        .args([
+            "--input-type=module",
+            "-e",
+            "const config = await import(process.argv[1]); \
+             console.log(JSON.stringify(config.default ?? config))",
+        ])
+        .arg(format!("file://{}", path.display()))
+        .output()
+        .context("Failed to run node to load the config file")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to load config file {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("Failed to parse the options of: {}", path.display()))
+}
+
+/// The options set by environment variables with the [`ENV_PREFIX`], e.g. `output_path` for
+/// `NEXT_INTL_EXTRACTOR_OUTPUT_PATH`
+///
+/// Lists are comma separated, e.g. `NEXT_INTL_EXTRACTOR_LOCALE=en,de`, and flags are set with
+/// `true` or `1`
+pub fn env_options(vars: impl IntoIterator<Item = (String, String)>) -> Map<String, Value> {
+    vars.into_iter()
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?.to_ascii_lowercase();
+            Some((key, Value::String(value)))
+        })
+        .collect()
+}
+
+/// The long names of the options passed on the command line, e.g. `output-path` for
+/// `-o messages.json` or `--output-path=messages.json`
+pub fn given_options(command: &Command, args: &[String]) -> HashSet<String> {
+    args.iter()
+        .take_while(|arg| *arg != "--")
+        .filter_map(|arg| {
+            if let Some(long) = arg.strip_prefix("--") {
+                let name = long.split_once('=').map_or(long, |(name, _)| name);
+                return Some(name.to_string());
+            }
+            let short = arg.strip_prefix('-')?.chars().next()?;
+            command
+                .get_arguments()
+                .find(|option| option.get_short() == Some(short))
+                .and_then(|option| option.get_long())
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Command line arguments for the options of a configuration file or the environment, leaving
+/// out the ones that are given already as those take precedence
+///
+/// Keys are the long names of the options, in kebab case, camel case or snake case, e.g.
+/// `output-path` or `outputPath`. A list is passed as the option repeated, `true` as a flag,
+/// also for options with an optional value, e.g. `namespaceFromPath`
+///
+/// `origin` names where the options come from in errors, e.g. `the config file`
+///
+/// Options that the subcommand doesn't take are left out, e.g. `watch` for `stats`
+pub fn to_args(
+    command: &Command,
+    subcommand: &Command,
+    options: &Map<String, Value>,
+    given: &HashSet<String>,
+    origin: &str,
+) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    for (key, value) in options {
+        let name = kebab_case(key);
+        let option = command
+            .get_arguments()
+            .find(|option| option.get_long() == Some(name.as_str()))
+            .ok_or_else(|| anyhow!("Unknown option '{key}' in {origin}"))?;
+        let applies = subcommand
+            .get_arguments()
+            .any(|option| option.get_long() == Some(name.as_str()));
+        if given.contains(&name) || !applies {
+            continue;
+        }
+        let values = match value {
+            Value::Array(values) => values.clone(),
+            value => vec![value.clone()],
+        };
+        // e.g. `--namespace-from-path` without a value takes the default template
+        let optional_value = option
+            .get_num_args()
+            .is_some_and(|range| range.min_values() == 0 && range.takes_values());
+        for value in values {
+            match value {
+                Value::Bool(flag) if optional_value => {
+                    if flag {
+                        args.push(format!("--{name}"));
+                    }
+                }
+                Value::String(flag)
+                    if optional_value && matches!(flag.as_str(), "true" | "1" | "false" | "0") =>
+                {
+                    if matches!(flag.as_str(), "true" | "1") {
+                        args.push(format!("--{name}"));
+                    }
+                }
+                Value::Bool(flag) if !option.get_action().takes_values() => {
+                    if flag {
+                        args.push(format!("--{name}"));
+                    }
+                }
+                // Environment variables are always strings
+                Value::String(flag) if !option.get_action().takes_values() => match flag.as_str() {
+                    "true" | "1" => args.push(format!("--{name}")),
+                    "false" | "0" | "" => {}
+                    _ => return Err(anyhow!("Option '{key}' in {origin} is not a boolean")),
+                },
+                Value::String(value) => args.push(format!("--{name}={value}")),
+                Value::Bool(_) | Value::Number(_) => args.push(format!("--{name}={value}")),
+                _ => return Err(anyhow!("Option '{key}' in {origin} has an invalid value")),
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// `outputPath` and `output_path` to `output-path`
+fn kebab_case(key: &str) -> String {
+    let mut kebab = String::new();
+    for c in key.chars() {
+        match c {
+            '_' => kebab.push('-'),
+            c if c.is_ascii_uppercase() => {
+                kebab.push('-');
+                kebab.push(c.to_ascii_lowercase());
+            }
+            c => kebab.push(c),
+        }
+    }
+    kebab
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction};
+    use serde_json::json;
+
+    fn command() -> Command {
+        Command::new("test")
+            .arg(Arg::new("output_path").long("output-path").short('o'))
+            .arg(Arg::new("locales").long("locale").action(ArgAction::Append))
+            .arg(Arg::new("watch").long("watch").action(ArgAction::SetTrue))
+            .arg(Arg::new("max_key_depth").long("max-key-depth"))
+            .arg(
+                Arg::new("namespace_from_path")
+                    .long("namespace-from-path")
+                    .num_args(0..=1)
+                    .default_missing_value("{dir}"),
+            )
+    }
+
+    #[test]
+    fn test_to_args() {
+        let options = json!({
+            "outputPath": "messages/{locale}.json",
+            "locale": ["en", "de"],
+            "watch": true,
+            "max_key_depth": 3
+        });
+        let args = to_args(
+            &command(),
+            &command(),
+            options.as_object().unwrap(),
+            &HashSet::new(),
+            "the config file",
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "--locale=en",
+                "--locale=de",
+                "--max-key-depth=3",
+                "--output-path=messages/{locale}.json",
+                "--watch"
+            ]
+        );
+
+        // Options on the command line take precedence
+        let given = given_options(&command(), &["-o".to_string(), "out.json".to_string()]);
+        let args = to_args(
+            &command(),
+            &command(),
+            options.as_object().unwrap(),
+            &given,
+            "the config file",
+        )
+        .unwrap();
+        assert!(!args.iter().any(|arg| arg.starts_with("--output-path")));
+
+        // Options of another subcommand are left out
+        let stats = Command::new("stats").arg(Arg::new("output_path").long("output-path"));
+        let args = to_args(
+            &command(),
+            &stats,
+            options.as_object().unwrap(),
+            &HashSet::new(),
+            "the config file",
+        )
+        .unwrap();
+        assert_eq!(args, ["--output-path=messages/{locale}.json"]);
+
+        let options = json!({ "outputDir": "messages" });
+        assert!(to_args(
+            &command(),
+            &command(),
+            options.as_object().unwrap(),
+            &HashSet::new(),
+            "the config file"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_optional_values() {
+        let args = |options: Value| {
+            to_args(
+                &command(),
+                &command(),
+                options.as_object().unwrap(),
+                &HashSet::new(),
+                "the config file",
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            args(json!({ "namespaceFromPath": true })),
+            ["--namespace-from-path"]
+        );
+        assert!(args(json!({ "namespaceFromPath": false })).is_empty());
+        assert_eq!(
+            args(json!({ "namespaceFromPath": "{file}" })),
+            ["--namespace-from-path={file}"]
+        );
+    }
+
+    #[test]
+    fn test_env_options() {
+        let options = env_options([
+            (
+                "NEXT_INTL_EXTRACTOR_OUTPUT_PATH".to_string(),
+                "messages.json".to_string(),
+            ),
+            ("NEXT_INTL_EXTRACTOR_WATCH".to_string(), "1".to_string()),
+            (
+                "NEXT_INTL_EXTRACTOR_LOCALE".to_string(),
+                "en,de".to_string(),
+            ),
+            ("HOME".to_string(), "/root".to_string()),
+        ]);
+        assert_eq!(
+            Value::Object(options.clone()),
+            json!({ "output_path": "messages.json", "watch": "1", "locale": "en,de" })
+        );
+        assert_eq!(
+            to_args(
+                &command(),
+                &command(),
+                &options,
+                &HashSet::new(),
+                "the environment"
+            )
+            .unwrap(),
+            ["--locale=en,de", "--output-path=messages.json", "--watch"]
+        );
+
+        let options = env_options([("NEXT_INTL_EXTRACTOR_WATCH".to_string(), "yes".to_string())]);
+        assert!(to_args(
+            &command(),
+            &command(),
+            &options,
+            &HashSet::new(),
+            "the environment"
+        )
+        .is_err());
+
+        // `true` of an option with an optional value is the option without one
+        for (value, expected) in [
+            ("true", vec!["--namespace-from-path"]),
+            ("1", vec!["--namespace-from-path"]),
+            ("false", vec![]),
+            ("{file}", vec!["--namespace-from-path={file}"]),
+        ] {
+            let options = env_options([(
+                "NEXT_INTL_EXTRACTOR_NAMESPACE_FROM_PATH".to_string(),
+                value.to_string(),
+            )]);
+            assert_eq!(
+                to_args(
+                    &command(),
+                    &command(),
+                    &options,
+                    &HashSet::new(),
+                    "the environment"
+                )
+                .unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_by_package() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let package = dir.path().join("packages/web");
+        fs::create_dir_all(package.join("app")).unwrap();
+        fs::write(package.join("next-intl-extractor.config.json"), "{}").unwrap();
+
+        let files = vec![
+            dir.path().join("app/page.tsx"),
+            package.join("app/page.tsx"),
+            package.join("page.tsx"),
+        ];
+        let (own, packages) = group_by_package(files);
+        assert_eq!(own, vec![dir.path().join("app/page.tsx")]);
+        assert_eq!(
+            packages,
+            BTreeMap::from([(
+                package.join("next-intl-extractor.config.json"),
+                vec![package.join("app/page.tsx"), package.join("page.tsx")]
+            )])
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(find(dir.path()), None);
+
+        let path = dir.path().join("next-intl-extractor.config.toml");
+        fs::write(
+            &path,
+            "output-path = \"messages.json\"\nlocale = [\"en\"]\n",
+        )
+        .unwrap();
+        assert_eq!(find(dir.path()), Some(path.clone()));
+        assert_eq!(
+            Value::Object(load(&path).unwrap()),
+            json!({ "output-path": "messages.json", "locale": ["en"] })
+        );
+
+        let path = dir.path().join("next-intl-extractor.config.json");
+        fs::write(&path, r#"{ "watch": true }"#).unwrap();
+        assert_eq!(find(dir.path()), Some(path.clone()));
+        assert_eq!(
+            Value::Object(load(&path).unwrap()),
+            json!({ "watch": true })
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How many strings of the files are translated and how many are hard-coded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counts {
+    /// Usages of static keys, e.g. `t("title")`
+    pub translated: usize,
+    /// User-visible strings in JSX that are not translated
+    pub hardcoded: usize,
+}
+
+impl Counts {
+    /// Percentage of the strings that are translated, `None` without any strings
+    pub fn ratio(&self) -> Option<f64> {
+        let total = self.translated + self.hardcoded;
+        (total > 0).then(|| self.translated as f64 * 100.0 / total as f64)
+    }
+
+    fn add(&mut self, other: Counts) {
+        self.translated += other.translated;
+        self.hardcoded += other.hardcoded;
+    }
+}
+
+/// Translated and hard-coded strings by directory, as a measure of how far the i18n got
+#[derive(Debug, Default)]
+pub struct Coverage {
+    directories: BTreeMap<String, Counts>,
+}
+
+impl Coverage {
+    /// Add the strings of a file to its directory
+    pub fn add(&mut self, file: &Path, counts: Counts) {
+        let directory = match file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.display().to_string(),
+            _ => ".".to_string(),
+        };
+        self.directories.entry(directory).or_default().add(counts);
+    }
+
+    pub fn total(&self) -> Counts {
+        let mut total = Counts::default();
+        for counts in self.directories.values() {
+            total.add(*counts);
+        }
+        total
+    }
+
+    /// A line per directory with strings, e.g. `app/cart: 12 translated, 3 hard-coded (80.0%)`,
+    /// and one with the total
+    pub fn to_text(&self) -> String {
+        let line = |name: &str, counts: Counts| {
+            let ratio = counts
+                .ratio()
+                .map_or("-".to_string(), |ratio| format!("{ratio:.1}%"));
+            format!(
+                "{name}: {} translated, {} hard-coded ({ratio})\n",
+                counts.translated, counts.hardcoded
+            )
+        };
+        let mut text = String::from("Coverage by directory\n");
+        for (directory, counts) in &self.directories {
+            if counts.ratio().is_some() {
+                text += &line(&format!("  {directory}"), *counts);
+            }
+        }
+        text + &line("Total", self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage() {
+        let mut coverage = Coverage::default();
+        let counts = |translated, hardcoded| Counts {
+            translated,
+            hardcoded,
+        };
+        coverage.add(Path::new("app/cart/page.tsx"), counts(3, 1));
+        coverage.add(Path::new("app/cart/total.tsx"), counts(1, 0));
+        coverage.add(Path::new("app/layout.tsx"), counts(0, 2));
+        coverage.add(Path::new("middleware.ts"), counts(0, 0));
+
+        assert_eq!(coverage.total(), counts(4, 3));
+        assert_eq!(counts(0, 0).ratio(), None);
+        assert_eq!(
+            coverage.to_text(),
+            "Coverage by directory\n\
+             \x20 app: 0 translated, 2 hard-coded (0.0%)\n\
+             \x20 app/cart: 4 translated, 1 hard-coded (80.0%)\n\
+             Total: 4 translated, 3 hard-coded (57.1%)\n"
+        );
+    }
+}
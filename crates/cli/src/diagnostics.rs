@@ -0,0 +1,304 @@
+use next_intl_resolver::hardcoded::{HardcodedString, HardcodedStringKind};
+use next_intl_resolver::location::Location;
+use next_intl_resolver::misuse::ImportMisuse;
+use next_intl_resolver::provider::ProvidedMessages;
+use next_intl_resolver::{CatalogUsage, DynamicKey, ParseError};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::icu::IcuError;
+use crate::messages::{PlaceholderMismatch, PolicyViolation};
+use crate::ownership::Violation;
+use crate::schema::SchemaError;
+
+/// Log a warning for every syntax error found in a file
+pub fn warn_parse_errors(file_path: &Path, parse_errors: &[ParseError]) {
+    for parse_error in parse_errors {
+        match &parse_error.location {
+            Some(location) => warn!(
+                "Syntax error: {} at {}:{}",
+                parse_error.message,
+                file_path.display(),
+                location
+            ),
+            None => warn!(
+                "Syntax error: {} in {}",
+                parse_error.message,
+                file_path.display()
+            ),
+        }
+    }
+}
+
+/// Log the messages that are no longer used but kept in a catalog
+pub fn warn_kept_keys(catalog_path: &Path, removed_keys: &[String]) {
+    for key in removed_keys {
+        warn!(
+            "Message '{}' is no longer used but kept in {}",
+            key,
+            catalog_path.display()
+        );
+    }
+}
+
+/// Log the messages that are no longer used and moved below `__obsolete__` in a catalog
+pub fn warn_obsolete_keys(catalog_path: &Path, removed_keys: &[String]) {
+    for key in removed_keys {
+        warn!(
+            "Message '{}' is no longer used and marked obsolete in {}",
+            key,
+            catalog_path.display()
+        );
+    }
+}
+
+/// Log the translations whose message in the default locale changed since they were translated
+pub fn warn_outdated_keys(locale: &str, default_locale: &str, keys: &[String]) {
+    for key in keys {
+        warn!(
+            "Message '{}' changed in {} and its translation in {} may be outdated",
+            key, default_locale, locale
+        );
+    }
+}
+
+/// Log a warning for every dynamic key found in a file
+pub fn warn_dynamic_keys(file_path: &Path, dynamic_keys: &[DynamicKey]) {
+    for dynamic_key in dynamic_keys {
+        warn!(
+            "Dynamic key in namespace '{}' can't be extracted at {}:{}",
+            dynamic_key.namespace,
+            file_path.display(),
+            dynamic_key.location
+        );
+    }
+}
+
+/// Log a warning for every usage of the whole message catalog in a file
+pub fn warn_catalog_usages(file_path: &Path, catalog_usages: &[CatalogUsage]) {
+    for usage in catalog_usages {
+        warn!(
+            "{}() pulls in the whole message catalog, so its messages can't be extracted and all \
+             of them are shipped to client components, at {}:{}",
+            usage.function,
+            file_path.display(),
+            usage.location
+        );
+    }
+}
+
+/// Log a warning for every next-intl function that is imported from the wrong module or used
+/// where it fails at runtime
+pub fn warn_import_misuses(file_path: &Path, import_misuses: &[ImportMisuse]) {
+    for misuse in import_misuses {
+        warn!(
+            "{} at {}:{}",
+            misuse.message,
+            file_path.display(),
+            misuse.location
+        );
+    }
+}
+
+/// Log the ways the messages of a catalog don't match the schema of the extracted messages
+pub fn warn_schema_errors(catalog_path: &Path, errors: &[SchemaError]) {
+    for error in errors {
+        match error {
+            SchemaError::TypeMismatch {
+                key,
+                expected,
+                found,
+            } => warn!(
+                "Message '{}' is a {} but should be a {} in {}",
+                key,
+                found,
+                expected,
+                catalog_path.display()
+            ),
+            SchemaError::UnexpectedKey { key } => warn!(
+                "Message '{}' is not used in the code but is in {}",
+                key,
+                catalog_path.display()
+            ),
+            SchemaError::MissingKey { key } => warn!(
+                "Message '{}' is used in the code but missing in {}",
+                key,
+                catalog_path.display()
+            ),
+        }
+    }
+}
+
+/// Log the messages of a catalog that are not valid ICU messages, which fail at runtime
+pub fn warn_icu_errors(catalog_path: &Path, errors: &[IcuError]) {
+    for error in errors {
+        warn!(
+            "Message '{}' in {} is not a valid ICU message: {}",
+            error.key,
+            catalog_path.display(),
+            error
+        );
+    }
+}
+
+/// Log the messages of a catalog that use other values than the code passes to them
+pub fn warn_placeholder_mismatches(catalog_path: &Path, mismatches: &[PlaceholderMismatch]) {
+    for mismatch in mismatches {
+        if !mismatch.missing.is_empty() {
+            warn!(
+                "Message '{}' in {} uses {} but {} doesn't pass it",
+                mismatch.key,
+                catalog_path.display(),
+                mismatch.missing.join(", "),
+                mismatch.usage
+            );
+        }
+        if !mismatch.extra.is_empty() {
+            warn!(
+                "{} passes {} to '{}' but the message in {} doesn't use it",
+                mismatch.usage,
+                mismatch.extra.join(", "),
+                mismatch.key,
+                catalog_path.display()
+            );
+        }
+    }
+}
+
+/// Log a warning for every extracted message whose key breaks the key policy
+pub fn warn_policy_violations(violations: &[PolicyViolation]) {
+    for violation in violations {
+        let full_key = if violation.namespace.is_empty() {
+            violation.key.clone()
+        } else {
+            format!("{}.{}", violation.namespace, violation.key)
+        };
+        warn!(
+            "Key '{}' {} at {}",
+            full_key, violation.problem, violation.usage
+        );
+    }
+}
+
+/// Log a warning for every namespace a file uses without owning it
+pub fn warn_ownership_violations(file_path: &Path, violations: &[Violation]) {
+    for violation in violations {
+        warn!(
+            "Namespace '{}' is used in {}, but {} may only be used in {}",
+            violation.namespace,
+            file_path.display(),
+            violation.rule,
+            violation.paths.join(", ")
+        );
+    }
+}
+
+/// Log a warning for every translator whose namespace can't be resolved in a file
+pub fn warn_unresolved_namespaces(file_path: &Path, unresolved_namespaces: &[Location]) {
+    for location in unresolved_namespaces {
+        warn!(
+            "Could not find namespace for translations call, its keys can't be extracted at {}:{}",
+            file_path.display(),
+            location
+        );
+    }
+}
+
+/// Log a warning for every hard-coded string found in a file
+pub fn warn_hardcoded_strings(file_path: &Path, hardcoded_strings: &[HardcodedString]) {
+    for hardcoded_string in hardcoded_strings {
+        let usage = match &hardcoded_string.kind {
+            HardcodedStringKind::JsxText => "JSX text".to_string(),
+            HardcodedStringKind::Attribute(name) => format!("'{}' prop", name),
+        };
+        warn!(
+            "Hard-coded string \"{}\" in {} is not translated at {}:{}",
+            hardcoded_string.text,
+            usage,
+            file_path.display(),
+            hardcoded_string.location
+        );
+    }
+}
+
+/// Whether a namespace is (partly) included in a namespace picked from the messages, e.g.
+/// `Cart.Summary` in `Cart`
+fn overlaps(namespace: &str, picked: &str) -> bool {
+    let is_within = |inner: &str, outer: &str| {
+        inner == outer
+            || inner
+                .strip_prefix(outer)
+                .is_some_and(|rest| rest.starts_with('.'))
+    };
+    is_within(namespace, picked) || is_within(picked, namespace)
+}
+
+/// Compare the namespaces passed to `NextIntlClientProvider` with the ones client components use
+///
+/// Warns about namespaces that are shipped to the client without being used by a client
+/// component, and about namespaces client components use that no provider passes. The latter
+/// are only known if the namespaces of every provider can be determined
+pub fn warn_client_messages(
+    provided_messages: &[(PathBuf, ProvidedMessages)],
+    client_namespaces: &BTreeSet<String>,
+) {
+    if provided_messages.is_empty() {
+        return;
+    }
+
+    let mut all_picked = Vec::new();
+    let mut all_known = true;
+    for (file_path, provided) in provided_messages {
+        let Some(namespaces) = &provided.namespaces else {
+            warn!(
+                "Messages passed to NextIntlClientProvider can't be determined statically at {}:{}, \
+                 use pick() to only ship the namespaces client components use",
+                file_path.display(),
+                provided.location
+            );
+            all_known = false;
+            continue;
+        };
+        for picked in namespaces {
+            if !client_namespaces
+                .iter()
+                .any(|namespace| overlaps(namespace, picked))
+            {
+                warn!(
+                    "Namespace '{}' is shipped to the client but not used by client components at {}:{}",
+                    picked,
+                    file_path.display(),
+                    provided.location
+                );
+            }
+        }
+        all_picked.extend(namespaces);
+    }
+
+    if !all_known {
+        return;
+    }
+    for namespace in client_namespaces {
+        if !all_picked.iter().any(|picked| overlaps(namespace, picked)) {
+            warn!(
+                "Namespace '{}' is used by client components but not passed to NextIntlClientProvider",
+                namespace
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlaps() {
+        assert!(overlaps("Cart", "Cart"));
+        assert!(overlaps("Cart.Summary", "Cart"));
+        assert!(overlaps("Cart", "Cart.Summary"));
+        assert!(!overlaps("CartSummary", "Cart"));
+        assert!(!overlaps("Checkout", "Cart"));
+    }
+}
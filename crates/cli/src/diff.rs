@@ -0,0 +1,408 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::format::CatalogFormat;
+use crate::messages::parse_messages;
+
+/// Unchanged lines around every change of a unified diff
+const CONTEXT: usize = 3;
+
+/// Most line pairs to find the longest common subsequence of, larger changes are diffed as a
+/// replacement of all their lines so the table of the lengths stays small, about 64 MB
+const MAX_COMPARED_LINES: usize = 1 << 24;
+
+/// A file that would be written with other content than it has
+#[derive(Debug, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    /// Content of the file, `None` if it doesn't exist yet
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl FileChange {
+    /// How many lines would be added and removed, e.g. `messages/en.json: +2 -1 lines`
+    pub fn summary(&self) -> String {
+        let Some(old) = &self.old else {
+            return format!("{}: would be created", self.path.display());
+        };
+        let (mut added, mut removed) = (0, 0);
+        for line in lines(old, &self.new) {
+            match line {
+                Line::Added(_) => added += 1,
+                Line::Removed(_) => removed += 1,
+                Line::Same(_) => {}
+            }
+        }
+        format!("{}: +{added} -{removed} lines", self.path.display())
+    }
+
+    /// The messages that would be added, removed or changed, `None` if the content can't be
+    /// parsed as a catalog
+    pub fn key_diff(&self) -> Option<KeyDiff> {
+        let format = CatalogFormat::from_path(&self.path).ok()?;
+        let old = match &self.old {
+            Some(old) => parse_messages(&self.path, old, format).ok()?,
+            None => Map::new(),
+        };
+        let new = parse_messages(&self.path, &self.new, format).ok()?;
+        Some(KeyDiff::new(&old, &new))
+    }
+
+    /// The change as a unified diff like the one of `diff -u`, with ANSI colors if asked to
+    pub fn unified(&self, color: bool) -> String {
+        let paint = |code: &str, text: String| {
+            if color {
+                format!("\x1b[{code}m{text}\x1b[0m")
+            } else {
+                text
+            }
+        };
+        let old_name = match self.old {
+            Some(_) => format!("a/{}", self.path.display()),
+            None => "/dev/null".to_string(),
+        };
+        let mut unified = paint("1", format!("--- {old_name}\n"));
+        unified += &paint("1", format!("+++ b/{}\n", self.path.display()));
+
+        let diff = lines(self.old.as_deref().unwrap_or_default(), &self.new);
+        let changed: Vec<usize> = (0..diff.len())
+            .filter(|&index| !matches!(diff[index], Line::Same(_)))
+            .collect();
+        let mut next = 0;
+        while next < changed.len() {
+            // Changes with no more than twice the context between them share a hunk
+            let start = changed[next].saturating_sub(CONTEXT);
+            let mut end = changed[next] + 1;
+            next += 1;
+            while next < changed.len() && changed[next] - end <= 2 * CONTEXT {
+                end = changed[next] + 1;
+                next += 1;
+            }
+            let end = (end + CONTEXT).min(diff.len());
+
+            let old_start = 1 + diff[..start]
+                .iter()
+                .filter(|line| !matches!(line, Line::Added(_)))
+                .count();
+            let new_start = 1 + diff[..start]
+                .iter()
+                .filter(|line| !matches!(line, Line::Removed(_)))
+                .count();
+            let hunk = &diff[start..end];
+            let old_len = hunk
+                .iter()
+                .filter(|line| !matches!(line, Line::Added(_)))
+                .count();
+            let new_len = hunk
+                .iter()
+                .filter(|line| !matches!(line, Line::Removed(_)))
+                .count();
+            unified += &paint(
+                "36",
+                format!(
+                    "@@ -{} +{} @@\n",
+                    hunk_range(old_start, old_len),
+                    hunk_range(new_start, new_len)
+                ),
+            );
+            for line in hunk {
+                match line {
+                    Line::Same(text) => unified += &format!(" {text}\n"),
+                    Line::Removed(text) => unified += &paint("31", format!("-{text}\n")),
+                    Line::Added(text) => unified += &paint("32", format!("+{text}\n")),
+                }
+            }
+        }
+        unified
+    }
+}
+
+/// The lines of a hunk, e.g. `3,4`, an empty hunk starts at the line before it
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start - 1),
+        1 => start.to_string(),
+        len => format!("{start},{len}"),
+    }
+}
+
+/// A line of a diff between an old and a new text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines of the old and the new text, with the longest common subsequence kept
+///
+/// Lines of a change are mostly in one place of a catalog, so only the lines between the
+/// common start and end are compared with each other. If there are too many, they're all
+/// removed and added instead, see [`MAX_COMPARED_LINES`]
+pub fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let start = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let end = old[start..]
+        .iter()
+        .rev()
+        .zip(new[start..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[start..old.len() - end];
+    let new_middle = &new[start..new.len() - end];
+
+    let mut diff: Vec<Line> = old[..start].iter().map(|line| Line::Same(line)).collect();
+    if (old_middle.len() + 1) * (new_middle.len() + 1) > MAX_COMPARED_LINES {
+        diff.extend(old_middle.iter().map(|line| Line::Removed(line)));
+        diff.extend(new_middle.iter().map(|line| Line::Added(line)));
+        diff.extend(old[old.len() - end..].iter().map(|line| Line::Same(line)));
+        return diff;
+    }
+
+    // Length of the longest common subsequence of the rest of both, by position
+    let width = new_middle.len() + 1;
+    let mut common = vec![0u32; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            common[i * width + j] = if old_middle[i] == new_middle[j] {
+                common[(i + 1) * width + j + 1] + 1
+            } else {
+                common[(i + 1) * width + j].max(common[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() && j < new_middle.len() {
+        if old_middle[i] == new_middle[j] {
+            diff.push(Line::Same(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if common[(i + 1) * width + j] >= common[i * width + j + 1] {
+            diff.push(Line::Removed(old_middle[i]));
+            i += 1;
+        } else {
+            diff.push(Line::Added(new_middle[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old_middle[i..].iter().map(|line| Line::Removed(line)));
+    diff.extend(new_middle[j..].iter().map(|line| Line::Added(line)));
+    diff.extend(old[old.len() - end..].iter().map(|line| Line::Same(line)));
+    diff
+}
+
+/// The messages that were added, removed or changed between two catalogs, by full key
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct KeyDiff {
+    pub added: BTreeMap<String, Value>,
+    pub removed: BTreeMap<String, Value>,
+    /// The old and the new value
+    pub changed: BTreeMap<String, (Value, Value)>,
+}
+
+impl KeyDiff {
+    /// Compare the messages of two catalogs
+    pub fn new(old: &Map<String, Value>, new: &Map<String, Value>) -> Self {
+        let mut old_messages = BTreeMap::new();
+        collect_messages(old, "", &mut old_messages);
+        let mut new_messages = BTreeMap::new();
+        collect_messages(new, "", &mut new_messages);
+
+        let mut diff = Self::default();
+        for (key, old_value) in old_messages {
+            match new_messages.remove(&key) {
+                Some(new_value) if new_value != old_value => {
+                    diff.changed.insert(key, (old_value, new_value));
+                }
+                Some(_) => {}
+                None => {
+                    diff.removed.insert(key, old_value);
+                }
+            }
+        }
+        diff.added = new_messages;
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The diff for tooling, e.g. `{"added": {"Cart.total": "Total"}, "removed": {},
+    /// "changed": {"Cart.title": {"from": "Cart", "to": "Your cart"}}}`
+    pub fn to_json(&self) -> Value {
+        let changed: Map<String, Value> = self
+            .changed
+            .iter()
+            .map(|(key, (from, to))| (key.clone(), json!({ "from": from, "to": to })))
+            .collect();
+        json!({
+            "added": self.added,
+            "removed": self.removed,
+            "changed": changed,
+        })
+    }
+
+    /// A line per message, e.g. `+ Cart.total: "Total"`, and a line with the counts
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (key, value) in &self.added {
+            text += &format!("+ {key}: {value}\n");
+        }
+        for (key, value) in &self.removed {
+            text += &format!("- {key}: {value}\n");
+        }
+        for (key, (from, to)) in &self.changed {
+            text += &format!("~ {key}: {from} -> {to}\n");
+        }
+        text += &format!(
+            "{} added, {} removed, {} changed\n",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        );
+        text
+    }
+}
+
+/// The messages below objects by full key, e.g. `Cart.title`
+fn collect_messages(
+    messages: &Map<String, Value>,
+    prefix: &str,
+    all: &mut BTreeMap<String, Value>,
+) {
+    for (key, value) in messages {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Object(nested) => collect_messages(nested, &full_key, all),
+            value => {
+                all.insert(full_key, value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let old = "{\n  \"a\": \"A\",\n  \"b\": \"B\"\n}";
+        let new = "{\n  \"a\": \"A\",\n  \"c\": \"C\",\n  \"b\": \"B!\"\n}";
+        assert_eq!(
+            lines(old, new),
+            vec![
+                Line::Same("{"),
+                Line::Same("  \"a\": \"A\","),
+                Line::Removed("  \"b\": \"B\""),
+                Line::Added("  \"c\": \"C\","),
+                Line::Added("  \"b\": \"B!\""),
+                Line::Same("}"),
+            ]
+        );
+        assert!(lines(old, old)
+            .iter()
+            .all(|line| matches!(line, Line::Same(_))));
+
+        // Too many changed lines to compare are replaced as a whole
+        let old: String = (0..5000).map(|n| format!("{n}\n")).collect();
+        let new: String = (0..5000).map(|n| format!("{n}!\n")).collect();
+        let diff = lines(&old, &new);
+        assert_eq!(diff.len(), 10000);
+        assert_eq!(diff[4999], Line::Removed("4999"));
+        assert_eq!(diff[5000], Line::Added("0!"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let change = FileChange {
+            path: PathBuf::from("messages/en.json"),
+            old: Some("{\n  \"a\": \"A\"\n}\n".to_string()),
+            new: "{\n  \"a\": \"A\",\n  \"b\": \"B\"\n}\n".to_string(),
+        };
+        assert_eq!(change.summary(), "messages/en.json: +2 -1 lines");
+
+        let change = FileChange {
+            old: None,
+            ..change
+        };
+        assert_eq!(change.summary(), "messages/en.json: would be created");
+    }
+
+    #[test]
+    fn test_key_diff_of_change() {
+        let change = FileChange {
+            path: PathBuf::from("messages/en.json"),
+            old: Some(r#"{ "Cart": { "title": "Cart", "old": "Old" } }"#.to_string()),
+            new: r#"{ "Cart": { "title": "Cart", "total": "Cart.total" } }"#.to_string(),
+        };
+        let diff = change.key_diff().unwrap();
+        assert_eq!(diff.added.keys().collect::<Vec<_>>(), ["Cart.total"]);
+        assert_eq!(diff.removed.keys().collect::<Vec<_>>(), ["Cart.old"]);
+        assert!(diff.changed.is_empty());
+
+        let change = FileChange {
+            old: None,
+            ..change
+        };
+        assert_eq!(change.key_diff().unwrap().added.len(), 2);
+    }
+
+    #[test]
+    fn test_unified() {
+        let old: String = (1..=10).map(|n| format!("{n}\n")).collect();
+        let new = old.replace("2\n", "two\n").replace("10\n", "10\n11\n");
+        let change = FileChange {
+            path: PathBuf::from("en.json"),
+            old: Some(old),
+            new,
+        };
+        assert_eq!(
+            change.unified(false),
+            "--- a/en.json\n+++ b/en.json\n\
+             @@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n\
+             @@ -8,3 +8,4 @@\n 8\n 9\n 10\n+11\n"
+        );
+        assert!(change.unified(true).contains("\x1b[31m-2\n\x1b[0m"));
+
+        let change = FileChange {
+            old: None,
+            new: "{}\n".to_string(),
+            ..change
+        };
+        assert_eq!(
+            change.unified(false),
+            "--- /dev/null\n+++ b/en.json\n@@ -0,0 +1 @@\n+{}\n"
+        );
+    }
+
+    #[test]
+    fn test_key_diff() {
+        let old = json!({ "Cart": { "title": "Cart", "old": "Old" }, "Nav": { "home": "Home" } });
+        let new = json!({ "Cart": { "title": "Your cart", "total": "Total" }, "Nav": { "home": "Home" } });
+        let diff = KeyDiff::new(old.as_object().unwrap(), new.as_object().unwrap());
+        assert_eq!(
+            diff.to_json(),
+            json!({
+                "added": { "Cart.total": "Total" },
+                "removed": { "Cart.old": "Old" },
+                "changed": { "Cart.title": { "from": "Cart", "to": "Your cart" } }
+            })
+        );
+        assert_eq!(
+            diff.to_text(),
+            "+ Cart.total: \"Total\"\n- Cart.old: \"Old\"\n~ Cart.title: \"Cart\" -> \"Your cart\"\n\
+             1 added, 1 removed, 1 changed\n"
+        );
+        assert!(KeyDiff::new(old.as_object().unwrap(), old.as_object().unwrap()).is_empty());
+    }
+}
@@ -1,19 +1,232 @@
 use anyhow::Result;
-use glob::glob;
-use std::path::PathBuf;
+use glob::Pattern;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
 use tracing::info;
 
-/// Find all files that match a glob pattern
-pub fn find_files(glob_pattern: &str) -> Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
+/// Pattern for the source files that are scanned by default, both TypeScript and JavaScript
+pub const DEFAULT_PATTERN: &str = "**/*.{tsx,ts,jsx,js,mjs,cjs}";
+
+/// Directories that never contain source files to scan, e.g. dependencies and build output
+const IGNORED_DIRECTORIES: [&str; 2] = ["node_modules", ".next"];
+
+/// Expand `{a,b}` alternatives in a glob pattern, which the glob crate doesn't support
+///
+/// e.g. `**/*.{tsx,ts}` expands to `**/*.tsx` and `**/*.ts`
+pub fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(start) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+    let Some(end) = pattern[start..].find('}').map(|end| start + end) else {
+        return vec![pattern.to_string()];
+    };
+
+    let (prefix, suffix) = (&pattern[..start], &pattern[end + 1..]);
+    pattern[start + 1..end]
+        .split(',')
+        .flat_map(|alternative| expand_braces(&format!("{prefix}{alternative}{suffix}")))
+        .collect()
+}
+
+/// Whether a path is inside one of the [`IGNORED_DIRECTORIES`]
+fn is_ignored(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        Component::Normal(name) => IGNORED_DIRECTORIES.iter().any(|dir| name == *dir),
+        _ => false,
+    })
+}
+
+fn compile(pattern: &str) -> Result<Vec<Pattern>> {
+    Ok(expand_braces(pattern)
+        .iter()
+        .map(|pattern| Pattern::new(pattern))
+        .collect::<Result<_, _>>()?)
+}
+
+/// A glob pattern that supports `{a,b}` alternatives
+#[derive(Debug)]
+pub struct FilePattern {
+    patterns: Vec<Pattern>,
+    /// Patterns of the files that are left out even though they match, e.g. `**/*.test.tsx`
+    ignored: Vec<Pattern>,
+}
+
+impl FilePattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        Ok(Self {
+            patterns: compile(pattern)?,
+            ignored: Vec::new(),
+        })
+    }
+
+    /// Leave out the files that match any of these patterns too
+    pub fn with_ignored(mut self, ignored: &[String]) -> Result<Self> {
+        for pattern in ignored {
+            self.ignored.extend(compile(pattern)?);
+        }
+        Ok(self)
+    }
 
-    info!("Finding files with pattern: {:?}", glob(glob_pattern)?);
+    pub fn matches_path(&self, path: &Path) -> bool {
+        !is_ignored(path)
+            && self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.matches_path(path))
+            && !self
+                .ignored
+                .iter()
+                .any(|pattern| pattern.matches_path(path))
+    }
+}
+
+/// The directory below which all the patterns match, e.g. `src` for `src/**/*.tsx` and
+/// `src/app/*.ts`, empty for the current directory
+fn base_directory(patterns: &[String]) -> PathBuf {
+    let mut base: Option<Vec<&str>> = None;
+    for pattern in patterns {
+        let mut segments: Vec<&str> = pattern.split('/').collect();
+        // The last segment is the file name, even without wildcards
+        segments.pop();
+        let literal = segments
+            .iter()
+            .take_while(|segment| !segment.contains(['*', '?', '[']))
+            .count();
+        segments.truncate(literal);
+        base = Some(match base {
+            Some(base) => base
+                .into_iter()
+                .zip(segments)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+            None => segments,
+        });
+    }
+    match base.unwrap_or_default().join("/") {
+        base if base.is_empty() && patterns.iter().all(|p| p.starts_with('/')) => "/".into(),
+        base => base.into(),
+    }
+}
 
-    for entry in glob(glob_pattern)?.flatten() {
-        if entry.is_file() {
-            files.push(entry);
+/// Collect the files below a directory that match the patterns, without descending into the
+/// [`IGNORED_DIRECTORIES`] or following links to directories
+fn walk(dir: &Path, pattern: &FilePattern, files: &mut Vec<PathBuf>) -> Result<()> {
+    let read_from = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    // Like a glob, a directory that doesn't exist has no files
+    let Ok(entries) = fs::read_dir(read_from) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = dir.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            if !is_ignored(Path::new(&entry.file_name())) {
+                walk(&path, pattern, files)?;
+            }
+        } else if path.is_file() && pattern.matches_path(&path) {
+            files.push(path);
         }
     }
+    Ok(())
+}
+
+/// Find all files that match a glob pattern, except the ones that match an ignored pattern
+///
+/// The tree below the directory the alternatives of the pattern share is walked once
+pub fn find_files(glob_pattern: &str, ignored: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    info!("Finding files with pattern: {:?}", glob_pattern);
+
+    let pattern = FilePattern::new(glob_pattern)?.with_ignored(ignored)?;
+    walk(
+        &base_directory(&expand_braces(glob_pattern)),
+        &pattern,
+        &mut files,
+    )?;
+    files.sort();
 
     Ok(files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("**/*.tsx"), vec!["**/*.tsx"]);
+        assert_eq!(expand_braces("**/*.{tsx,ts}"), vec!["**/*.tsx", "**/*.ts"]);
+        assert_eq!(
+            expand_braces("{app,src}/**/*.{js,jsx}"),
+            vec!["app/**/*.js", "app/**/*.jsx", "src/**/*.js", "src/**/*.jsx"]
+        );
+    }
+
+    #[test]
+    fn test_base_directory() {
+        let base = |pattern: &str| base_directory(&expand_braces(pattern));
+        assert_eq!(base("**/*.tsx"), PathBuf::new());
+        assert_eq!(base("src/**/*.{ts,tsx}"), PathBuf::from("src"));
+        assert_eq!(base("src/{app,lib}/*.ts"), PathBuf::from("src"));
+        assert_eq!(base("/tmp/app/page.tsx"), PathBuf::from("/tmp/app"));
+        assert_eq!(base("/**/*.ts"), PathBuf::from("/"));
+    }
+
+    #[test]
+    fn test_find_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        for file in [
+            "app/page.tsx",
+            "app/layout.jsx",
+            "lib/utils.mjs",
+            "lib/config.cjs",
+            "lib/styles.css",
+            "node_modules/next-intl/index.js",
+            ".next/server/page.js",
+        ] {
+            let path = temp_dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, "")?;
+        }
+
+        let pattern = format!("{}/{}", temp_dir.path().display(), DEFAULT_PATTERN);
+        let files = find_files(&pattern, &[])?;
+        let files: Vec<_> = files
+            .iter()
+            .map(|file| file.strip_prefix(temp_dir.path()).unwrap())
+            .collect();
+        assert_eq!(
+            files,
+            [
+                "app/layout.jsx",
+                "app/page.tsx",
+                "lib/config.cjs",
+                "lib/utils.mjs"
+            ]
+            .map(Path::new)
+        );
+
+        let pattern = FilePattern::new(DEFAULT_PATTERN)?;
+        assert!(pattern.matches_path(Path::new("app/page.js")));
+        assert!(!pattern.matches_path(Path::new("node_modules/next-intl/index.js")));
+        assert!(!pattern.matches_path(Path::new("app/styles.css")));
+
+        let ignored = ["**/*.cjs".to_string(), "**/app/*.jsx".to_string()];
+        let pattern = format!("{}/{}", temp_dir.path().display(), DEFAULT_PATTERN);
+        let files = find_files(&pattern, &ignored)?;
+        assert_eq!(files.len(), 2);
+        let pattern = FilePattern::new(DEFAULT_PATTERN)?.with_ignored(&ignored)?;
+        assert!(!pattern.matches_path(Path::new("app/layout.jsx")));
+        assert!(pattern.matches_path(Path::new("app/page.tsx")));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
+use std::path::Path;
+
+use crate::jsonc::Comments;
+use crate::order::OrderedValue;
+
+/// The indentation used when there is no existing file to take it from
+const DEFAULT_INDENT: &str = "  ";
+
+/// The file format of a message catalog, by default chosen by the extension of its path
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl CatalogFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(Self::Json),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            Some("toml") => Ok(Self::Toml),
+            _ => Err(anyhow!(
+                "Unsupported messages file {}, use a .json, .yaml, .yml or .toml file or --format",
+                path.display()
+            )),
+        }
+    }
+
+    /// Content of a catalog without messages
+    pub fn empty(&self) -> &'static str {
+        match self {
+            Self::Json => "{}",
+            Self::Yaml => "{}\n",
+            Self::Toml => "",
+        }
+    }
+}
+
+/// The line ending of a written file
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// Formatting of the written catalogs that replaces the one of the existing file, e.g. to
+/// follow the Prettier or editorconfig settings of the project
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatOverrides {
+    pub indent: Option<String>,
+    pub line_ending: Option<LineEnding>,
+    pub final_newline: Option<bool>,
+    /// Escape the non-ASCII characters of JSON catalogs, e.g. `\u00e9` for `é`
+    pub escape_unicode: bool,
+}
+
+impl FormatOverrides {
+    pub fn apply(&self, format: &mut JsonFormat) {
+        if let Some(indent) = &self.indent {
+            format.indent = indent.clone();
+        }
+        if let Some(line_ending) = self.line_ending {
+            format.line_ending = line_ending;
+        }
+        if let Some(final_newline) = self.final_newline {
+            format.trailing_newline = final_newline;
+        }
+        if self.escape_unicode {
+            format.escape_unicode = true;
+        }
+    }
+}
+
+/// Parse an indentation argument, a number of spaces or `tab`
+pub fn parse_indent(value: &str) -> Result<String, String> {
+    match value {
+        "tab" => Ok("\t".to_string()),
+        spaces => spaces
+            .parse::<usize>()
+            .map(|count| " ".repeat(count))
+            .map_err(|_| format!("'{value}' is not a number of spaces or 'tab'")),
+    }
+}
+
+/// How an existing messages file is formatted, so it can be rewritten the same way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonFormat {
+    /// Indentation of one level, e.g. two spaces or a tab
+    pub indent: String,
+    pub trailing_newline: bool,
+    pub line_ending: LineEnding,
+    /// Write non-ASCII characters as `\uXXXX` escapes rather than UTF-8
+    pub escape_unicode: bool,
+}
+
+impl Default for JsonFormat {
+    fn default() -> Self {
+        Self {
+            indent: DEFAULT_INDENT.to_string(),
+            trailing_newline: false,
+            line_ending: LineEnding::default(),
+            escape_unicode: false,
+        }
+    }
+}
+
+impl JsonFormat {
+    /// Detect the format from the content of a file, falling back to the defaults for the parts
+    /// that can't be detected, e.g. the indentation of `{}`
+    pub fn detect(content: &str) -> Self {
+        let indent = content
+            .lines()
+            .skip(1)
+            .map(|line| {
+                let content_start = line.len() - line.trim_start().len();
+                &line[..content_start]
+            })
+            .find(|indent| !indent.is_empty())
+            .unwrap_or(DEFAULT_INDENT);
+        let line_ending = if content.contains("\r\n") {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        };
+        Self {
+            indent: indent.to_string(),
+            trailing_newline: content.ends_with('\n'),
+            line_ending,
+            escape_unicode: false,
+        }
+    }
+
+    /// Set whether the output ends with a newline and convert its line endings, for output
+    /// that isn't serialized by this format, e.g. YAML
+    pub fn finish(&self, output: String) -> String {
+        let mut output = output.trim_end_matches(['\r', '\n']).to_string();
+        if self.trailing_newline {
+            output.push('\n');
+        }
+        match self.line_ending {
+            LineEnding::Lf => output.replace("\r\n", "\n"),
+            LineEnding::Crlf => output.replace("\r\n", "\n").replace('\n', "\r\n"),
+        }
+    }
+
+    pub fn to_string<T: Serialize>(&self, value: &T) -> Result<String> {
+        let mut output = self.pretty(value)?;
+        if self.trailing_newline {
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    /// Like `to_string`, with the comments of the existing file around the keys they belong to
+    pub fn to_string_with_comments(
+        &self,
+        value: &OrderedValue,
+        comments: &Comments,
+    ) -> Result<String> {
+        let mut output = String::new();
+        let root = comments.get(&[]);
+        for comment in root.map(|root| root.leading.as_slice()).unwrap_or_default() {
+            output.push_str(comment);
+            output.push('\n');
+        }
+        self.write_value(&mut output, value, &mut Vec::new(), comments)?;
+        if let Some(trailing) = root.and_then(|root| root.trailing.as_ref()) {
+            output.push(' ');
+            output.push_str(trailing);
+        }
+        for comment in &comments.footer {
+            output.push('\n');
+            output.push_str(comment);
+        }
+        if self.trailing_newline {
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    fn write_value(
+        &self,
+        output: &mut String,
+        value: &OrderedValue,
+        path: &mut Vec<String>,
+        comments: &Comments,
+    ) -> Result<()> {
+        let indent = self.indent.repeat(path.len());
+        let entries = match value {
+            OrderedValue::Value(value) => {
+                let json = self.pretty(value)?;
+                output.push_str(&json.replace('\n', &format!("\n{indent}")));
+                return Ok(());
+            }
+            OrderedValue::Object(entries) => entries,
+        };
+        let end = comments
+            .get(path)
+            .map(|comments| comments.end.as_slice())
+            .unwrap_or_default();
+        if entries.is_empty() && end.is_empty() {
+            output.push_str("{}");
+            return Ok(());
+        }
+
+        let inner = format!("{indent}{}", self.indent);
+        output.push('{');
+        for (index, (key, value)) in entries.iter().enumerate() {
+            path.push(key.clone());
+            let key_comments = comments.get(path);
+            for comment in key_comments
+                .map(|comments| comments.leading.as_slice())
+                .unwrap_or_default()
+            {
+                output.push('\n');
+                output.push_str(&inner);
+                output.push_str(comment);
+            }
+            output.push('\n');
+            output.push_str(&inner);
+            output.push_str(&self.escape(serde_json::to_string(key)?));
+            output.push_str(": ");
+            self.write_value(output, value, path, comments)?;
+            if index + 1 < entries.len() {
+                output.push(',');
+            }
+            if let Some(trailing) = key_comments.and_then(|comments| comments.trailing.as_ref()) {
+                output.push(' ');
+                output.push_str(trailing);
+            }
+            path.pop();
+        }
+        for comment in end {
+            output.push('\n');
+            output.push_str(&inner);
+            output.push_str(comment);
+        }
+        output.push('\n');
+        output.push_str(&indent);
+        output.push('}');
+        Ok(())
+    }
+
+    fn pretty<T: Serialize>(&self, value: &T) -> Result<String> {
+        let mut output = Vec::new();
+        let formatter = PrettyFormatter::with_indent(self.indent.as_bytes());
+        value.serialize(&mut Serializer::with_formatter(&mut output, formatter))?;
+        Ok(self.escape(String::from_utf8(output)?))
+    }
+
+    /// Escape the non-ASCII characters of serialized JSON if asked to, which can only be in
+    /// its strings
+    fn escape(&self, json: String) -> String {
+        if !self.escape_unicode || json.is_ascii() {
+            return json;
+        }
+        let mut escaped = String::with_capacity(json.len());
+        for c in json.chars() {
+            if c.is_ascii() {
+                escaped.push(c);
+                continue;
+            }
+            // Characters outside of the basic plane are escaped as a surrogate pair
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                escaped.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_format() {
+        let format = JsonFormat::detect("{\n\t\"Cart\": {\n\t\t\"title\": \"Cart\"\n\t}\n}\n");
+        assert_eq!(format.indent, "\t");
+        assert!(format.trailing_newline);
+        assert_eq!(
+            format
+                .to_string(&json!({ "Cart": { "title": "Cart" } }))
+                .unwrap(),
+            "{\n\t\"Cart\": {\n\t\t\"title\": \"Cart\"\n\t}\n}\n"
+        );
+
+        let format = JsonFormat::detect("{\n    \"title\": \"Title\"\n}");
+        assert_eq!(format.indent, "    ");
+        assert!(!format.trailing_newline);
+        assert_eq!(JsonFormat::detect("{}"), JsonFormat::default());
+    }
+
+    #[test]
+    fn test_escape_unicode() {
+        let format = JsonFormat {
+            escape_unicode: true,
+            ..JsonFormat::default()
+        };
+        let value = OrderedValue::Object(vec![(
+            "café".to_string(),
+            OrderedValue::Value(json!("Crème brûlée 🍮")),
+        )]);
+        let output = format
+            .to_string_with_comments(&value, &Comments::default())
+            .unwrap();
+        assert_eq!(
+            output,
+            "{\n  \"caf\\u00e9\": \"Cr\\u00e8me br\\u00fbl\\u00e9e \\ud83c\\udf6e\"\n}"
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed["café"], "Crème brûlée 🍮");
+    }
+
+    #[test]
+    fn test_format_overrides() {
+        let mut format = JsonFormat::detect("{\r\n  \"title\": \"Title\"\r\n}\r\n");
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+        assert_eq!(format.finish("{\n}".to_string()), "{\r\n}\r\n");
+
+        let overrides = FormatOverrides {
+            indent: Some(parse_indent("tab").unwrap()),
+            line_ending: Some(LineEnding::Lf),
+            final_newline: Some(false),
+            escape_unicode: false,
+        };
+        overrides.apply(&mut format);
+        assert_eq!(format.indent, "\t");
+        assert_eq!(format.finish("{\r\n}\r\n".to_string()), "{\n}");
+        assert_eq!(parse_indent("4").unwrap(), "    ");
+        assert!(parse_indent("four").is_err());
+    }
+
+    #[test]
+    fn test_to_string_with_comments() {
+        let content = r#"// Messages
+{
+    "Cart": {
+        // Shown when the cart is empty
+        "empty": "Your cart is empty", // Keep it short
+        "items": ["a", "b"]
+        // TODO: add checkout
+    },
+    "Nav": {}
+}
+"#;
+        let jsonc = crate::jsonc::parse(content);
+        let value = OrderedValue::Object(vec![
+            (
+                "Cart".to_string(),
+                OrderedValue::Object(vec![
+                    (
+                        "empty".to_string(),
+                        OrderedValue::Value(json!("Your cart is empty")),
+                    ),
+                    ("items".to_string(), OrderedValue::Value(json!(["a", "b"]))),
+                ]),
+            ),
+            ("Nav".to_string(), OrderedValue::Object(Vec::new())),
+        ]);
+        let format = JsonFormat::detect(content);
+        let output = format
+            .to_string_with_comments(&value, &jsonc.comments)
+            .unwrap();
+        assert_eq!(
+            output,
+            content.replace(
+                r#"["a", "b"]"#,
+                "[\n            \"a\",\n            \"b\"\n        ]"
+            )
+        );
+
+        // Without comments the output is the same as the plain serialization
+        assert_eq!(
+            format
+                .to_string_with_comments(&value, &Default::default())
+                .unwrap(),
+            format.to_string(&value).unwrap()
+        );
+    }
+}
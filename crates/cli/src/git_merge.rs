@@ -0,0 +1,287 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::format::JsonFormat;
+use crate::jsonc;
+use crate::order::{KeyOrder, OrderedValue};
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const THEIRS_MARKER: &str = "=======";
+const END_MARKER: &str = ">>>>>>>";
+
+/// The sides of a file with git conflict markers
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConflictSides {
+    pub ours: String,
+    pub theirs: String,
+    /// Only known when every conflict shows the base, e.g. with `merge.conflictStyle = diff3`
+    pub base: Option<String>,
+}
+
+/// Split a file with git conflict markers into our and their version of it
+pub fn split_conflicts(content: &str) -> Result<ConflictSides> {
+    #[derive(PartialEq)]
+    enum Section {
+        Common,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut sides = ConflictSides {
+        ours: String::new(),
+        theirs: String::new(),
+        base: Some(String::new()),
+    };
+    let mut section = Section::Common;
+    let mut has_base = false;
+    for line in content.split_inclusive('\n') {
+        let marker = |marker: &str| line.starts_with(marker);
+        match section {
+            Section::Common if marker(OURS_MARKER) => {
+                section = Section::Ours;
+                has_base = false;
+            }
+            Section::Ours if marker(BASE_MARKER) => {
+                section = Section::Base;
+                has_base = true;
+            }
+            Section::Ours | Section::Base if marker(THEIRS_MARKER) => {
+                if !has_base {
+                    sides.base = None;
+                }
+                section = Section::Theirs;
+            }
+            Section::Theirs if marker(END_MARKER) => section = Section::Common,
+            Section::Common => {
+                sides.ours.push_str(line);
+                sides.theirs.push_str(line);
+                if let Some(base) = &mut sides.base {
+                    base.push_str(line);
+                }
+            }
+            Section::Ours => sides.ours.push_str(line),
+            Section::Base => {
+                if let Some(base) = &mut sides.base {
+                    base.push_str(line);
+                }
+            }
+            Section::Theirs => sides.theirs.push_str(line),
+        }
+    }
+    if section != Section::Common {
+        return Err(anyhow!("Unterminated conflict marker"));
+    }
+    Ok(sides)
+}
+
+/// Merge our and their changes of messages to the base version, key by key
+///
+/// Returns the merged messages and the full keys that were changed on both sides, for which
+/// our value is kept. Without a base, keys that are only on one side are kept
+pub fn merge(
+    base: Option<&OrderedValue>,
+    ours: &OrderedValue,
+    theirs: &OrderedValue,
+) -> (OrderedValue, Vec<String>) {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(base, ours, theirs, "", &mut conflicts);
+    (merged, conflicts)
+}
+
+fn merge_value(
+    base: Option<&OrderedValue>,
+    ours: &OrderedValue,
+    theirs: &OrderedValue,
+    key: &str,
+    conflicts: &mut Vec<String>,
+) -> OrderedValue {
+    if let (OrderedValue::Object(our_entries), OrderedValue::Object(their_entries)) = (ours, theirs)
+    {
+        let base_entries = match base {
+            Some(OrderedValue::Object(entries)) => Some(entries),
+            _ => None,
+        };
+        let names = our_entries.iter().chain(
+            their_entries
+                .iter()
+                .filter(|(name, _)| get(our_entries, name).is_none()),
+        );
+
+        let mut merged = Vec::new();
+        for (name, _) in names {
+            let full_key = if key.is_empty() {
+                name.clone()
+            } else {
+                format!("{key}.{name}")
+            };
+            let base = base_entries.and_then(|entries| get(entries, name));
+            let value = match (get(our_entries, name), get(their_entries, name)) {
+                (Some(ours), Some(theirs)) => {
+                    Some(merge_value(base, ours, theirs, &full_key, conflicts))
+                }
+                // Removed on one side, kept when it was changed on the other
+                (Some(side), None) | (None, Some(side)) => match base {
+                    Some(base) if base == side => None,
+                    Some(_) => {
+                        conflicts.push(full_key);
+                        Some(side.clone())
+                    }
+                    None => Some(side.clone()),
+                },
+                (None, None) => None,
+            };
+            if let Some(value) = value {
+                merged.push((name.clone(), value));
+            }
+        }
+        return OrderedValue::Object(merged);
+    }
+
+    if ours == theirs || base == Some(theirs) {
+        ours.clone()
+    } else if base == Some(ours) {
+        theirs.clone()
+    } else {
+        conflicts.push(key.to_string());
+        ours.clone()
+    }
+}
+
+fn get<'e>(entries: &'e [(String, OrderedValue)], name: &str) -> Option<&'e OrderedValue> {
+    entries
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value)
+}
+
+/// Parse JSON messages with the order of their keys
+fn parse(content: &str, name: &str) -> Result<OrderedValue> {
+    let json = jsonc::parse(content).json;
+    let value: Value =
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse JSON of {name}"))?;
+    let order: KeyOrder =
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse JSON of {name}"))?;
+    Ok(OrderedValue::with_order(value, &order))
+}
+
+/// Merge the sides of a JSON messages file and write the result, formatted like our side
+///
+/// Returns the full keys that were changed on both sides
+fn merge_files(
+    output_path: &Path,
+    base: Option<&str>,
+    ours: &str,
+    theirs: &str,
+) -> Result<Vec<String>> {
+    let base = base.map(|base| parse(base, "the base")).transpose()?;
+    let (merged, conflicts) = merge(
+        base.as_ref(),
+        &parse(ours, "our side")?,
+        &parse(theirs, "their side")?,
+    );
+    let json = JsonFormat::detect(ours).to_string(&merged)?;
+    fs::write(output_path, json)?;
+    Ok(conflicts)
+}
+
+/// Resolve the git conflict markers in a JSON messages file in place
+///
+/// Returns the full keys that were changed on both sides, for which our value is kept
+pub fn resolve_conflicts(path: &Path) -> Result<Vec<String>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let sides = split_conflicts(&content)?;
+    merge_files(path, sides.base.as_deref(), &sides.ours, &sides.theirs)
+}
+
+/// Merge the base, our and their version of a JSON messages file like a git merge driver, the
+/// result is written to our file
+///
+/// Returns the full keys that were changed on both sides, for which our value is kept
+pub fn merge_driver(base_path: &Path, our_path: &Path, their_path: &Path) -> Result<Vec<String>> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+    };
+    let base = read(base_path)?;
+    let base = Some(base.as_str()).filter(|base| !base.trim().is_empty());
+    merge_files(our_path, base, &read(our_path)?, &read(their_path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_split_conflicts() {
+        let content = r#"{
+<<<<<<< HEAD
+  "a": "ours",
+||||||| base
+  "a": "base",
+=======
+  "a": "theirs",
+>>>>>>> feature
+  "b": "b"
+}
+"#;
+        let sides = split_conflicts(content).unwrap();
+        assert_eq!(sides.ours, "{\n  \"a\": \"ours\",\n  \"b\": \"b\"\n}\n");
+        assert_eq!(sides.theirs, "{\n  \"a\": \"theirs\",\n  \"b\": \"b\"\n}\n");
+        assert_eq!(
+            sides.base.as_deref(),
+            Some("{\n  \"a\": \"base\",\n  \"b\": \"b\"\n}\n")
+        );
+        assert!(split_conflicts("<<<<<<< HEAD\n").is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = parse(
+            r#"{ "Cart": { "title": "Cart", "old": "Old", "total": "Total" } }"#,
+            "base",
+        )
+        .unwrap();
+        let ours = parse(
+            r#"{ "Cart": { "title": "Basket", "total": "Sum", "ours": "Ours" } }"#,
+            "ours",
+        )
+        .unwrap();
+        let theirs = parse(
+            r#"{ "Cart": { "title": "Cart", "old": "Old", "total": "Amount" }, "Nav": {} }"#,
+            "theirs",
+        )
+        .unwrap();
+
+        let (merged, conflicts) = merge(Some(&base), &ours, &theirs);
+        assert_eq!(
+            merged.into_value(),
+            json!({
+                "Cart": { "title": "Basket", "total": "Sum", "ours": "Ours" },
+                "Nav": {}
+            })
+        );
+        assert_eq!(conflicts, vec!["Cart.total"]);
+    }
+
+    #[test]
+    fn test_resolve_conflicts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(
+            &path,
+            "{\n  \"Cart\": {\n<<<<<<< HEAD\n    \"a\": \"A\",\n=======\n    \"b\": \"B\",\n>>>>>>> feature\n    \"title\": \"Cart\"\n  }\n}\n",
+        )
+        .unwrap();
+
+        assert!(resolve_conflicts(&path).unwrap().is_empty());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"Cart\": {\n    \"a\": \"A\",\n    \"title\": \"Cart\",\n    \"b\": \"B\"\n  }\n}\n"
+        );
+    }
+}
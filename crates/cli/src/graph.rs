@@ -0,0 +1,146 @@
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// How the namespace graph is printed
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz, e.g. for `dot -Tsvg`
+    #[default]
+    Dot,
+    /// JSON for tooling
+    Json,
+}
+
+/// Which files use which namespaces, and which namespaces have keys of the same name
+#[derive(Debug, Default)]
+pub struct NamespaceGraph {
+    /// The namespaces every file uses
+    files: BTreeMap<String, BTreeSet<String>>,
+    /// The keys of every namespace
+    keys: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl NamespaceGraph {
+    /// Add the namespaces and keys a file uses
+    pub fn add(&mut self, file: &str, translations: &HashMap<String, HashSet<String>>) {
+        let namespaces = self.files.entry(file.to_string()).or_default();
+        for (namespace, keys) in translations {
+            namespaces.insert(namespace.clone());
+            self.keys
+                .entry(namespace.clone())
+                .or_default()
+                .extend(keys.iter().cloned());
+        }
+    }
+
+    /// The keys of the same name by pair of namespaces, e.g. `title` of `Cart` and `Checkout`
+    pub fn shared_keys(&self) -> BTreeMap<(&str, &str), Vec<&str>> {
+        let mut shared_keys = BTreeMap::new();
+        for (index, (namespace, keys)) in self.keys.iter().enumerate() {
+            for (other_namespace, other_keys) in self.keys.iter().skip(index + 1) {
+                let shared: Vec<&str> = keys.intersection(other_keys).map(String::as_str).collect();
+                if !shared.is_empty() {
+                    shared_keys.insert((namespace.as_str(), other_namespace.as_str()), shared);
+                }
+            }
+        }
+        shared_keys
+    }
+
+    pub fn to_string(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Json => {
+                serde_json::to_string_pretty(&self.to_json()).unwrap_or_default() + "\n"
+            }
+        }
+    }
+
+    /// A Graphviz graph with an edge from every file to the namespaces it uses, and a dashed
+    /// one between namespaces with shared keys
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph namespaces {\n    rankdir=LR;\n");
+        for (file, namespaces) in &self.files {
+            dot += &format!("    {} [shape=box];\n", quote(file));
+            for namespace in namespaces {
+                dot += &format!("    {} -> {};\n", quote(file), quote(namespace));
+            }
+        }
+        for ((namespace, other_namespace), keys) in self.shared_keys() {
+            dot += &format!(
+                "    {} -> {} [dir=none, style=dashed, label=\"{} shared key(s)\"];\n",
+                quote(namespace),
+                quote(other_namespace),
+                keys.len()
+            );
+        }
+        dot + "}\n"
+    }
+
+    /// e.g. `{"files": {"app/page.tsx": ["Cart"]}, "shared": [{"namespaces": ["Cart",
+    /// "Checkout"], "keys": ["title"]}]}`
+    fn to_json(&self) -> Value {
+        let shared: Vec<Value> = self
+            .shared_keys()
+            .into_iter()
+            .map(|((namespace, other_namespace), keys)| {
+                json!({ "namespaces": [namespace, other_namespace], "keys": keys })
+            })
+            .collect();
+        json!({ "files": self.files, "shared": shared })
+    }
+}
+
+/// A Graphviz ID in quotes
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translations(namespaces: &[(&str, &[&str])]) -> HashMap<String, HashSet<String>> {
+        namespaces
+            .iter()
+            .map(|(namespace, keys)| {
+                let keys = keys.iter().map(|key| key.to_string()).collect();
+                (namespace.to_string(), keys)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_namespace_graph() {
+        let mut graph = NamespaceGraph::default();
+        graph.add("cart.tsx", &translations(&[("Cart", &["title", "total"])]));
+        graph.add(
+            "checkout.tsx",
+            &translations(&[("Checkout", &["title", "pay"]), ("Cart", &["total"])]),
+        );
+
+        assert_eq!(
+            graph.shared_keys(),
+            BTreeMap::from([(("Cart", "Checkout"), vec!["title"])])
+        );
+        assert_eq!(
+            graph.to_string(GraphFormat::Dot),
+            "digraph namespaces {\n    rankdir=LR;\n\
+             \x20   \"cart.tsx\" [shape=box];\n\
+             \x20   \"cart.tsx\" -> \"Cart\";\n\
+             \x20   \"checkout.tsx\" [shape=box];\n\
+             \x20   \"checkout.tsx\" -> \"Cart\";\n\
+             \x20   \"checkout.tsx\" -> \"Checkout\";\n\
+             \x20   \"Cart\" -> \"Checkout\" [dir=none, style=dashed, label=\"1 shared key(s)\"];\n\
+             }\n"
+        );
+        assert_eq!(
+            graph.to_json(),
+            json!({
+                "files": { "cart.tsx": ["Cart"], "checkout.tsx": ["Cart", "Checkout"] },
+                "shared": [{ "namespaces": ["Cart", "Checkout"], "keys": ["title"] }]
+            })
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// A syntax error in the ICU message of a key
+#[derive(Debug, PartialEq, Eq)]
+pub struct IcuError {
+    /// Full key of the message, e.g. `Cart.title`
+    pub key: String,
+    /// Byte offset of the error in the message
+    pub offset: usize,
+    pub problem: String,
+}
+
+impl fmt::Display for IcuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.problem, self.offset)
+    }
+}
+
+/// The syntax errors of every string message, by full key
+pub fn check_messages(messages: &Map<String, Value>) -> Vec<IcuError> {
+    let mut errors = Vec::new();
+    collect_errors(messages, "", &mut errors);
+    errors
+}
+
+fn collect_errors(messages: &Map<String, Value>, prefix: &str, errors: &mut Vec<IcuError>) {
+    for (key, value) in messages {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Object(nested) => collect_errors(nested, &full_key, errors),
+            Value::String(message) => {
+                if let Err((offset, problem)) = check(message) {
+                    errors.push(IcuError {
+                        key: full_key,
+                        offset,
+                        problem,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Check the syntax of an ICU message, e.g. `{count, plural, one {# item} other {# items}}`
+///
+/// Returns the byte offset and the problem of the first error
+pub fn check(message: &str) -> Result<(), (usize, String)> {
+    parse(message).map(|_| ())
+}
+
+/// The names of the values a message is formatted with, including the ones of rich text tags,
+/// e.g. `name` and `b` for `Hello <b>{name}</b>`
+///
+/// `None` if the message is not a valid ICU message
+pub fn arguments(message: &str) -> Option<BTreeSet<String>> {
+    parse(message).ok()
+}
+
+fn parse(message: &str) -> ParseResult<BTreeSet<String>> {
+    let mut parser = Parser {
+        chars: message.char_indices().peekable(),
+        len: message.len(),
+        arguments: BTreeSet::new(),
+    };
+    parser.message(false)?;
+    match parser.chars.next() {
+        Some((offset, _)) => Err((offset, "Unexpected '}'".to_string())),
+        None => Ok(parser.arguments),
+    }
+}
+
+struct Parser<'m> {
+    chars: Peekable<CharIndices<'m>>,
+    len: usize,
+    /// Names of the arguments and tags so far
+    arguments: BTreeSet<String>,
+}
+
+type ParseResult<T> = Result<T, (usize, String)>;
+
+impl Parser<'_> {
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.len, |&(offset, _)| offset)
+    }
+
+    /// Text and arguments up to an unmatched `}` or the end, `#` is special in plural options
+    fn message(&mut self, in_plural: bool) -> ParseResult<()> {
+        while let Some(&(_, c)) = self.chars.peek() {
+            match c {
+                '}' => return Ok(()),
+                '{' => {
+                    self.chars.next();
+                    self.argument()?;
+                }
+                '\'' => {
+                    self.chars.next();
+                    self.quoted(in_plural);
+                }
+                '<' => {
+                    self.chars.next();
+                    self.tag();
+                }
+                _ => {
+                    self.chars.next();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `''` is an apostrophe, and an apostrophe before a special character quotes the text up
+    /// to the next apostrophe
+    fn quoted(&mut self, in_plural: bool) {
+        match self.chars.peek() {
+            Some((_, '\'')) => {
+                self.chars.next();
+            }
+            Some((_, '{' | '}')) => self.skip_quoted(),
+            Some((_, '#')) if in_plural => self.skip_quoted(),
+            _ => {}
+        }
+    }
+
+    /// A rich text tag after its `<`, e.g. `b>` of `<b>`, closing tags are skipped
+    fn tag(&mut self) {
+        let mut ahead = self.chars.clone();
+        let mut name = String::new();
+        while let Some((_, c)) =
+            ahead.next_if(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            name.push(c);
+        }
+        if !name.is_empty() && ahead.next_if(|&(_, c)| c == '>').is_some() {
+            self.arguments.insert(name);
+            self.chars = ahead;
+        }
+    }
+
+    fn skip_quoted(&mut self) {
+        for (_, c) in self.chars.by_ref() {
+            if c == '\'' {
+                return;
+            }
+        }
+    }
+
+    /// An argument after its `{`, up to and including its `}`
+    fn argument(&mut self) -> ParseResult<()> {
+        self.whitespace();
+        let name_offset = self.offset();
+        let name = self.word();
+        if name.is_empty() {
+            return Err((name_offset, "Expected an argument name".to_string()));
+        }
+        self.arguments.insert(name);
+        self.whitespace();
+        if self.eat('}') {
+            return Ok(());
+        }
+        self.expect(',')?;
+        self.whitespace();
+        let type_offset = self.offset();
+        let kind = self.word();
+        self.whitespace();
+        match kind.as_str() {
+            "number" | "date" | "time" => {
+                if self.eat(',') {
+                    // The style, e.g. `percent` or a skeleton like `::currency/EUR`
+                    while let Some(&(offset, c)) = self.chars.peek() {
+                        match c {
+                            '}' => break,
+                            '{' => return Err((offset, "Unexpected '{' in style".to_string())),
+                            _ => {
+                                self.chars.next();
+                            }
+                        }
+                    }
+                }
+                self.expect('}')
+            }
+            "plural" | "selectordinal" => {
+                self.expect(',')?;
+                self.whitespace();
+                if self.eat_word("offset:") {
+                    self.whitespace();
+                    let offset = self.offset();
+                    if !self.word().chars().all(|c| c.is_ascii_digit()) {
+                        return Err((offset, "Expected a number after 'offset:'".to_string()));
+                    }
+                }
+                self.options(true)
+            }
+            "select" => {
+                self.expect(',')?;
+                self.options(false)
+            }
+            "" => Err((type_offset, "Expected an argument type".to_string())),
+            kind => Err((type_offset, format!("Unknown argument type '{kind}'"))),
+        }
+    }
+
+    /// The options of a plural or select argument, up to and including its `}`
+    fn options(&mut self, plural: bool) -> ParseResult<()> {
+        let mut has_other = false;
+        loop {
+            self.whitespace();
+            let offset = self.offset();
+            if self.eat('}') {
+                if !has_other {
+                    return Err((offset, "Missing 'other' option".to_string()));
+                }
+                return Ok(());
+            }
+            let selector = self.word();
+            if selector.is_empty() {
+                return Err((offset, "Expected an option".to_string()));
+            }
+            if plural && !is_plural_selector(&selector) {
+                return Err((offset, format!("Invalid plural option '{selector}'")));
+            }
+            has_other |= selector == "other";
+            self.whitespace();
+            self.expect('{')?;
+            self.message(plural)?;
+            self.expect('}')?;
+        }
+    }
+
+    /// Characters up to whitespace or syntax
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c.is_whitespace() || matches!(c, '{' | '}' | ',') {
+                break;
+            }
+            word.push(c);
+            self.chars.next();
+        }
+        word
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let mut ahead = self.chars.clone();
+        for expected in word.chars() {
+            match ahead.next() {
+                Some((_, c)) if c == expected => {}
+                _ => return false,
+            }
+        }
+        self.chars = ahead;
+        true
+    }
+
+    fn whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.chars.next_if(|&(_, c)| c == expected).is_some()
+    }
+
+    fn expect(&mut self, expected: char) -> ParseResult<()> {
+        let offset = self.offset();
+        if self.eat(expected) {
+            return Ok(());
+        }
+        match self.chars.peek() {
+            Some((_, found)) => Err((offset, format!("Expected '{expected}', found '{found}'"))),
+            // Only arguments expect anything, so the input ended inside of one
+            None => Err((offset, "Unbalanced braces, missing '}'".to_string())),
+        }
+    }
+}
+
+/// A plural category or an exact match, e.g. `=0`
+fn is_plural_selector(selector: &str) -> bool {
+    PLURAL_CATEGORIES.contains(&selector)
+        || selector
+            .strip_prefix('=')
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_valid_messages() {
+        for message in [
+            "Hello",
+            "Hello {name}!",
+            "{count, plural, =0 {No items} one {# item} other {# items}}",
+            "{count, plural, offset:1 one {You} other {You and # others}}",
+            "{place, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}",
+            "{gender, select, female {She} male {He} other {They}} liked it",
+            "{price, number, ::currency/EUR} on {date, date, short}",
+            "It's '{escaped}' and '#' and ''quoted''",
+            "<b>Bold</b> {name}",
+            "{count, plural, other {{name} has # '{items}'}}",
+        ] {
+            assert_eq!(check(message), Ok(()), "{message}");
+        }
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let problem = |message: &str| check(message).unwrap_err();
+        assert_eq!(
+            problem("Hello {name"),
+            (11, "Unbalanced braces, missing '}'".to_string())
+        );
+        assert_eq!(problem("Hello }"), (6, "Unexpected '}'".to_string()));
+        assert_eq!(problem("{}"), (1, "Expected an argument name".to_string()));
+        assert_eq!(
+            problem("{count, plural, one {# item}}"),
+            (28, "Missing 'other' option".to_string())
+        );
+        assert_eq!(
+            problem("{count, plural, single {# item} other {# items}}"),
+            (16, "Invalid plural option 'single'".to_string())
+        );
+        assert_eq!(
+            problem("{count, plural, one # item other {# items}}"),
+            (20, "Expected '{', found '#'".to_string())
+        );
+        assert_eq!(
+            problem("{count, plurl, other {}}"),
+            (8, "Unknown argument type 'plurl'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_arguments() {
+        let names = |message: &str| arguments(message).unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(names("Hello"), Vec::<String>::new());
+        assert_eq!(
+            names("{count, plural, one {<b>#</b> item by {name}} other {# items}}"),
+            vec!["b", "count", "name"]
+        );
+        assert_eq!(names("'{quoted}' a < b"), Vec::<String>::new());
+        assert_eq!(arguments("{name"), None);
+    }
+
+    #[test]
+    fn test_check_messages() {
+        let messages = json!({
+            "Cart": { "title": "Cart {count", "empty": "Empty" },
+            "Nav": { "count": 1 }
+        });
+        let errors = check_messages(messages.as_object().unwrap());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].key, "Cart.title");
+        assert_eq!(
+            errors[0].to_string(),
+            "Unbalanced braces, missing '}' at offset 11"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+/// The comments around a key of a JSONC file, e.g. context for translators
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyComments {
+    /// Comments on the lines above the key
+    pub leading: Vec<String>,
+    /// A comment after the value on the same line
+    pub trailing: Option<String>,
+    /// Comments after the last key of an object value, before its closing brace
+    pub end: Vec<String>,
+}
+
+/// The comments of a JSONC file by the path of the key they belong to
+///
+/// The comments of the root object are stored under the empty path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comments {
+    keys: HashMap<Vec<String>, KeyComments>,
+    /// Comments after the root object
+    pub footer: Vec<String>,
+}
+
+impl Comments {
+    pub fn get(&self, path: &[String]) -> Option<&KeyComments> {
+        self.keys.get(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.footer.is_empty()
+    }
+
+    fn entry(&mut self, path: Vec<String>) -> &mut KeyComments {
+        self.keys.entry(path).or_default()
+    }
+}
+
+/// A JSONC file split into plain JSON and its comments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Jsonc {
+    /// The content without comments and trailing commas
+    pub json: String,
+    pub comments: Comments,
+}
+
+struct Frame {
+    is_object: bool,
+    /// The key whose value is currently read, for objects
+    key: Option<String>,
+    expecting_key: bool,
+}
+
+/// Split JSON with comments and trailing commas into plain JSON and the comments
+pub fn parse(content: &str) -> Jsonc {
+    let mut json = String::with_capacity(content.len());
+    let mut comments = Comments::default();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    // The key of the last value that was read, for comments on the same line
+    let mut last_value: Option<Vec<String>> = None;
+    let mut newline_since_token = true;
+    let mut chars = content.char_indices().peekable();
+
+    // The path of the key that is currently read, `None` inside arrays
+    let key_path = |stack: &[Frame]| -> Option<Vec<String>> {
+        stack
+            .iter()
+            .map(|frame| frame.key.clone().filter(|_| frame.is_object))
+            .collect()
+    };
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '/' if matches!(chars.peek(), Some((_, '/' | '*'))) => {
+                let block = chars.next().is_some_and(|(_, c)| c == '*');
+                let mut end = content.len();
+                let mut previous = ' ';
+                while let Some(&(index, c)) = chars.peek() {
+                    if !block && c == '\n' {
+                        end = index;
+                        break;
+                    }
+                    chars.next();
+                    if block && previous == '*' && c == '/' {
+                        end = index + 1;
+                        break;
+                    }
+                    previous = c;
+                }
+                let comment = content[start..end].trim_end().to_string();
+                match last_value.clone().filter(|_| !newline_since_token) {
+                    Some(path) => comments.entry(path).trailing = Some(comment),
+                    None => pending.push(comment),
+                }
+            }
+            '"' => {
+                let mut end = content.len();
+                while let Some((index, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => {
+                            end = index + 1;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                let string = &content[start..end];
+                json.push_str(string);
+                newline_since_token = false;
+
+                match stack.last_mut() {
+                    Some(frame) if frame.is_object && frame.expecting_key => {
+                        frame.key = serde_json::from_str(string).ok();
+                        frame.expecting_key = false;
+                        if let Some(path) = key_path(&stack) {
+                            comments.entry(path).leading.append(&mut pending);
+                        }
+                        pending.clear();
+                    }
+                    _ => last_value = key_path(&stack),
+                }
+            }
+            '{' | '[' => {
+                if stack.is_empty() {
+                    comments.entry(Vec::new()).leading.append(&mut pending);
+                }
+                pending.clear();
+                json.push(c);
+                newline_since_token = false;
+                last_value = None;
+                stack.push(Frame {
+                    is_object: c == '{',
+                    key: None,
+                    expecting_key: c == '{',
+                });
+            }
+            '}' | ']' => {
+                // Trailing comma
+                let trimmed = json.trim_end().len();
+                if json[..trimmed].ends_with(',') {
+                    json.remove(trimmed - 1);
+                }
+                if c == '}' {
+                    let path = key_path(&stack[..stack.len().saturating_sub(1)]);
+                    if let Some(path) = path.filter(|_| !pending.is_empty()) {
+                        comments.entry(path).end.append(&mut pending);
+                    }
+                }
+                pending.clear();
+                json.push(c);
+                newline_since_token = false;
+                stack.pop();
+                last_value = key_path(&stack);
+            }
+            ',' => {
+                json.push(c);
+                if let Some(frame) = stack.last_mut() {
+                    frame.expecting_key = frame.is_object;
+                }
+            }
+            '\n' => {
+                json.push(c);
+                newline_since_token = true;
+            }
+            c if c.is_whitespace() || c == ':' => json.push(c),
+            // Numbers, `true`, `false` and `null`
+            c => {
+                json.push(c);
+                last_value = key_path(&stack);
+                newline_since_token = false;
+            }
+        }
+    }
+
+    comments.footer = pending;
+    Jsonc { json, comments }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let jsonc = parse(
+            r#"// Messages of the shop
+{
+    "Cart": {
+        // Shown when the cart is empty
+        "empty": "Your cart is empty", // Keep it short
+        /* The total
+           price */
+        "total": 10,
+        "items": ["a", "b",],
+        // TODO: add checkout
+    },
+    "Nav": {} // Main navigation
+}
+// End
+"#,
+        );
+
+        let value: serde_json::Value = serde_json::from_str(&jsonc.json).unwrap();
+        assert_eq!(value["Cart"]["items"][1], "b");
+
+        let comments = &jsonc.comments;
+        assert_eq!(
+            comments.get(&[]).unwrap().leading,
+            vec!["// Messages of the shop"]
+        );
+        let empty = comments.get(&path(&["Cart", "empty"])).unwrap();
+        assert_eq!(empty.leading, vec!["// Shown when the cart is empty"]);
+        assert_eq!(empty.trailing.as_deref(), Some("// Keep it short"));
+        assert_eq!(
+            comments.get(&path(&["Cart", "total"])).unwrap().leading,
+            vec!["/* The total\n           price */"]
+        );
+        assert_eq!(
+            comments.get(&path(&["Cart"])).unwrap().end,
+            vec!["// TODO: add checkout"]
+        );
+        assert_eq!(
+            comments.get(&path(&["Nav"])).unwrap().trailing.as_deref(),
+            Some("// Main navigation")
+        );
+        assert_eq!(comments.footer, vec!["// End"]);
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use glob::{glob, Pattern};
+use std::path::{Path, PathBuf};
+
+/// Placeholder in the output path for the locale of the catalog, e.g. `messages/{locale}.json`
+pub const LOCALE_PLACEHOLDER: &str = "{locale}";
+
+/// Placeholder in the output path for a file per namespace, e.g. `messages/{namespace}.json`
+pub const NAMESPACE_PLACEHOLDER: &str = "{namespace}";
+
+pub fn has_placeholder(template: &Path, placeholder: &str) -> bool {
+    template.to_string_lossy().contains(placeholder)
+}
+
+/// Replace a placeholder of a path template, e.g. `{locale}` with `en`
+pub fn fill(template: &Path, placeholder: &str, value: &str) -> PathBuf {
+    PathBuf::from(template.to_string_lossy().replace(placeholder, value))
+}
+
+/// The existing files of a template with a `{namespace}` placeholder, with their namespace
+pub fn namespace_files(template: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let template = template.to_string_lossy();
+    let Some((prefix, suffix)) = template.split_once(NAMESPACE_PLACEHOLDER) else {
+        return Ok(Vec::new());
+    };
+    let pattern = format!("{}*{}", Pattern::escape(prefix), Pattern::escape(suffix));
+
+    let mut files = Vec::new();
+    for path in glob(&pattern).with_context(|| format!("Invalid output path {template}"))? {
+        let path = path?;
+        let namespace = path
+            .to_string_lossy()
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+            .map(str::to_string);
+        // `*` doesn't cross directories, but the prefix may be normalized by the glob
+        if let Some(namespace) = namespace.filter(|namespace| !namespace.is_empty()) {
+            files.push((namespace, path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_namespace_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("en")).unwrap();
+        fs::write(dir.path().join("en/Cart.json"), "{}").unwrap();
+        fs::write(dir.path().join("en/Nav.json"), "{}").unwrap();
+        fs::write(dir.path().join("en/notes.txt"), "").unwrap();
+
+        let template = dir.path().join("{locale}/{namespace}.json");
+        assert!(has_placeholder(&template, LOCALE_PLACEHOLDER));
+        let template = fill(&template, LOCALE_PLACEHOLDER, "en");
+        assert_eq!(
+            namespace_files(&template).unwrap(),
+            vec![
+                ("Cart".to_string(), dir.path().join("en/Cart.json")),
+                ("Nav".to_string(), dir.path().join("en/Nav.json")),
+            ]
+        );
+        assert_eq!(
+            fill(&template, NAMESPACE_PLACEHOLDER, "Cart"),
+            dir.path().join("en/Cart.json")
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::messages::write_formatted;
+
+const HASHES_KEY: &str = "hashes";
+const OUTDATED_KEY: &str = "outdated";
+const GENERATED_KEY: &str = "generated";
+
+/// Hashes of the source locale's messages as of the last run, and the translations that were
+/// outdated by a change of their source message since then
+///
+/// ```json
+/// {
+///   "hashes": { "Cart.title": "af63bd4c8601b7be" },
+///   "outdated": { "de": { "Cart.title": "5f1a3d8b2e0c9471" } },
+///   "generated": { "de": { "Cart.empty": "0c4b1e2d9a7f3856" } }
+/// }
+/// ```
+///
+/// An outdated translation is stored with the hash of its value, it stays outdated until the
+/// value is changed. A generated value, e.g. the placeholder of a new message, is stored the
+/// same way, it counts as edited by hand once it is changed
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Lock {
+    hashes: BTreeMap<String, String>,
+    outdated: BTreeMap<String, BTreeMap<String, String>>,
+    generated: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Lock {
+    pub fn load(path: &Path) -> Result<Self> {
+        // The lock is created on the first write
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let value: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?;
+        Ok(Self {
+            hashes: string_map(value.get(HASHES_KEY)),
+            outdated: locale_maps(value.get(OUTDATED_KEY)),
+            generated: locale_maps(value.get(GENERATED_KEY)),
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut lock = Map::new();
+        lock.insert(HASHES_KEY.to_string(), json_map(&self.hashes));
+        lock.insert(OUTDATED_KEY.to_string(), json_locale_maps(&self.outdated));
+        lock.insert(GENERATED_KEY.to_string(), json_locale_maps(&self.generated));
+        write_formatted(path, |format| format.to_string(&lock))
+    }
+
+    /// Full keys of the messages of a locale whose values were edited by hand, i.e. the ones
+    /// that aren't a generated value the lock knows of
+    pub fn edited_keys(&self, locale: &str, messages: &Map<String, Value>) -> BTreeSet<String> {
+        let generated = self.generated.get(locale);
+        hashes(messages)
+            .into_iter()
+            .filter(|(key, hash)| generated.and_then(|generated| generated.get(key)) != Some(hash))
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Remember the hashes of the values of a locale that were generated in this run, on top
+    /// of the ones generated before that are still unchanged
+    pub fn update_generated(
+        &mut self,
+        locale: &str,
+        messages: &Map<String, Value>,
+        generated_keys: &[String],
+    ) {
+        let current = hashes(messages);
+        let generated = self.generated.entry(locale.to_string()).or_default();
+        generated.retain(|key, hash| current.get(key) == Some(hash));
+        for key in generated_keys {
+            if let Some(hash) = current.get(key) {
+                generated.insert(key.clone(), hash.clone());
+            }
+        }
+    }
+
+    /// Mark the translations whose source message changed since the last update as outdated,
+    /// and remember the hashes of the current source messages
+    ///
+    /// Returns the full keys of the outdated translations by locale
+    pub fn update(
+        &mut self,
+        source: &Map<String, Value>,
+        translations: &[(&str, Map<String, Value>)],
+    ) -> BTreeMap<String, Vec<String>> {
+        let source_hashes = hashes(source);
+        let mut report = BTreeMap::new();
+        for (locale, messages) in translations {
+            let translated = hashes(messages);
+            let outdated = self.outdated.entry(locale.to_string()).or_default();
+            // Translations that were changed or removed since they were marked are up to date
+            outdated.retain(|key, hash| {
+                source_hashes.contains_key(key) && translated.get(key) == Some(hash)
+            });
+            for (key, hash) in &source_hashes {
+                let changed = self.hashes.get(key).is_some_and(|old| old != hash);
+                if let (true, Some(translation)) = (changed, translated.get(key)) {
+                    outdated.insert(key.clone(), translation.clone());
+                }
+            }
+            report.insert(locale.to_string(), outdated.keys().cloned().collect());
+        }
+        self.hashes = source_hashes;
+        report
+    }
+}
+
+/// The hashes of the values of messages by full key
+fn hashes(messages: &Map<String, Value>) -> BTreeMap<String, String> {
+    let mut hashes = BTreeMap::new();
+    collect_hashes(messages, "", &mut hashes);
+    hashes
+}
+
+fn collect_hashes(
+    messages: &Map<String, Value>,
+    prefix: &str,
+    hashes: &mut BTreeMap<String, String>,
+) {
+    for (key, value) in messages {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Object(nested) => collect_hashes(nested, &full_key, hashes),
+            value => {
+                hashes.insert(full_key, hash(&value.to_string()));
+            }
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the hasher of the standard library is stable across releases
+fn hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn string_map(value: Option<&Value>) -> BTreeMap<String, String> {
+    value
+        .and_then(Value::as_object)
+        .map(|map| {
+            map.iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn locale_maps(value: Option<&Value>) -> BTreeMap<String, BTreeMap<String, String>> {
+    value
+        .and_then(Value::as_object)
+        .map(|locales| {
+            locales
+                .iter()
+                .map(|(locale, keys)| (locale.clone(), string_map(Some(keys))))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The maps of every locale, leaving out the empty ones
+fn json_locale_maps(maps: &BTreeMap<String, BTreeMap<String, String>>) -> Value {
+    Value::Object(
+        maps.iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(locale, keys)| (locale.clone(), json_map(keys)))
+            .collect(),
+    )
+}
+
+fn json_map(map: &BTreeMap<String, String>) -> Value {
+    Value::Object(
+        map.iter()
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn messages(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_update() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.lock.json");
+        let translations = [("de", messages(json!({ "Cart": { "title": "Warenkorb" } })))];
+
+        let mut lock = Lock::load(&path).unwrap();
+        let report = lock.update(
+            &messages(json!({ "Cart": { "title": "Cart" } })),
+            &translations,
+        );
+        assert_eq!(report["de"], Vec::<String>::new());
+        lock.write(&path).unwrap();
+
+        // The source message changed, the translation stays outdated until it is changed too
+        let mut lock = Lock::load(&path).unwrap();
+        let source = messages(json!({ "Cart": { "title": "Basket" } }));
+        assert_eq!(
+            lock.update(&source, &translations)["de"],
+            vec!["Cart.title"]
+        );
+        lock.write(&path).unwrap();
+        let mut lock = Lock::load(&path).unwrap();
+        assert_eq!(
+            lock.update(&source, &translations)["de"],
+            vec!["Cart.title"]
+        );
+
+        let translations = [("de", messages(json!({ "Cart": { "title": "Korb" } })))];
+        assert_eq!(
+            lock.update(&source, &translations)["de"],
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(
+            Lock::load(&dir.path().join("messages.lock.json")).unwrap(),
+            Lock::default()
+        );
+        // A lock that can't be read isn't replaced by an empty one
+        assert!(Lock::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_generated() {
+        let mut lock = Lock::default();
+        let scaffolded = messages(json!({ "Cart": { "title": "Cart", "empty": "Cart.empty" } }));
+        lock.update_generated("de", &scaffolded, &["Cart.empty".to_string()]);
+        assert_eq!(
+            lock.edited_keys("de", &scaffolded),
+            BTreeSet::from(["Cart.title".to_string()])
+        );
+
+        // A generated value that was translated counts as edited and is no longer remembered
+        let translated = messages(json!({ "Cart": { "title": "Warenkorb", "empty": "Leer" } }));
+        assert_eq!(lock.edited_keys("de", &translated).len(), 2);
+        lock.update_generated("de", &translated, &[]);
+        assert!(lock.generated["de"].is_empty());
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(""), "cbf29ce484222325");
+        assert_eq!(hash("a"), "af63dc4c8601ec8c");
+    }
+}
@@ -1,35 +1,1008 @@
-use std::{path::PathBuf, process::ExitCode};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-use crate::files::find_files;
-use crate::messages::MessageHandler;
+use crate::catalogs::Catalogs;
+use crate::coverage::{Counts, Coverage};
+use crate::diagnostics::{
+    warn_catalog_usages, warn_client_messages, warn_dynamic_keys, warn_hardcoded_strings,
+    warn_icu_errors, warn_import_misuses, warn_kept_keys, warn_obsolete_keys,
+    warn_ownership_violations, warn_parse_errors, warn_placeholder_mismatches,
+    warn_policy_violations, warn_schema_errors, warn_unresolved_namespaces,
+};
+use crate::diff::{FileChange, KeyDiff};
+use crate::files::{find_files, FilePattern, DEFAULT_PATTERN};
+use crate::format::{parse_indent, CatalogFormat, FormatOverrides, LineEnding};
+use crate::graph::{GraphFormat, NamespaceGraph};
+use crate::layout::LOCALE_PLACEHOLDER;
+use crate::manifest::write_manifest;
+use crate::messages::{write_formatted, MessageHandler, NamespaceConflict, RemovedKeys};
+use crate::migrate::{IdRule, Migration};
+use crate::order::SortOrder;
+use crate::ownership::Ownership;
+use crate::policy::{Casing, CharSet, KeyPolicy};
+use crate::shape::{EntryShape, Shape};
+use crate::strategy::BuiltinStrategy;
 use crate::watch::watch;
 use anyhow::{anyhow, Error};
-use clap::{arg, command, Parser};
-use next_intl_resolver::extract_translations;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use next_intl_resolver::codemod::{fix_hardcoded_strings, move_namespace, Fix, MessageKeys};
+use next_intl_resolver::comments::DEFAULT_MESSAGE_PREFIX;
+use next_intl_resolver::convention::DEFAULT_NAMESPACE_TEMPLATE;
+use next_intl_resolver::factory::FactorySignature;
+use next_intl_resolver::options::{DynamicKeyPolicy, ExtractorOptions, ParserOptions};
+use next_intl_resolver::transform::{Transform, Transforms};
+use next_intl_resolver::tsconfig::PathAliases;
+use next_intl_resolver::Extractor;
 
-use tracing::{error, info, span, Level};
+use tracing::{error, info, span, warn, Level};
 
+pub mod attic;
+pub mod catalogs;
+pub mod config;
+pub mod coverage;
+pub mod diagnostics;
+pub mod diff;
 pub mod files;
+pub mod format;
+pub mod git_merge;
+pub mod graph;
+pub mod icu;
+pub mod jsonc;
+pub mod layout;
+pub mod lock;
+pub mod manifest;
 pub mod messages;
+pub mod migrate;
+pub mod order;
+pub mod ownership;
+pub mod policy;
+pub mod schema;
+pub mod shape;
+pub mod strategy;
+pub mod time;
+pub mod toml;
 pub mod watch;
+pub mod yaml;
+
+/// Number of files and namespaces listed as the ones with the most keys
+const TOP_COUNT: usize = 10;
+
+/// What to do with files that have syntax errors
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ParseErrorPolicy {
+    /// Log the errors and extract the messages from the parts that could be parsed
+    Warn,
+    /// Log the errors and leave the file out
+    Skip,
+    /// Stop with an error
+    Fail,
+}
+
+/// What to do with translation keys that can't be resolved statically, e.g. `t(variable)`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum DynamicKeys {
+    /// Leave them out without a warning
+    Ignore,
+    /// Log a warning for each of them
+    Warn,
+    /// Stop with an error at the first one
+    Error,
+}
+
+/// What to do when a key is used in multiple files
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ConflictPolicy {
+    /// Log the conflicts and stop with an error
+    Error,
+    /// Log the conflicts and write the messages anyway
+    Warn,
+    /// Write the messages without logging the conflicts, e.g. for shared labels
+    Allow,
+}
 
 #[derive(Parser, Debug)]
 #[command(name = "next-intl-resolver")]
 #[command(version = "0.1.0")]
 #[command(about = "Extracts next-intl messages")]
 #[command(long_about = None)]
-struct CliArguments {
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+
+    /// Without a subcommand the messages are extracted
+    #[command(flatten)]
+    args: ExtractArguments,
+}
+
+#[derive(Subcommand, Debug)]
+enum Subcommands {
+    /// Extract the messages and merge them into the catalogs
+    Extract(ExtractArguments),
+    /// Extract the messages and report the problems of the catalogs and the files that would
+    /// change, without writing them. Fails if any file would change
+    Check(CheckArguments),
+    /// Remove the messages that are no longer used from every catalog without adding the new
+    /// ones, and print them. With `--keep-removed`, `--mark-obsolete` or `--attic` they're
+    /// kept, marked obsolete or moved to the attic instead
+    Prune(CliArguments),
+    /// Add the new messages without removing the ones that are no longer used
+    Sync(CliArguments),
+    /// Print how many messages of every namespace would be added, removed or kept
+    Stats(CliArguments),
+    /// Extract the messages, then keep merging the changes of the files
+    Watch(CliArguments),
+    /// Print every file and line that uses a key, or the keys of a namespace, without writing
+    /// the catalogs
+    Find(FindArguments),
+    /// Print a graph of which files use which namespaces, and which namespaces have keys of
+    /// the same name, without writing the catalogs
+    Graph(GraphArguments),
+    /// Print how many strings are translated and how many are hard-coded in every directory,
+    /// without writing the catalogs
+    Coverage(CliArguments),
+    /// Move a namespace to another one, in the translators of the files and in the catalogs
+    /// of every locale. The catalogs are written like with `sync`
+    MoveNamespace(MoveNamespaceArguments),
+    /// Print the messages that were added, removed or changed between two catalogs, e.g. the
+    /// ones of the main branch and of a feature branch
+    Diff(DiffArguments),
+    /// Write the catalogs in the configured style, e.g. `--sort`, `--indent` or `--reshape`,
+    /// without extracting, e.g. after editing them by hand
+    Fmt(CliArguments),
+    /// Add the messages of another library to the catalog, written in the configured style
+    Migrate(MigrateArguments),
+    /// Merge JSON messages files key by key as a git merge driver, e.g.
+    /// `driver = next-intl-extractor merge %O %A %B`. Keys changed on both sides keep our
+    /// value and are reported
+    Merge(MergeArguments),
+    /// Resolve the git conflict markers in a JSON messages file key by key. Keys changed on
+    /// both sides keep our value and are reported
+    ResolveConflicts(ResolveConflictsArguments),
+}
+
+#[derive(Args, Debug)]
+struct ExtractArguments {
     /// Watch for file changes and merge them automatically
     #[arg(short, long, default_value = "false")]
     watch: bool,
 
-    /// Output file
+    /// Fail if the catalogs would change, without writing them, e.g. in CI. Same as the
+    /// `check` subcommand
+    #[arg(long, conflicts_with = "watch")]
+    check: bool,
+
+    /// Print a unified diff of the changes to every catalog file instead of writing them
+    #[arg(long, conflicts_with_all = ["watch", "check"])]
+    dry_run: bool,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+impl ExtractArguments {
+    /// Only `extract` takes `--check`, `--dry-run` and `--watch`, the other subcommands have
+    /// a mode of their own
+    fn into_mode(self) -> (Mode, CliArguments) {
+        let mode = if self.check {
+            Mode::Check
+        } else if self.dry_run {
+            Mode::DryRun
+        } else if self.watch {
+            Mode::Watch
+        } else {
+            Mode::Extract
+        };
+        (mode, self.args)
+    }
+}
+
+#[derive(Args, Debug)]
+struct MigrateArguments {
+    #[command(subcommand)]
+    from: MigrateSubcommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum MigrateSubcommands {
+    /// The messages by id that `formatjs extract` writes, with their `defaultMessage` and
+    /// `description`. With `--entry-shape extended` the descriptions are kept
+    Formatjs(FormatjsArguments),
+    /// An i18next JSON catalog, with its plural keys and `{{name}}` interpolations converted to
+    /// ICU messages
+    I18next(I18nextArguments),
+}
+
+#[derive(Args, Debug)]
+struct I18nextArguments {
+    /// The i18next catalog of one locale, e.g. `locales/en/translation.json`
+    input: PathBuf,
+
+    /// Put the messages below this namespace, e.g. `translation`
+    #[arg(long, value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+struct FormatjsArguments {
+    /// Output of `formatjs extract`
+    input: PathBuf,
+
+    /// Split the ids into namespaces at this separator, e.g. `_` for `cart_title`. Empty to
+    /// keep the ids as keys
+    #[arg(long, value_name = "SEPARATOR", default_value = ".")]
+    id_separator: String,
+
+    /// Put the messages below this namespace, e.g. `Legacy`
+    #[arg(long, value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+struct DiffArguments {
+    /// The old catalog
+    old: PathBuf,
+
+    /// The new catalog
+    new: PathBuf,
+
+    /// Print the differences as JSON
+    #[arg(long, default_value = "false")]
+    json: bool,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+struct MergeArguments {
+    /// The common ancestor of both versions, empty if there is none
+    base: PathBuf,
+
+    /// Our version, which the result is written to
+    ours: PathBuf,
+
+    /// Their version
+    theirs: PathBuf,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+struct ResolveConflictsArguments {
+    /// The file with the conflict markers, which is resolved in place
+    path: PathBuf,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+struct MoveNamespaceArguments {
+    /// Namespace to move, e.g. `Cart`
+    from: String,
+
+    /// Where to move it, e.g. `Shop.Cart`
+    to: String,
+
+    /// Move the namespace even if some translators of it can't be rewritten, e.g.
+    /// `useTranslations(NAMESPACE)`, leaving those to be changed by hand
+    #[arg(long, default_value = "false")]
+    force: bool,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+struct GraphArguments {
+    /// Print the graph for Graphviz or as JSON
+    #[arg(long, value_enum, default_value = "dot")]
+    graph_format: GraphFormat,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+struct FindArguments {
+    /// Full key of the message, e.g. `Cart.title`, or a namespace, e.g. `Cart`
+    key: String,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+struct CheckArguments {
+    #[command(subcommand)]
+    command: Option<CheckSubcommands>,
+
+    /// Only check that the catalogs are written in the configured style, e.g. sorted, without
+    /// extracting. Fails if any isn't
+    #[arg(long, default_value = "false")]
+    format_only: bool,
+
+    #[command(flatten)]
+    args: CliArguments,
+}
+
+#[derive(Subcommand, Debug)]
+enum CheckSubcommands {
+    /// List the messages of the default locale that are missing or empty in the other locales,
+    /// without extracting. Fails if any are
+    Missing(Box<CliArguments>),
+}
+
+/// What a run does with the extracted messages, see [`Subcommands`] and [`ExtractArguments`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    Extract,
+    DryRun,
+    Check,
+    CheckMissing,
+    /// Check the style of the catalogs instead of extracting
+    CheckFormat,
+    Prune,
+    Sync,
+    Stats,
+    Watch,
+    /// Print where a key is used
+    Find(String),
+    /// Print the namespaces of the files
+    Graph(GraphFormat),
+    Coverage,
+    MoveNamespace {
+        from: String,
+        to: String,
+        force: bool,
+    },
+    Fmt,
+    Migrate(Migration),
+    /// Compare two catalogs instead of extracting
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        json: bool,
+    },
+    /// Merge three versions of a catalog instead of extracting
+    Merge {
+        base: PathBuf,
+        ours: PathBuf,
+        theirs: PathBuf,
+    },
+    /// Resolve the conflict markers of a catalog instead of extracting
+    ResolveConflicts(PathBuf),
+}
+
+impl Mode {
+    /// Whether the source files may be rewritten, e.g. by `--fix`
+    fn writes_sources(&self) -> bool {
+        matches!(
+            self,
+            Mode::Extract | Mode::Prune | Mode::Sync | Mode::Watch | Mode::MoveNamespace { .. }
+        )
+    }
+}
+
+impl Cli {
+    fn into_mode(self) -> (Mode, CliArguments) {
+        match self.command {
+            None => self.args.into_mode(),
+            Some(Subcommands::Extract(args)) => args.into_mode(),
+            Some(Subcommands::Check(CheckArguments {
+                command: Some(CheckSubcommands::Missing(args)),
+                ..
+            })) => (Mode::CheckMissing, *args),
+            Some(Subcommands::Check(CheckArguments {
+                format_only: true,
+                args,
+                ..
+            })) => (Mode::CheckFormat, args),
+            Some(Subcommands::Check(CheckArguments { args, .. })) => (Mode::Check, args),
+            Some(Subcommands::Prune(args)) => (Mode::Prune, args),
+            Some(Subcommands::Sync(args)) => (Mode::Sync, args),
+            Some(Subcommands::Stats(args)) => (Mode::Stats, args),
+            Some(Subcommands::Watch(args)) => (Mode::Watch, args),
+            Some(Subcommands::Find(FindArguments { key, args })) => (Mode::Find(key), args),
+            Some(Subcommands::Graph(GraphArguments { graph_format, args })) => {
+                (Mode::Graph(graph_format), args)
+            }
+            Some(Subcommands::Coverage(args)) => (Mode::Coverage, args),
+            Some(Subcommands::MoveNamespace(MoveNamespaceArguments {
+                from,
+                to,
+                force,
+                args,
+            })) => (Mode::MoveNamespace { from, to, force }, args),
+            Some(Subcommands::Fmt(args)) => (Mode::Fmt, args),
+            Some(Subcommands::Migrate(MigrateArguments {
+                from:
+                    MigrateSubcommands::Formatjs(FormatjsArguments {
+                        input,
+                        id_separator,
+                        namespace,
+                        args,
+                    }),
+            })) => {
+                let ids = IdRule {
+                    separator: id_separator,
+                    namespace,
+                };
+                (Mode::Migrate(Migration::Formatjs { input, ids }), args)
+            }
+            Some(Subcommands::Migrate(MigrateArguments {
+                from:
+                    MigrateSubcommands::I18next(I18nextArguments {
+                        input,
+                        namespace,
+                        args,
+                    }),
+            })) => (Mode::Migrate(Migration::I18next { input, namespace }), args),
+            Some(Subcommands::Diff(DiffArguments {
+                old,
+                new,
+                json,
+                args,
+            })) => (Mode::Diff { old, new, json }, args),
+            Some(Subcommands::Merge(MergeArguments {
+                base,
+                ours,
+                theirs,
+                args,
+            })) => (Mode::Merge { base, ours, theirs }, args),
+            Some(Subcommands::ResolveConflicts(ResolveConflictsArguments { path, args })) => {
+                (Mode::ResolveConflicts(path), args)
+            }
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct CliArguments {
+    /// Take the options from this configuration file, by default the first of
+    /// `next-intl-extractor.config.json`, `.toml` or `.js` in the working directory. Options
+    /// on the command line take precedence
+    ///
+    /// Files in a directory below with a config file of their own, e.g. a package of a
+    /// monorepo, are extracted with the nearest config file instead, from its directory
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Output file, a `.json`, `.yaml` or `.toml` catalog. `{locale}` is replaced by each of
+    /// the `--locale`s and `{namespace}` writes a file per namespace, e.g.
+    /// `messages/{locale}/{namespace}.json`. Required unless every file belongs to a package
+    /// with a config file of its own
     #[clap(long, short, value_parser = clap::value_parser!(PathBuf))]
-    output_path: PathBuf,
+    output_path: Option<PathBuf>,
+
+    /// Locales to write a catalog for, replacing `{locale}` in the output path
+    #[arg(long = "locale", value_name = "LOCALE", value_delimiter = ',')]
+    locales: Vec<String>,
+
+    /// Fill the messages that are missing in the other locales with the ones of this locale
+    #[arg(long, value_name = "LOCALE")]
+    default_locale: Option<String>,
+
+    /// Prefix of the messages copied from the default locale, e.g. `[TODO] `
+    #[arg(
+        long,
+        value_name = "TEXT",
+        default_value = "",
+        requires = "default_locale"
+    )]
+    fill_prefix: String,
 
-    /// Pattern for components to find
-    #[arg(short, long, default_value = "**/*.{tsx,ts}")]
+    /// Lock file with the hashes of the default locale's messages, translations of messages that
+    /// changed since the last run are reported as outdated until they are changed too
+    #[arg(long, value_name = "PATH", requires = "default_locale")]
+    lock: Option<PathBuf>,
+
+    /// Existing catalogs to take message values from on top of the output file, e.g.
+    /// `base.json,overrides.json`. Later catalogs override the values of earlier ones
+    #[arg(long = "source", value_name = "PATH", value_delimiter = ',')]
+    sources: Vec<PathBuf>,
+
+    /// Format of the output file, instead of the one of its extension
+    #[arg(long, value_enum)]
+    format: Option<CatalogFormat>,
+
+    /// Pattern for components to find, `{a,b}` alternatives are supported
+    #[arg(short, long, default_value = DEFAULT_PATTERN)]
     pattern: String,
+
+    /// Leave out the files that match this glob pattern, e.g. `**/*.test.tsx`
+    #[arg(long = "ignore", value_name = "GLOB", value_delimiter = ',')]
+    ignored: Vec<String>,
+
+    /// What to do with translation keys that can't be resolved statically, e.g. `t(variable)`
+    #[arg(long, value_enum, default_value = "warn")]
+    dynamic_keys: DynamicKeys,
+
+    /// Fail when translation keys can't be resolved statically, after warning about all of them
+    #[arg(long, default_value = "false")]
+    fail_on_dynamic_keys: bool,
+
+    /// Follow relative imports to resolve keys and namespaces declared as constants in other files
+    #[arg(long, default_value = "false")]
+    resolve_imports: bool,
+
+    /// Follow imports through the path aliases of this `tsconfig.json`, e.g. `@/lib/i18n`.
+    /// Enables `--resolve-imports`
+    #[arg(long, value_name = "PATH")]
+    tsconfig: Option<PathBuf>,
+
+    /// Custom functions that create a translator, e.g. `useAppTranslations`
+    ///
+    /// The namespace is taken like next-intl does by default. Use `NAME=arg:INDEX`,
+    /// `NAME=prop:PROPERTY` or `NAME=template:TEMPLATE` (e.g. `useAdmin=template:Admin.{0}`) to
+    /// configure where the namespace is taken from
+    #[arg(long = "hook", value_name = "NAME[=SIGNATURE]", value_delimiter = ',')]
+    hooks: Vec<FactorySignature>,
+
+    /// Additional translator methods that take a message key, e.g. `maybe` for `t.maybe("key")`
+    #[arg(long = "translator-method", value_name = "NAME", value_delimiter = ',')]
+    translator_methods: Vec<String>,
+
+    /// Prefix of default message annotations, e.g. `default:` for
+    /// `t("title") /* default: "Welcome back" */`
+    #[arg(long, value_name = "PREFIX", default_value = DEFAULT_MESSAGE_PREFIX)]
+    default_message_prefix: String,
+
+    /// Write the descriptions of the messages for translators to this JSON file, taken from the
+    /// comment above the usage, e.g. `/** Shown on empty cart */`
+    #[arg(long, value_name = "PATH")]
+    context_output: Option<PathBuf>,
+
+    /// Write a JSON Schema of the extracted messages to this file, e.g. for editors to check
+    /// the catalogs
+    #[arg(long, value_name = "PATH")]
+    schema_output: Option<PathBuf>,
+
+    /// Check the existing catalogs against the schema of the extracted messages before they are
+    /// merged, and warn about type mismatches, unexpected and missing keys
+    #[arg(long, default_value = "false")]
+    validate: bool,
+
+    /// Write a summary of the run to this JSON file: the version, the time, the number of
+    /// scanned files and the keys added, removed and unchanged per namespace
+    #[arg(long, value_name = "PATH")]
+    manifest: Option<PathBuf>,
+
+    /// With the stats subcommand, also print the number of keys every source file uses and the
+    /// files and namespaces with the most keys, e.g. to find components that should be split
+    #[arg(long, default_value = "false")]
+    by_file: bool,
+
+    /// Write the files and locations where every message is used to this JSON file, e.g.
+    /// `messages.meta.json`, so reviewers and translators can find a message in the app
+    #[arg(long, value_name = "PATH")]
+    provenance_output: Option<PathBuf>,
+
+    /// Additional values passed to `t()` that select a variant of the message, e.g. `role` for
+    /// `t("welcome", { role })`, new messages get a select skeleton for them like `gender` does
+    #[arg(
+        long = "select-placeholder",
+        value_name = "NAME",
+        value_delimiter = ','
+    )]
+    select_placeholders: Vec<String>,
+
+    /// Convert the messages to nested objects or a flat file with dot-delimited keys, e.g.
+    /// `"Cart.checkout.title"`. By default the shape of the existing file is kept
+    #[arg(long, value_enum, value_name = "SHAPE")]
+    reshape: Option<Shape>,
+
+    /// Write a flat file with dot-delimited keys, same as `--reshape flat`
+    #[arg(long, default_value = "false", conflicts_with = "reshape")]
+    flat: bool,
+
+    /// Write every message as its value, or as an object with the value and the description
+    /// for translators from the comment above the usage, e.g.
+    /// `{"message": "Cart", "description": "Heading of the cart page"}`. By default the shape of
+    /// the existing file is kept
+    #[arg(long, value_enum, value_name = "SHAPE")]
+    entry_shape: Option<EntryShape>,
+
+    /// Indent the written catalogs with this number of spaces or `tab`, instead of like the
+    /// existing files
+    #[arg(long, value_parser = parse_indent, value_name = "INDENT")]
+    indent: Option<String>,
+
+    /// Line endings of the written catalogs, by default the ones of the existing files
+    #[arg(long, value_enum, value_name = "EOL")]
+    eol: Option<LineEnding>,
+
+    /// Whether the written catalogs end with a newline, by default like the existing files
+    #[arg(long, value_name = "BOOL")]
+    final_newline: Option<bool>,
+
+    /// Write non-ASCII characters of JSON catalogs as `\uXXXX` escapes, for pipelines that
+    /// need ASCII-only JSON
+    #[arg(long, default_value = "false")]
+    escape_unicode: bool,
+
+    /// How existing values and the default messages in the code are combined
+    #[arg(long, value_enum, default_value = "prefer-source")]
+    merge_strategy: BuiltinStrategy,
+
+    /// Copy the messages files to `<file>.bak` before they are overwritten
+    #[arg(long, default_value = "false")]
+    backup: bool,
+
+    /// Keep the messages whose keys are no longer used in the code, they are listed as warnings
+    #[arg(long, default_value = "false")]
+    keep_removed: bool,
+
+    /// Move the values of the messages whose keys are no longer used to this JSON file, below the
+    /// date they were removed. `{locale}` is replaced like in the output path
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["keep_removed", "mark_obsolete"])]
+    attic: Option<PathBuf>,
+
+    /// Move the messages whose keys are no longer used below `__obsolete__` instead of removing
+    /// them, they are moved back when used again
+    #[arg(long, default_value = "false", conflicts_with = "keep_removed")]
+    mark_obsolete: bool,
+
+    /// Give a new message the value of the removed message it replaces, when both are the only
+    /// added and removed message of the file the removed one was last used in, so renaming a
+    /// key keeps its translations. The `--provenance-output` of the last run tells that file
+    #[arg(long, default_value = "false", requires = "provenance_output")]
+    tm: bool,
+
+    /// How the keys of the written messages are ordered
+    #[arg(long, value_enum, default_value = "existing")]
+    sort: SortOrder,
+
+    /// Warn about user-visible strings in JSX text and props such as `title`, `alt` and
+    /// `placeholder` that are not translated
+    #[arg(long, default_value = "false")]
+    find_hardcoded_strings: bool,
+
+    /// Rewrite hard-coded strings in components to translator calls, e.g. `<h1>Welcome</h1>` to
+    /// `<h1>{t('welcome')}</h1>`, and add them to the messages with the original text. With
+    /// `extract --dry-run` the rewrites are printed, commands that don't write ignore it
+    #[arg(long, default_value = "false")]
+    fix: bool,
+
+    /// JSON file with component props that take a message key and their namespace, e.g.
+    /// `{"Button": {"labelKey": "Buttons"}}` to extract `<Button labelKey="save" />`
+    #[arg(long, value_name = "PATH")]
+    component_props: Option<PathBuf>,
+
+    /// JSON file that maps namespaces to the path globs that may use them, e.g.
+    /// `{"Checkout.*": "app/checkout/**"}`, usages elsewhere are reported as warnings
+    #[arg(long, value_name = "PATH")]
+    ownership: Option<PathBuf>,
+
+    /// Derive the namespace of `useTranslations()` without a namespace from the file path, e.g.
+    /// `app/cart/page.tsx` to `Cart`. The template can use `{path}`, `{dir}` and `{file}`
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_NAMESPACE_TEMPLATE
+    )]
+    namespace_from_path: Option<String>,
+
+    /// Transform the extracted namespaces before they are merged, in order. One of
+    /// `kebab-case`, `snake-case`, `camel-case`, `pascal-case`, `prefix:TEXT`,
+    /// `strip-prefix:TEXT` or `strip-suffix:TEXT`
+    #[arg(
+        long = "namespace-transform",
+        value_name = "TRANSFORM",
+        value_delimiter = ','
+    )]
+    namespace_transforms: Vec<Transform>,
+
+    /// Transform the extracted keys before they are merged, in order, like
+    /// `--namespace-transform`
+    #[arg(
+        long = "key-transform",
+        value_name = "TRANSFORM",
+        value_delimiter = ','
+    )]
+    key_transforms: Vec<Transform>,
+
+    /// Compare the namespaces passed to `NextIntlClientProvider` (e.g. with
+    /// `pick(messages, 'Cart')`) with the ones client components use, and warn about
+    /// namespaces that are shipped to the client without being used
+    #[arg(long, default_value = "false")]
+    check_client_messages: bool,
+
+    /// Maximum number of parts of a full message key, e.g. 3 for `Checkout.Summary.total`
+    #[arg(long, value_name = "DEPTH")]
+    max_key_depth: Option<usize>,
+
+    /// Characters allowed in namespaces and keys, like a regex character class without
+    /// brackets, e.g. `a-zA-Z0-9_`
+    #[arg(long, value_name = "CHARS")]
+    key_chars: Option<String>,
+
+    /// Casing of the parts of message keys, namespaces are not checked
+    #[arg(long, value_enum, value_name = "CASING")]
+    key_casing: Option<Casing>,
+
+    /// What to do when the same key of a namespace is used in multiple files
+    #[arg(long, value_enum, default_value = "error")]
+    on_conflict: ConflictPolicy,
+
+    /// What to do with files that have syntax errors
+    #[arg(long, value_enum, default_value = "warn")]
+    on_parse_error: ParseErrorPolicy,
+
+    /// Parse JavaScript files as TypeScript, e.g. for type annotations in `.js` files
+    #[arg(long, default_value = "false")]
+    parse_typescript: bool,
+
+    /// Parse JSX in files that don't allow it by extension, e.g. `.ts` files with JSX
+    #[arg(long, default_value = "false")]
+    parse_jsx: bool,
+
+    /// Allow `return` statements outside of functions
+    #[arg(long, default_value = "false")]
+    allow_return_outside_function: bool,
+}
+
+/// Read the namespaces of the translatable component props, by component and prop name
+fn read_component_props(path: &Path) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))
+}
+
+/// Wrap the hard-coded strings of a file in translator calls, with the rewritten file as a
+/// change that is only written once the messages can be merged
+fn fix_file(
+    file: &Path,
+    template: &str,
+    keys: &mut MessageKeys,
+) -> Result<(Fix, Option<FileChange>), Error> {
+    let source_text = std::fs::read_to_string(file)?;
+    let mut fix = match fix_hardcoded_strings(file, &source_text, template, keys) {
+        Ok(fix) => fix,
+        Err(e) => {
+            warn!("{}", e);
+            return Ok((Fix::default(), None));
+        }
+    };
+    if fix.is_empty() {
+        return Ok((fix, None));
+    }
+    let change = FileChange {
+        path: file.to_path_buf(),
+        old: Some(source_text),
+        new: std::mem::take(&mut fix.source_text),
+    };
+    Ok((fix, Some(change)))
+}
+
+/// Colors only for a terminal, see https://no-color.org
+fn color_output() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Move the translators of a namespace in the files to another namespace
+///
+/// Nothing is written if any translator of the namespace can't be rewritten, e.g.
+/// `useTranslations(NAMESPACE)`, as its messages would be left behind. They're printed, and
+/// with `force` the others are moved anyway
+fn move_files_namespace(
+    extractor: &Extractor,
+    files: &[PathBuf],
+    from: &str,
+    to: &str,
+    force: bool,
+) -> Result<(), Error> {
+    let mut moved = Vec::new();
+    let mut unmovable = 0;
+    for file in files {
+        let source_text = std::fs::read_to_string(file)?;
+        let extraction = match extractor.extract_with_source(file, &source_text) {
+            Ok(extraction) => extraction,
+            Err(e) => {
+                warn!("{}", e);
+                continue;
+            }
+        };
+        let namespace_move =
+            move_namespace(&source_text, &extraction.translator_namespaces, from, to);
+        if !extraction.parse_errors.is_empty()
+            && (namespace_move.source_text.is_some() || !namespace_move.unmovable.is_empty())
+        {
+            println!(
+                "{}: can't be parsed to move namespace {from}",
+                file.display()
+            );
+            unmovable += 1;
+            continue;
+        }
+        for usage in &namespace_move.unmovable {
+            println!(
+                "{}:{}: translator of {} can't be moved, its namespace isn't a string",
+                file.display(),
+                usage.location,
+                usage.namespace
+            );
+        }
+        unmovable += namespace_move.unmovable.len();
+        if let Some(source_text) = namespace_move.source_text {
+            moved.push((file, source_text));
+        }
+    }
+    if unmovable > 0 && !force {
+        return Err(anyhow!(
+            "{unmovable} translator(s) of namespace {from} can't be moved, change them by hand \
+             or pass --force to move the others"
+        ));
+    }
+    for (file, source_text) in moved {
+        std::fs::write(file, source_text)?;
+        info!("Moved namespace {} to {} in {}", from, to, file.display());
+    }
+    Ok(())
+}
+
+/// The files of a conflict with their number of usages, e.g. `a.tsx (1 usage), b.tsx (2 usages)`
+fn conflict_files(conflict: &NamespaceConflict) -> String {
+    conflict
+        .usage_counts()
+        .into_iter()
+        .map(|(file, count)| match count {
+            1 => format!("{file} (1 usage)"),
+            count => format!("{file} ({count} usages)"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parse the command line arguments on top of the options of the configuration file, if any
+fn parse_arguments() -> Result<(Mode, CliArguments), Error> {
+    let args: Vec<String> = std::env::args().collect();
+    // The path of the config file is needed before the arguments can be parsed
+    let config_path = args[1..]
+        .iter()
+        .position(|arg| arg == "--config")
+        .and_then(|index| args.get(index + 2).map(PathBuf::from))
+        .or_else(|| {
+            args.iter()
+                .find_map(|arg| arg.strip_prefix("--config=").map(PathBuf::from))
+        })
+        .or_else(|| {
+            std::env::var(format!("{}CONFIG", config::ENV_PREFIX))
+                .ok()
+                .map(PathBuf::from)
+        })
+        .or_else(|| config::find(Path::new(".")));
+    arguments_with_config(args, config_path.as_deref())
+}
+
+/// Parse command line arguments on top of the options of the `NEXT_INTL_EXTRACTOR_*`
+/// environment variables and then the ones of a configuration file
+///
+/// The options go after the subcommands, if any, e.g. `check missing`
+fn arguments_with_config(
+    args: Vec<String>,
+    config_path: Option<&Path>,
+) -> Result<(Mode, CliArguments), Error> {
+    let command = Cli::command();
+    let mut options_start = 1;
+    let mut subcommand = &command;
+    while let Some(nested) = args
+        .get(options_start)
+        .and_then(|arg| subcommand.find_subcommand(arg))
+    {
+        subcommand = nested;
+        options_start += 1;
+    }
+    let mut given = config::given_options(&command, &args[options_start..]);
+    let env_options = config::env_options(std::env::vars());
+    let mut option_args = config::to_args(
+        &command,
+        subcommand,
+        &env_options,
+        &given,
+        "the environment",
+    )?;
+    given.extend(env_options.keys().map(|key| key.replace('_', "-")));
+    if let Some(config_path) = config_path {
+        info!("Using config file {}", config_path.display());
+        let options = config::load(config_path)?;
+        option_args.extend(config::to_args(
+            &command,
+            subcommand,
+            &options,
+            &given,
+            "the config file",
+        )?);
+    }
+    // Options of the config file and environment go first, so that a `--` on the command line
+    // stays last
+    let mut parsed_args = args[..options_start].to_vec();
+    parsed_args.extend(option_args);
+    parsed_args.extend(args[options_start..].iter().cloned());
+    Ok(Cli::parse_from(parsed_args).into_mode())
+}
+
+/// Extract the files of a package with its own config file, from the directory of the config
+/// file. The config file applies instead of the one of the working directory
+fn extract_package(config_path: &Path, files: &[PathBuf]) -> Result<(), Error> {
+    let dir = config_path.parent().unwrap_or(Path::new("."));
+    // The options on the command line still apply, except for the config file
+    let mut args = Vec::new();
+    let mut command_line = std::env::args();
+    while let Some(arg) = command_line.next() {
+        if arg == "--config" {
+            command_line.next();
+        } else if !arg.starts_with("--config=") {
+            args.push(arg);
+        }
+    }
+    let (mode, args) = arguments_with_config(args, Some(config_path))?;
+
+    // Paths of the package are relative to its directory, like the options of its config file
+    let pattern = FilePattern::new(&args.pattern)?.with_ignored(&args.ignored)?;
+    let files: Vec<PathBuf> = files
+        .iter()
+        .filter_map(|file| file.strip_prefix(dir).ok())
+        .filter(|file| pattern.matches_path(file))
+        .map(Path::to_path_buf)
+        .collect();
+    if files.is_empty() {
+        return Ok(());
+    }
+    let mode = if mode == Mode::Watch {
+        warn!(
+            "Watch mode only watches the files outside of packages, not the ones of {}",
+            dir.display()
+        );
+        Mode::Extract
+    } else {
+        mode
+    };
+
+    info!(
+        "Extracting the messages of the package in {}",
+        dir.display()
+    );
+    let _working_dir = WorkingDir::change_to(dir)?;
+    extract(&args, files, mode)
+}
+
+/// The working directory before it was changed, which is changed back to when this is dropped,
+/// so that it's also restored when an extraction fails
+struct WorkingDir(PathBuf);
+
+impl WorkingDir {
+    fn change_to(dir: &Path) -> Result<Self, Error> {
+        let previous = std::env::current_dir()?;
+        std::env::set_current_dir(dir)?;
+        Ok(Self(previous))
+    }
+}
+
+impl Drop for WorkingDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::env::set_current_dir(&self.0) {
+            error!(
+                "Failed to change back to the directory {}: {}",
+                self.0.display(),
+                e
+            );
+        }
+    }
 }
 
 fn run() -> Result<(), Error> {
@@ -39,91 +1012,643 @@ fn run() -> Result<(), Error> {
     info!("Starting run function");
 
     // Parse arguments
-    let args = CliArguments::parse();
-    info!("Arguments parsed: {:?}", args);
-
-    // Check that output file is a .json file
-    if args.output_path.extension().unwrap_or_default() != "json" {
-        error!("Invalid output file extension");
-        return Err(anyhow!("Output file must be a .json file"));
-    }
-
-    // Check if output file exists, if not create it
-    if !args.output_path.exists() {
-        let parent = args
-            .output_path
-            .parent()
-            .ok_or_else(|| anyhow!("Invalid output path"))?;
-        std::fs::create_dir_all(parent)?;
-        std::fs::write(&args.output_path, "{}")?;
-        info!(
-            "Output file does not exist yet. Created: {:?}",
-            args.output_path
-        );
+    let (mode, args) = parse_arguments()?;
+    info!("Arguments parsed: {:?} {:?}", mode, args);
+
+    if let Mode::Diff { old, new, json } = &mode {
+        return print_catalog_diff(old, new, *json);
+    }
+
+    let conflicting_keys = match &mode {
+        Mode::Merge { base, ours, theirs } => Some(git_merge::merge_driver(base, ours, theirs)?),
+        Mode::ResolveConflicts(path) => Some(git_merge::resolve_conflicts(path)?),
+        _ => None,
+    };
+    if let Some(conflicting_keys) = conflicting_keys {
+        if conflicting_keys.is_empty() {
+            return Ok(());
+        }
+        for key in &conflicting_keys {
+            warn!(
+                "Message '{}' was changed on both sides, kept our value",
+                key
+            );
+        }
+        return Err(anyhow!(
+            "{} message(s) were changed on both sides",
+            conflicting_keys.len()
+        ));
     }
 
-    // Initialize message handler
-    let mut message_handler = MessageHandler::new(&args.output_path)?;
+    // Modes that only read the catalogs don't need the files
+    if matches!(
+        mode,
+        Mode::CheckMissing | Mode::CheckFormat | Mode::Fmt | Mode::Migrate(_)
+    ) {
+        return extract(&args, Vec::new(), mode);
+    }
 
     // Find and process files
-    let files = find_files(&args.pattern)?;
+    let files = find_files(&args.pattern, &args.ignored)?;
 
     if files.is_empty() {
         return Err(anyhow!("No files found for pattern: {}", args.pattern));
     }
 
-    for file in files {
-        let translations = extract_translations(&file);
-
-        if let Ok(translations) = translations {
-            for (namespace, keys) in translations.iter() {
-                for key in keys {
-                    message_handler.add_extracted_message(
-                        namespace.clone(),
-                        key.to_string(),
-                        file.to_string_lossy().into_owned(),
-                    );
-                }
+    // Files of a package with its own config file are extracted with that config file
+    let (files, packages) = config::group_by_package(files);
+    for (config_path, package_files) in &packages {
+        extract_package(config_path, package_files)?;
+    }
+    if !packages.is_empty() && (files.is_empty() || args.output_path.is_none()) {
+        return Ok(());
+    }
+    extract(&args, files, mode)
+}
+
+/// Extract the messages of the files and merge them into the catalogs as the mode says, then
+/// keep watching the files in watch mode
+fn extract(args: &CliArguments, files: Vec<PathBuf>, mode: Mode) -> Result<(), Error> {
+    let output_path = args
+        .output_path
+        .clone()
+        .ok_or_else(|| anyhow!("Pass the output file with --output-path"))?;
+
+    // Check that output file is a catalog format we can write
+    let catalog_format = match args.format {
+        Some(format) => format,
+        None => CatalogFormat::from_path(&output_path).inspect_err(|_| {
+            error!("Invalid output file extension");
+        })?,
+    };
+
+    let key_policy = KeyPolicy {
+        max_depth: args.max_key_depth,
+        chars: args.key_chars.as_deref().map(CharSet::new).transpose()?,
+        casing: args.key_casing,
+    };
+
+    // Initialize the message handler of every locale
+    let mut catalogs = Catalogs::open(
+        &output_path,
+        &args.locales,
+        catalog_format,
+        |handler, locale| {
+            // Source catalogs can be per locale too
+            let sources: Vec<PathBuf> = match locale {
+                Some(locale) => args
+                    .sources
+                    .iter()
+                    .map(|path| layout::fill(path, LOCALE_PLACEHOLDER, locale))
+                    .collect(),
+                None => args.sources.clone(),
+            };
+            let mut handler = handler
+                .with_source_layers(&sources)?
+                .with_select_placeholders(args.select_placeholders.clone())
+                .with_sort_order(args.sort);
+            if args.flat {
+                handler = handler.with_shape(Shape::Flat);
+            }
+            if let Some(shape) = args.reshape {
+                handler = handler.with_shape(shape);
+            }
+            handler = handler.with_format_overrides(FormatOverrides {
+                indent: args.indent.clone(),
+                line_ending: args.eol,
+                final_newline: args.final_newline,
+                escape_unicode: args.escape_unicode,
+            });
+            if let Some(entry_shape) = args.entry_shape {
+                handler = handler.with_entry_shape(entry_shape);
             }
+            if let Some(context_path) = &args.context_output {
+                handler = handler.with_context_output(context_path.clone());
+            }
+            if let Some(provenance_path) = &args.provenance_output {
+                handler = handler.with_provenance_output(provenance_path.clone());
+            }
+            if args.keep_removed {
+                handler = handler.with_removed_keys(RemovedKeys::Keep);
+            }
+            if args.mark_obsolete {
+                handler = handler.with_removed_keys(RemovedKeys::Obsolete);
+            }
+            match mode {
+                Mode::Sync
+                | Mode::MoveNamespace { .. }
+                | Mode::CheckFormat
+                | Mode::Fmt
+                | Mode::Migrate(_) => handler = handler.with_removed_keys(RemovedKeys::Keep),
+                Mode::Prune => handler = handler.without_new_keys(),
+                _ => {}
+            }
+            handler = handler
+                .with_key_policy(key_policy.clone())
+                .with_merge_strategy(args.merge_strategy.strategy());
+            if args.tm {
+                handler = handler.with_translation_memory();
+            }
+            if args.backup {
+                handler = handler.with_backup()?;
+            }
+            if let Some(attic) = &args.attic {
+                let attic = match locale {
+                    Some(locale) => layout::fill(attic, LOCALE_PLACEHOLDER, locale),
+                    None => attic.clone(),
+                };
+                handler = handler.with_attic(attic);
+            }
+            Ok(handler)
+        },
+    )?;
+    if let Some(locale) = &args.default_locale {
+        catalogs = catalogs.with_default_locale(locale.clone(), args.fill_prefix.clone())?;
+    }
+    if let Some(lock_path) = &args.lock {
+        catalogs = catalogs.with_lock(lock_path.clone())?;
+    }
+
+    if let Mode::Migrate(migration) = &mode {
+        let (messages, descriptions) = migration.messages()?;
+        catalogs.add_source_messages(messages, descriptions)?;
+    }
+    if matches!(mode, Mode::Fmt | Mode::Migrate(_)) {
+        // Without extracted messages every message is kept, and written in the configured style
+        for catalog in catalogs.iter() {
+            catalog.handler.write_merged_messages(&catalog.path)?;
+        }
+        return Ok(());
+    }
+    if mode == Mode::CheckFormat {
+        let changes = catalogs.unformatted_files()?;
+        for change in &changes {
+            println!("{}", change.summary());
+        }
+        if changes.is_empty() {
+            return Ok(());
         }
+        return Err(anyhow!(
+            "{} catalog file(s) are not formatted, run fmt to format them",
+            changes.len()
+        ));
     }
 
-    // Check for conflicts before proceeding
-    let conflicts = message_handler.get_conflicts();
-    if !conflicts.is_empty() {
-        error!("Found namespace conflicts:");
-        for conflict in conflicts {
-            error!(
-                "Namespace '{}' key '{}' is used in multiple files:",
-                conflict.namespace, conflict.key
+    if mode == Mode::CheckMissing {
+        let Some(default_locale) = &args.default_locale else {
+            return Err(anyhow!(
+                "Pass the locale to compare the others with with --default-locale"
+            ));
+        };
+        let missing_keys = catalogs.missing_keys();
+        for (locale, keys) in &missing_keys {
+            println!(
+                "{}: {} message(s) of {} missing",
+                locale,
+                keys.len(),
+                default_locale
             );
-            for file in &conflict.files {
-                error!("  - {}", file);
+            for key in keys {
+                println!("  {key}");
+            }
+        }
+        let count: usize = missing_keys.values().map(Vec::len).sum();
+        if count > 0 {
+            return Err(anyhow!(
+                "{count} message(s) are missing in the other locales"
+            ));
+        }
+        return Ok(());
+    }
+
+    let mut options = ExtractorOptions::new()
+        .with_translator_factories(args.hooks.clone())
+        .with_translator_methods(args.translator_methods.clone())
+        .with_default_message_prefix(args.default_message_prefix.clone())
+        .with_dynamic_key_policy(match args.dynamic_keys {
+            DynamicKeys::Ignore => DynamicKeyPolicy::Ignore,
+            DynamicKeys::Warn => DynamicKeyPolicy::Report,
+            DynamicKeys::Error => DynamicKeyPolicy::Error,
+        })
+        .with_parser_options(ParserOptions {
+            typescript: args.parse_typescript,
+            jsx: args.parse_jsx,
+            allow_return_outside_function: args.allow_return_outside_function,
+        });
+    if let Some(path) = &args.component_props {
+        options = options.with_translatable_props(read_component_props(path)?);
+    }
+
+    let mut extractor = Extractor::new()
+        .with_options(options)
+        .with_transforms(Transforms {
+            namespace: args.namespace_transforms.clone(),
+            key: args.key_transforms.clone(),
+        });
+    if args.resolve_imports {
+        extractor = extractor.with_import_resolution();
+    }
+    if let Some(path) = &args.tsconfig {
+        extractor = extractor.with_path_aliases(PathAliases::from_tsconfig(path)?);
+    }
+    if args.find_hardcoded_strings || mode == Mode::Coverage {
+        extractor = extractor.with_hardcoded_string_detection();
+    }
+    if let Some(template) = &args.namespace_from_path {
+        extractor = extractor.with_path_namespaces(template.clone());
+    }
+
+    let ownership = args
+        .ownership
+        .as_deref()
+        .map(Ownership::from_file)
+        .transpose()?;
+    let file_count = files.len();
+    let mut dynamic_key_count = 0;
+    let mut provided_messages = Vec::new();
+    let mut client_namespaces = BTreeSet::new();
+    let mut graph = NamespaceGraph::default();
+    let mut coverage = Coverage::default();
+    // Generated keys don't overwrite the messages of any catalog
+    let mut message_keys = MessageKeys::default();
+    if args.fix {
+        for catalog in catalogs.iter() {
+            message_keys.add_catalog(catalog.handler.source_messages());
+        }
+    }
+    let template = args
+        .namespace_from_path
+        .as_deref()
+        .unwrap_or(DEFAULT_NAMESPACE_TEMPLATE);
+    let mut fixed_files = Vec::new();
+    // The files are rewritten before they're extracted, and only if the catalogs move too
+    if let Mode::MoveNamespace { from, to, force } = &mode {
+        move_files_namespace(&extractor, &files, from, to, *force)?;
+        catalogs.move_namespace(from, to);
+    }
+    for file in files {
+        let (fix, fixed_file) = if args.fix && (mode.writes_sources() || mode == Mode::DryRun) {
+            fix_file(&file, template, &mut message_keys)?
+        } else {
+            (Fix::default(), None)
+        };
+
+        // Fixed files are written after the conflict check, so the messages are extracted from
+        // their text
+        let extraction = match &fixed_file {
+            Some(fixed_file) => extractor.extract_with_source(&file, &fixed_file.new),
+            None => extractor.extract(&file),
+        };
+        fixed_files.extend(fixed_file);
+
+        let extraction =
+            extraction.map_err(|e| anyhow!("Failed to extract {}: {}", file.display(), e))?;
+        if !extraction.parse_errors.is_empty() {
+            warn_parse_errors(&file, &extraction.parse_errors);
+            match args.on_parse_error {
+                ParseErrorPolicy::Warn => {}
+                ParseErrorPolicy::Skip => continue,
+                ParseErrorPolicy::Fail => {
+                    return Err(anyhow!("Failed to parse {}", file.display()))
+                }
+            }
+        }
+
+        warn_dynamic_keys(&file, &extraction.dynamic_keys);
+        warn_unresolved_namespaces(&file, &extraction.unresolved_namespaces);
+        dynamic_key_count += extraction.dynamic_keys.len();
+        warn_hardcoded_strings(&file, &extraction.hardcoded_strings);
+        warn_catalog_usages(&file, &extraction.catalog_usages);
+        warn_import_misuses(&file, &extraction.import_misuses);
+        if let Some(ownership) = &ownership {
+            let mut namespaces: Vec<&str> =
+                extraction.translations.keys().map(String::as_str).collect();
+            namespaces.sort();
+            warn_ownership_violations(&file, &ownership.violations(&file, namespaces));
+        }
+
+        if extraction.client_component {
+            client_namespaces.extend(extraction.translations.keys().cloned());
+        }
+        provided_messages.extend(
+            extraction
+                .provided_messages
+                .iter()
+                .map(|provided| (file.clone(), provided.clone())),
+        );
+
+        if let Mode::Graph(_) = mode {
+            graph.add(&file.to_string_lossy(), &extraction.translations);
+        }
+        let counts = Counts {
+            translated: extraction.usages.len(),
+            hardcoded: extraction.hardcoded_strings.len(),
+        };
+        coverage.add(&file, counts);
+        catalogs.add_extraction(extraction, file.to_string_lossy().into_owned());
+
+        // The original text of wrapped strings is used as the value of the new messages
+        let transforms = extractor.transforms();
+        for (namespace, messages) in fix.messages {
+            for (key, text) in messages {
+                catalogs.add_default_message(
+                    &transforms.namespace(&namespace),
+                    &transforms.key(&key),
+                    text,
+                );
             }
         }
+    }
+
+    if args.check_client_messages {
+        warn_client_messages(&provided_messages, &client_namespaces);
+    }
+
+    if args.fail_on_dynamic_keys && dynamic_key_count > 0 {
         return Err(anyhow!(
-            "Namespace conflicts detected. Please resolve conflicts before proceeding."
+            "Found {} dynamic translation key(s). Please use static keys or disable --fail-on-dynamic-keys.",
+            dynamic_key_count
         ));
     }
 
+    if let Mode::Find(key) = &mode {
+        return print_usages(&catalogs, key);
+    }
+    if let Mode::Graph(format) = mode {
+        print!("{}", graph.to_string(format));
+        return Ok(());
+    }
+    if mode == Mode::Coverage {
+        print!("{}", coverage.to_text());
+        return Ok(());
+    }
+
+    // Check for conflicts before proceeding
+    warn_policy_violations(catalogs.get_policy_violations());
+
+    let conflicts = catalogs.get_conflicts();
+    match args.on_conflict {
+        ConflictPolicy::Error if !conflicts.is_empty() => {
+            error!("Found namespace conflicts:");
+            for conflict in conflicts {
+                error!(
+                    "Namespace '{}' key '{}' is used in multiple files: {}",
+                    conflict.namespace,
+                    conflict.key,
+                    conflict_files(conflict)
+                );
+                for usage in &conflict.usages {
+                    error!("  - {}", usage);
+                }
+                error!("  {}", conflict.suggestion());
+            }
+            return Err(anyhow!(
+                "Namespace conflicts detected. Please resolve conflicts before proceeding."
+            ));
+        }
+        ConflictPolicy::Warn => {
+            for conflict in conflicts {
+                warn!(
+                    "Namespace '{}' key '{}' is used in multiple files: {}",
+                    conflict.namespace,
+                    conflict.key,
+                    conflict_files(conflict)
+                );
+                for usage in &conflict.usages {
+                    warn!("  - {}", usage);
+                }
+                warn!("  {}", conflict.suggestion());
+            }
+        }
+        ConflictPolicy::Error | ConflictPolicy::Allow => {}
+    }
+
+    if let Some(catalog) = catalogs.iter().next() {
+        let schema = catalog.handler.schema();
+        if let Some(schema_path) = &args.schema_output {
+            write_formatted(schema_path, |format| format.to_string(&schema))?;
+        }
+        if args.validate {
+            for catalog in catalogs.iter() {
+                let errors = schema::validate(&schema, catalog.handler.source_messages());
+                warn_schema_errors(&catalog.path, &errors);
+            }
+        }
+    }
+
+    for catalog in catalogs.iter() {
+        warn_icu_errors(
+            &catalog.path,
+            &icu::check_messages(catalog.handler.source_messages()),
+        );
+        warn_placeholder_mismatches(&catalog.path, &catalog.handler.placeholder_mismatches());
+    }
+
+    match mode {
+        Mode::Check => {
+            let changes = catalogs.changed_files()?;
+            for change in &changes {
+                println!("{}", change.summary());
+                // The keys to act on, e.g. `  + Cart.total: "Cart.total"`
+                if let Some(diff) = change.key_diff().filter(|diff| !diff.is_empty()) {
+                    for line in diff.to_text().lines() {
+                        println!("  {line}");
+                    }
+                }
+            }
+            if changes.is_empty() {
+                return Ok(());
+            }
+            return Err(anyhow!(
+                "{} catalog file(s) would change, run extract to update them",
+                changes.len()
+            ));
+        }
+        Mode::Stats => {
+            print_stats(&catalogs);
+            if args.by_file {
+                print_file_stats(&catalogs);
+            }
+            return Ok(());
+        }
+        Mode::DryRun => {
+            let color = color_output();
+            for change in &fixed_files {
+                print!("{}", change.unified(color));
+            }
+            for change in catalogs.changed_files()? {
+                print!("{}", change.unified(color));
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
     // If no conflicts, proceed with merging
-    message_handler.write_merged_messages(&args.output_path)?;
+    for change in &fixed_files {
+        std::fs::write(&change.path, &change.new)?;
+        info!("Wrapped hard-coded strings in {}", change.path.display());
+    }
+    catalogs.write_merged_messages()?;
+    if mode == Mode::Prune {
+        print_pruned(&catalogs, args);
+    }
+    for catalog in catalogs.iter() {
+        if args.keep_removed {
+            warn_kept_keys(&catalog.path, &catalog.handler.removed_keys());
+        } else if args.mark_obsolete {
+            warn_obsolete_keys(&catalog.path, &catalog.handler.removed_keys());
+        }
+    }
+
+    if let Some(manifest_path) = &args.manifest {
+        write_manifest(manifest_path, file_count, &catalogs)?;
+    }
 
     info!("Successfully merged messages");
 
     // Check if watch mode is enabled
-    if args.watch {
+    if mode == Mode::Watch {
         info!("Watch mode enabled. Watching for file changes...");
-        watch(&args.pattern, &args.output_path, &mut message_handler)?;
+        watch(&args.pattern, &args.ignored, &mut catalogs, &extractor)?;
+    }
+
+    Ok(())
+}
+
+/// Print the messages that a prune removed from every catalog, or kept, marked obsolete or
+/// moved to the attic as the arguments say
+fn print_pruned(catalogs: &Catalogs, args: &CliArguments) {
+    let action = if args.keep_removed {
+        "Kept"
+    } else if args.mark_obsolete {
+        "Marked obsolete"
+    } else if args.attic.is_some() {
+        "Moved to the attic"
+    } else {
+        "Removed"
+    };
+    for catalog in catalogs.iter() {
+        let removed_keys = catalog.handler.removed_keys();
+        println!(
+            "{}: {} {} unused message(s)",
+            catalog.path.display(),
+            action,
+            removed_keys.len()
+        );
+        for key in removed_keys {
+            println!("  {key}");
+        }
+    }
+}
+
+/// Print how many keys of every namespace the catalogs would get, lose or keep
+fn print_stats(catalogs: &Catalogs) {
+    for catalog in catalogs.iter() {
+        println!("{}", catalog.path.display());
+        for (namespace, changes) in catalog.handler.key_changes() {
+            println!(
+                "  {}: {} added, {} removed, {} unchanged",
+                namespace, changes.added, changes.removed, changes.unchanged
+            );
+        }
+    }
+}
+
+/// Print the messages that were added, removed or changed between two catalogs of any format
+fn print_catalog_diff(old: &Path, new: &Path, json: bool) -> Result<(), Error> {
+    let open = |path: &Path| {
+        if !path.is_file() {
+            return Err(anyhow!("Catalog {} does not exist", path.display()));
+        }
+        MessageHandler::open(path, CatalogFormat::from_path(path)?)
+    };
+    let diff = KeyDiff::new(open(old)?.source_messages(), open(new)?.source_messages());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff.to_json())?);
+    } else {
+        print!("{}", diff.to_text());
     }
+    Ok(())
+}
 
+/// Print where a key or the keys below it are used, e.g. `app/page.tsx:12:18`
+fn print_usages(catalogs: &Catalogs, key: &str) -> Result<(), Error> {
+    // Every catalog has the same extracted messages
+    let provenance = catalogs
+        .iter()
+        .next()
+        .map(|catalog| catalog.handler.provenance())
+        .unwrap_or_default();
+    let prefix = format!("{key}.");
+    let mut found = false;
+    for (full_key, usages) in &provenance {
+        if full_key != key && !full_key.starts_with(&prefix) {
+            continue;
+        }
+        for usage in usages
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(serde_json::Value::as_str)
+        {
+            if full_key == key {
+                println!("{usage}");
+            } else {
+                println!("{usage} {full_key}");
+            }
+            found = true;
+        }
+    }
+    if !found {
+        return Err(anyhow!("Key '{key}' is not used in any of the files"));
+    }
     Ok(())
 }
 
+/// Print the number of keys every source file uses, and the files and namespaces with the most
+/// keys
+fn print_file_stats(catalogs: &Catalogs) {
+    // Every catalog has the same extracted messages
+    let Some(catalog) = catalogs.iter().next() else {
+        return;
+    };
+    let key_counts: Vec<(String, usize)> = catalog
+        .handler
+        .keys_by_file()
+        .into_iter()
+        .map(|(file, keys)| (file, keys.len()))
+        .collect();
+    println!("Keys by file");
+    for (file, count) in &key_counts {
+        println!("  {file}: {count}");
+    }
+
+    let namespace_counts: Vec<(String, usize)> = catalog
+        .handler
+        .key_changes()
+        .into_iter()
+        .map(|(namespace, changes)| (namespace, changes.added + changes.unchanged))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    for (title, mut counts) in [
+        ("Top files", key_counts),
+        ("Top namespaces", namespace_counts),
+    ] {
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        println!("{title}");
+        for (name, count) in counts.iter().take(TOP_COUNT) {
+            println!("  {name}: {count}");
+        }
+    }
+}
+
 fn main() -> ExitCode {
     // Initialize tracing
+    // Logs go to stderr, stdout is for the output of the commands, e.g. `graph` or `diff`
     let subscriber = tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
         .finish();
 
     tracing::subscriber::set_global_default(subscriber)
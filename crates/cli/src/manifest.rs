@@ -0,0 +1,83 @@
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+use crate::catalogs::Catalogs;
+use crate::messages::write_formatted;
+use crate::time;
+
+/// Summary of a run for CI dashboards and caches, e.g.
+///
+/// ```json
+/// {
+///   "version": "0.1.1",
+///   "timestamp": "2024-05-01T09:30:00Z",
+///   "files": 42,
+///   "catalogs": {
+///     "messages/en.json": {
+///       "Cart": { "added": 1, "removed": 0, "unchanged": 12 }
+///     }
+///   }
+/// }
+/// ```
+pub fn write_manifest(path: &Path, file_count: usize, catalogs: &Catalogs) -> Result<()> {
+    let catalogs: Map<String, Value> = catalogs
+        .iter()
+        .map(|catalog| {
+            let namespaces: Map<String, Value> = catalog
+                .handler
+                .key_changes()
+                .into_iter()
+                .map(|(namespace, changes)| {
+                    let changes = json!({
+                        "added": changes.added,
+                        "removed": changes.removed,
+                        "unchanged": changes.unchanged,
+                    });
+                    (namespace, changes)
+                })
+                .collect();
+            (
+                catalog.path.display().to_string(),
+                Value::Object(namespaces),
+            )
+        })
+        .collect();
+    let manifest = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "timestamp": time::now(),
+        "files": file_count,
+        "catalogs": catalogs,
+    });
+    write_formatted(path, |format| format.to_string(&manifest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::CatalogFormat;
+    use std::fs;
+
+    #[test]
+    fn test_write_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output_path = dir.path().join("messages.json");
+        let manifest_path = dir.path().join("manifest.json");
+        fs::write(&output_path, r#"{ "Cart": { "title": "Cart" } }"#).unwrap();
+        let mut catalogs = Catalogs::open(&output_path, &[], CatalogFormat::Json, |handler, _| {
+            Ok(handler)
+        })
+        .unwrap();
+        catalogs.add_default_message("Cart", "title", String::new());
+
+        write_manifest(&manifest_path, 3, &catalogs).unwrap();
+        let manifest: Value =
+            serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        assert_eq!(manifest["files"], 3);
+        assert_eq!(manifest["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            manifest["catalogs"][output_path.display().to_string()]["Cart"],
+            json!({ "added": 0, "removed": 1, "unchanged": 0 })
+        );
+    }
+}
@@ -1,18 +1,100 @@
 use anyhow::{Context, Result};
+use next_intl_resolver::location::Location;
+use next_intl_resolver::Extraction;
 use serde_json::{Map, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
 
+use crate::attic;
+use crate::format::{CatalogFormat, FormatOverrides, JsonFormat, LineEnding};
+use crate::icu;
+use crate::jsonc::{self, Comments};
+use crate::layout::{self, NAMESPACE_PLACEHOLDER};
+use crate::order::{KeyOrder, OrderedValue, SortOrder};
+use crate::policy::{KeyPolicy, PolicyProblem};
+use crate::schema;
+use crate::shape::{self, EntryShape, Shape};
+use crate::strategy::{MergeCandidate, MergeStrategy, PreferSource};
+use crate::{toml, yaml};
+
+/// Values passed to `t()` that likely select a variant of a message
+pub const SELECT_PLACEHOLDERS: &[&str] = &["gender"];
+
+/// Key of the subtree unused messages are moved to with [`RemovedKeys::Obsolete`]
+pub const OBSOLETE_KEY: &str = "__obsolete__";
+
+/// The value passed to `t()` that selects the plural form of a message
+const PLURAL_PLACEHOLDER: &str = "count";
+
+/// What happens to source messages whose keys are no longer used in the code
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RemovedKeys {
+    /// Leave them out of the written messages
+    #[default]
+    Delete,
+    /// Keep them untouched
+    Keep,
+    /// Move them below [`OBSOLETE_KEY`] for review, they are moved back when used again
+    Obsolete,
+}
+
+/// How many keys of a namespace were added, removed or kept by a merge
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChanges {
+    pub added: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+/// Extracted messages by key, ordered so that every run over the same sources produces the
+/// same output
 #[derive(Default, Clone)]
 pub struct MessageMap {
-    messages: HashMap<String, Either<MessageInfo, Box<MessageMap>>>,
+    messages: BTreeMap<String, Either<MessageInfo, Box<MessageMap>>>,
+}
+
+impl MessageMap {
+    /// When a message at or below a key was first added
+    fn first_added(&self, key: &str) -> Option<usize> {
+        match self.messages.get(key)? {
+            Either::Left(info) => Some(info.order),
+            Either::Right(nested) => nested
+                .messages
+                .keys()
+                .filter_map(|key| nested.first_added(key))
+                .min(),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct MessageInfo {
-    value: String,
     file_path: String,
+    /// Where the message is first used in the file, if known
+    location: Option<Location>,
+    /// When the message was added, in source file and usage order
+    order: usize,
+    /// Value for new messages, declared next to the usage in the source file
+    default_message: Option<String>,
+    /// Context for translators, from the comment above the usage in the source file
+    description: Option<String>,
+    /// Names of the values passed to the usages, e.g. `name` for `t("greeting", { name })`
+    placeholders: BTreeSet<String>,
+    /// Names of the rich text tags passed to the usages, e.g. `link` for
+    /// `t.rich("terms", { link: (chunks) => <a>{chunks}</a> })`
+    tags: BTreeSet<String>,
+}
+
+impl MessageInfo {
+    /// The file and location of the usage, e.g. `app/page.tsx:12:18`
+    fn usage(&self) -> String {
+        match &self.location {
+            Some(location) => format!("{}:{}", self.file_path, location),
+            None => self.file_path.clone(),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -23,71 +105,688 @@ pub enum Either<L, R> {
 
 pub struct MessageHandler {
     source_messages: Map<String, Value>,
+    /// Format of the source messages, the merged messages are written in it too
+    format: CatalogFormat,
     extracted_messages: MessageMap,
     conflicts: Vec<NamespaceConflict>,
+    /// Constraints on the keys of the extracted messages
+    key_policy: KeyPolicy,
+    policy_violations: Vec<PolicyViolation>,
+    /// Sidecar file with the descriptions of the messages for translators
+    context_path: Option<PathBuf>,
+    /// Sidecar file with the usages of the messages
+    provenance_path: Option<PathBuf>,
+    /// Every usage of the extracted messages by full key, with the file and location
+    usages: BTreeMap<String, Vec<(String, Option<Location>)>>,
+    /// File the values of removed messages are moved to, by date
+    attic_path: Option<PathBuf>,
+    /// Give new messages the value of a removed message last used in the same file
+    reuse_renamed: bool,
+    /// The files every message was used in when it was last used, by full key, from the
+    /// provenance file of the last run and the files changed since
+    last_usages: BTreeMap<String, BTreeSet<String>>,
+    /// Add the extracted messages that have no value yet, see [`Self::without_new_keys`]
+    add_new_keys: bool,
+    /// Full keys of the source messages whose values were edited by hand, see
+    /// [`Self::set_edited_keys`]
+    edited_keys: BTreeSet<String>,
+    /// Values passed to `t()` that select a variant of new messages, e.g. `gender`
+    select_placeholders: Vec<String>,
+    sort_order: SortOrder,
+    /// Shape of the written messages, the one of the source messages unless reshaped
+    shape: Shape,
+    /// Shape of every written message, the one of the source messages unless set
+    entry_shape: EntryShape,
+    /// Formatting of the written messages that replaces the one of the existing files
+    format_overrides: FormatOverrides,
+    /// Descriptions of the source messages in the extended shape by full key
+    source_descriptions: Map<String, Value>,
+    /// The order of the keys in the source messages file
+    source_order: KeyOrder,
+    /// The comments of the source messages files by path, kept when writing
+    source_comments: HashMap<PathBuf, Comments>,
+    removed_keys: RemovedKeys,
+    /// How existing values and extracted messages are combined
+    strategy: Box<dyn MergeStrategy>,
+    /// Messages of the default locale that fill the ones missing in the source messages
+    fallback: Option<Fallback>,
+    /// Number of messages added so far
+    added: usize,
+}
+
+/// The merged messages of the default locale, copied into the other locales with a prefix
+struct Fallback {
+    messages: Map<String, Value>,
+    prefix: String,
 }
 
 #[derive(Debug)]
 pub struct NamespaceConflict {
     pub namespace: String,
     pub key: String,
+    pub kind: ConflictKind,
     pub files: Vec<String>,
+    /// Where the key is used, `file:line:col` if the location is known or otherwise the file
+    ///
+    /// For [`ConflictKind::LeafAndBranch`] the full key of the usage follows, e.g.
+    /// `app/page.tsx:12:18 (Cart.title.sub)`
+    pub usages: Vec<String>,
+}
+
+/// A message whose value in the catalog uses other values than its usages pass, e.g.
+/// `"Hello {name}"` for `t("greeting", { user })`
+#[derive(Debug, PartialEq, Eq)]
+pub struct PlaceholderMismatch {
+    /// Full key of the message, e.g. `Cart.greeting`
+    pub key: String,
+    /// Where the message is first used, `file:line:col` if the location is known
+    pub usage: String,
+    /// Values of the message that the usages don't pass
+    pub missing: Vec<String>,
+    /// Values the usages pass that the message doesn't use
+    pub extra: Vec<String>,
+}
+
+/// An extracted message whose key breaks the [`KeyPolicy`]
+#[derive(Debug)]
+pub struct PolicyViolation {
+    pub namespace: String,
+    pub key: String,
+    pub file_path: String,
+    /// Where the key is used, `file:line:col` if the location is known or otherwise the file
+    pub usage: String,
+    pub problem: PolicyProblem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// The same message is used in multiple files
+    Duplicate,
+    /// A message is used as a namespace of other messages too, e.g. `Cart.title` and
+    /// `Cart.title.sub`, which can't both be stored in the catalog
+    LeafAndBranch {
+        /// Full key of the message, e.g. `Cart.title`
+        message: String,
+    },
+}
+
+impl NamespaceConflict {
+    /// The number of usages of every file, in the order of the files
+    pub fn usage_counts(&self) -> Vec<(&str, usize)> {
+        self.files
+            .iter()
+            .map(|file| {
+                let count = self
+                    .usages
+                    .iter()
+                    .filter(|usage| {
+                        usage
+                            .strip_prefix(file.as_str())
+                            .is_some_and(|rest| rest.is_empty() || rest.starts_with([':', ' ']))
+                    })
+                    .count();
+                (file.as_str(), count)
+            })
+            .collect()
+    }
+
+    /// How the conflict can be resolved
+    pub fn suggestion(&self) -> String {
+        match &self.kind {
+            ConflictKind::Duplicate => format!(
+                "Move '{}' to a namespace shared by these files, or use --on-conflict allow if \
+                 they share it on purpose",
+                self.key
+            ),
+            ConflictKind::LeafAndBranch { message } => format!(
+                "'{message}' is a message and a namespace at the same time, rename the message \
+                 (e.g. to '{message}.label') or move the nested messages to another namespace"
+            ),
+        }
+    }
 }
 
 impl MessageHandler {
+    /// Load the source messages in the format of their file extension
     pub fn new(source_path: &Path) -> Result<Self> {
-        let source_messages = load_source_messages(source_path)?;
+        Self::open(source_path, CatalogFormat::from_path(source_path)?)
+    }
+
+    /// Load the source messages in a format, regardless of the file extension
+    ///
+    /// A path with a `{namespace}` placeholder loads the existing file of every namespace, e.g.
+    /// `messages/{namespace}.json`
+    pub fn open(source_path: &Path, format: CatalogFormat) -> Result<Self> {
+        let files = if layout::has_placeholder(source_path, NAMESPACE_PLACEHOLDER) {
+            layout::namespace_files(source_path)?
+                .into_iter()
+                .map(|(namespace, path)| (Some(namespace), path))
+                .collect()
+        } else {
+            vec![(None, source_path.to_path_buf())]
+        };
+
+        let mut source_messages = Map::new();
+        let mut source_order = KeyOrder::default();
+        let mut namespace_orders = Vec::new();
+        let mut source_comments = HashMap::new();
+        let mut shape = Shape::Nested;
+        let mut entry_shape = EntryShape::Simple;
+        let mut source_descriptions = Map::new();
+        for (namespace, path) in files {
+            // A missing catalog is created on write
+            let source = if namespace.is_none() && !path.exists() {
+                parse_source_messages(&path, format.empty(), format)?
+            } else {
+                load_source_messages(&path, format)?
+            };
+            // Messages in the extended shape are replaced by their values, and extended on write
+            let (messages, file_entry_shape) = shape::simplify(
+                source.messages,
+                namespace.as_deref().unwrap_or_default(),
+                &mut source_descriptions,
+            );
+            if file_entry_shape == EntryShape::Extended {
+                entry_shape = EntryShape::Extended;
+            }
+            // Flat source messages are nested to look messages up, and flattened again on write
+            let (messages, order) = match Shape::detect(&messages) {
+                Shape::Flat => {
+                    shape = Shape::Flat;
+                    (shape::unflatten(messages), source.order.unflatten())
+                }
+                Shape::Nested => (messages, source.order),
+            };
+            source_comments.insert(path, source.comments);
+            match namespace {
+                // The file of a namespace whose messages were all removed
+                Some(_) if messages.is_empty() => {}
+                Some(namespace) => {
+                    source_messages.insert(namespace.clone(), Value::Object(messages));
+                    namespace_orders.push((namespace, order));
+                }
+                None => (source_messages, source_order) = (messages, order),
+            }
+        }
+        if !namespace_orders.is_empty() {
+            source_order = namespace_orders.into_iter().collect();
+        }
+
         Ok(Self {
             source_messages,
+            format,
             extracted_messages: MessageMap::default(),
             conflicts: Vec::new(),
+            key_policy: KeyPolicy::default(),
+            policy_violations: Vec::new(),
+            context_path: None,
+            provenance_path: None,
+            usages: BTreeMap::new(),
+            attic_path: None,
+            reuse_renamed: false,
+            last_usages: BTreeMap::new(),
+            add_new_keys: true,
+            edited_keys: BTreeSet::new(),
+            select_placeholders: SELECT_PLACEHOLDERS.iter().map(|s| s.to_string()).collect(),
+            sort_order: SortOrder::default(),
+            shape,
+            entry_shape,
+            format_overrides: FormatOverrides::default(),
+            source_descriptions,
+            source_order,
+            source_comments,
+            removed_keys: RemovedKeys::default(),
+            strategy: Box::new(PreferSource),
+            fallback: None,
+            added: 0,
         })
     }
 
+    /// Order the keys of the written messages like this instead of alphabetically
+    pub fn with_sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = sort_order;
+        self
+    }
+
+    /// Take message values from these catalogs too, on top of the source messages and in
+    /// order, e.g. `base.json` and then `overrides.json`
+    ///
+    /// A value of a later catalog replaces the value of the same key in the earlier ones
+    pub fn with_source_layers(mut self, paths: &[PathBuf]) -> Result<Self> {
+        for path in paths {
+            let layer = load_source_messages(path, CatalogFormat::from_path(path)?)?.messages;
+            let (layer, _) = shape::simplify(layer, "", &mut self.source_descriptions);
+            let layer = match Shape::detect(&layer) {
+                Shape::Flat => shape::unflatten(layer),
+                Shape::Nested => layer,
+            };
+            merge_layer(&mut self.source_messages, layer);
+        }
+        Ok(self)
+    }
+
+    /// Move the values of removed messages to this JSON file below the date of removal, so
+    /// translations are never lost
+    pub fn with_attic(mut self, attic_path: PathBuf) -> Self {
+        self.attic_path = Some(attic_path);
+        self
+    }
+
+    /// Copy the source messages files to `<file>.bak` before they are overwritten, e.g.
+    /// `messages.json.bak`, so manual edits can be restored
+    pub fn with_backup(self) -> Result<Self> {
+        for path in self.source_comments.keys().filter(|path| path.exists()) {
+            let mut backup_path = path.clone().into_os_string();
+            backup_path.push(".bak");
+            fs::copy(path, &backup_path)
+                .with_context(|| format!("Failed to back up {}", path.display()))?;
+        }
+        Ok(self)
+    }
+
+    /// Carry the value of a removed message over to a new message that replaces it, so renaming
+    /// a key keeps its translations
+    ///
+    /// A message counts as renamed when it is the only removed and the new one the only added
+    /// message of the file the removed one was last used in, see
+    /// [`Self::with_provenance_output`]
+    pub fn with_translation_memory(mut self) -> Self {
+        self.reuse_renamed = true;
+        self
+    }
+
+    /// Add messages and their descriptions by full key to the source messages, e.g. the ones of
+    /// another library. They replace the values of the same keys
+    pub fn add_source_messages(
+        &mut self,
+        messages: Map<String, Value>,
+        descriptions: Map<String, Value>,
+    ) {
+        merge_layer(&mut self.source_messages, messages);
+        self.source_descriptions.extend(descriptions);
+    }
+
+    /// Move the source messages of a namespace to another namespace, next to the messages that
+    /// are there already, e.g. `Cart` to `Shop.Cart`
+    pub fn move_namespace(&mut self, from: &str, to: &str) {
+        let from: Vec<&str> = from.split('.').collect();
+        let Some(messages) = remove_path(&mut self.source_messages, &from) else {
+            return;
+        };
+        let to: Vec<&str> = to.split('.').collect();
+        shape::insert_path(&mut self.source_messages, &to, messages);
+    }
+
+    /// Leave out the extracted messages that have no value yet, e.g. to only remove the unused
+    /// messages
+    pub fn without_new_keys(mut self) -> Self {
+        self.add_new_keys = false;
+        self
+    }
+
+    /// What to do with source messages whose keys are no longer used
+    pub fn with_removed_keys(mut self, removed_keys: RemovedKeys) -> Self {
+        self.removed_keys = removed_keys;
+        self
+    }
+
+    /// Fill the messages that are missing in the source messages with these messages of the
+    /// default locale, string values get the prefix, e.g. `[TODO] `
+    pub fn set_fallback(&mut self, messages: Map<String, Value>, prefix: String) {
+        self.fallback = Some(Fallback { messages, prefix });
+    }
+
+    /// Mark the source messages whose values were edited by hand, rather than generated
+    ///
+    /// Such a value is never replaced by a placeholder: when its message moves to another
+    /// namespace with the same key, e.g. `Cart.title` to `Shop.Cart.title`, the value moves along
+    pub fn set_edited_keys(&mut self, edited_keys: BTreeSet<String>) {
+        self.edited_keys = edited_keys;
+    }
+
+    /// Write the messages in this shape instead of the one of the source messages
+    pub fn with_shape(mut self, shape: Shape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Write every message in this shape instead of the one of the source messages, the
+    /// extended shape carries the description of the message
+    pub fn with_entry_shape(mut self, entry_shape: EntryShape) -> Self {
+        self.entry_shape = entry_shape;
+        self
+    }
+
+    /// Format the written messages like this instead of like the existing files, e.g. with tabs
+    pub fn with_format_overrides(mut self, format_overrides: FormatOverrides) -> Self {
+        self.format_overrides = format_overrides;
+        self
+    }
+
+    /// Also write the descriptions of the messages to a sidecar JSON file, keyed by the full
+    /// message key, whenever the merged messages are written
+    pub fn with_context_output(mut self, context_path: PathBuf) -> Self {
+        self.context_path = Some(context_path);
+        self
+    }
+
+    /// Combine existing values and extracted messages with this strategy instead of
+    /// [`PreferSource`]
+    pub fn with_merge_strategy(mut self, strategy: Box<dyn MergeStrategy>) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Check the keys of the extracted messages against a policy when they are added
+    pub fn with_key_policy(mut self, key_policy: KeyPolicy) -> Self {
+        self.key_policy = key_policy;
+        self
+    }
+
+    /// Also write where the messages are used to a sidecar JSON file, e.g. `messages.meta.json`
+    /// with `{"Cart.title": ["app/cart/page.tsx:12:18"]}`, whenever the merged messages are
+    /// written
+    ///
+    /// The file of the last run tells where the removed messages were last used
+    pub fn with_provenance_output(mut self, provenance_path: PathBuf) -> Self {
+        self.last_usages = read_last_usages(&provenance_path);
+        self.provenance_path = Some(provenance_path);
+        self
+    }
+
+    /// Scaffold a select message for new messages that are passed these values on top of
+    /// [`SELECT_PLACEHOLDERS`], e.g. `role` for `t("welcome", { role })`
+    pub fn with_select_placeholders<I>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.select_placeholders.extend(names);
+        self
+    }
+
     /// Add a new message to the extracted messages
+    ///
+    /// Dotted keys (e.g. `t("section.title")`) are nested below the namespace, just like
+    /// next-intl resolves them at runtime
     pub fn add_extracted_message(&mut self, namespace: String, key: String, file_path: String) {
-        let parts: Vec<&str> = namespace.split('.').collect();
+        self.add_message(namespace, key, file_path, None);
+    }
+
+    fn add_message(
+        &mut self,
+        namespace: String,
+        key: String,
+        file_path: String,
+        location: Option<Location>,
+    ) {
+        self.added += 1;
+        self.add_usage(&namespace, &key, &file_path, location);
+        let info = MessageInfo {
+            file_path,
+            location,
+            order: self.added,
+            default_message: None,
+            description: None,
+            placeholders: BTreeSet::new(),
+            tags: BTreeSet::new(),
+        };
+        for problem in self.key_policy.check(&namespace, &key) {
+            self.policy_violations.push(PolicyViolation {
+                namespace: namespace.clone(),
+                key: key.clone(),
+                file_path: info.file_path.clone(),
+                usage: info.usage(),
+                problem,
+            });
+        }
+        let mut parts: Vec<&str> = namespace.split('.').chain(key.split('.')).collect();
+        let leaf = parts.pop().unwrap_or_default().to_string();
         let mut current = &mut self.extracted_messages.messages;
 
         // Navigate through all but the last part
-        for &part in parts.iter() {
+        for (index, &part) in parts.iter().enumerate() {
             current = match current
                 .entry(part.to_string())
                 .or_insert_with(|| Either::Right(Box::default()))
             {
                 Either::Right(map) => &mut map.messages,
-                Either::Left(info) => {
+                Either::Left(existing_info) => {
                     // Found a leaf where we expected a branch - record conflict
-                    self.conflicts.push(NamespaceConflict {
-                        namespace: namespace.clone(),
-                        key: part.to_string(),
-                        files: vec![info.file_path.clone(), file_path.clone()],
-                    });
+                    let message = parts[..=index].join(".");
+                    add_conflict(
+                        &mut self.conflicts,
+                        NamespaceConflict {
+                            namespace: namespace.clone(),
+                            key: part.to_string(),
+                            files: vec![existing_info.file_path.clone(), info.file_path.clone()],
+                            usages: vec![
+                                format!("{} ({message})", existing_info.usage()),
+                                format!("{} ({namespace}.{key})", info.usage()),
+                            ],
+                            kind: ConflictKind::LeafAndBranch { message },
+                        },
+                    );
                     return;
                 }
             };
         }
 
         // Check for existing key
-        if let Some(Either::Left(existing_info)) = current.get(&key) {
-            self.conflicts.push(NamespaceConflict {
-                namespace,
-                key: key.clone(),
-                files: vec![existing_info.file_path.clone(), file_path.clone()],
-            });
+        match current.get(&leaf) {
+            Some(Either::Left(existing_info)) => {
+                add_conflict(
+                    &mut self.conflicts,
+                    NamespaceConflict {
+                        namespace,
+                        key: key.clone(),
+                        kind: ConflictKind::Duplicate,
+                        files: vec![existing_info.file_path.clone(), info.file_path.clone()],
+                        usages: vec![existing_info.usage(), info.usage()],
+                    },
+                );
+            }
+            Some(Either::Right(existing_map)) => {
+                // Found a branch where we expected a leaf - record conflict and keep the branch
+                let message = format!("{namespace}.{key}");
+                let mut infos = Vec::new();
+                collect_message_infos(existing_map, &message, &mut infos);
+                infos.push((message.clone(), &info));
+                let mut files: Vec<String> = Vec::new();
+                for (_, existing_info) in &infos {
+                    if !files.contains(&existing_info.file_path) {
+                        files.push(existing_info.file_path.clone());
+                    }
+                }
+                let usages = infos
+                    .iter()
+                    .map(|(full_key, info)| format!("{} ({full_key})", info.usage()))
+                    .collect();
+                add_conflict(
+                    &mut self.conflicts,
+                    NamespaceConflict {
+                        namespace,
+                        key,
+                        kind: ConflictKind::LeafAndBranch { message },
+                        files,
+                        usages,
+                    },
+                );
+                return;
+            }
+            None => {}
         }
 
         // Insert the final key as a Left value with file information
-        current.insert(
-            key,
-            Either::Left(MessageInfo {
-                value: String::new(),
-                file_path,
-            }),
-        );
+        current.insert(leaf, Either::Left(info));
+    }
+
+    fn add_usage(
+        &mut self,
+        namespace: &str,
+        key: &str,
+        file_path: &str,
+        location: Option<Location>,
+    ) {
+        let full_key = if namespace.is_empty() {
+            key.to_string()
+        } else {
+            format!("{namespace}.{key}")
+        };
+        let usages = self.usages.entry(full_key).or_default();
+        let known = usages
+            .iter()
+            .any(|(file, known)| file == file_path && (*known == location || location.is_none()));
+        if !known {
+            usages.retain(|(file, known)| file != file_path || known.is_some());
+            usages.push((file_path.to_string(), location));
+        }
+    }
+
+    /// Get the extracted message for a key in a namespace
+    fn message_info_mut(&mut self, namespace: &str, key: &str) -> Option<&mut MessageInfo> {
+        let mut current = &mut self.extracted_messages;
+        let mut parts: Vec<&str> = namespace.split('.').chain(key.split('.')).collect();
+        let leaf = parts.pop()?;
+        for part in parts {
+            current = match current.messages.get_mut(part)? {
+                Either::Right(map) => map,
+                Either::Left(_) => return None,
+            };
+        }
+        match current.messages.get_mut(leaf)? {
+            Either::Left(info) => Some(info),
+            Either::Right(_) => None,
+        }
+    }
+
+    /// Set the value that is used for an extracted message when it's not in the source messages
+    /// yet, instead of the `namespace.key` placeholder
+    pub fn add_default_message(&mut self, namespace: &str, key: &str, default_message: String) {
+        if let Some(info) = self.message_info_mut(namespace, key) {
+            info.default_message = Some(default_message);
+        }
+    }
+
+    /// Set the description of an extracted message for translators
+    pub fn add_description(&mut self, namespace: &str, key: &str, description: String) {
+        if let Some(info) = self.message_info_mut(namespace, key) {
+            info.description = Some(description);
+        }
+    }
+
+    /// Add the names of the values passed to an extracted message, which are included in its
+    /// value when it's not in the source messages yet
+    pub fn add_placeholders<I>(&mut self, namespace: &str, key: &str, placeholders: I)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        if let Some(info) = self.message_info_mut(namespace, key) {
+            info.placeholders.extend(placeholders);
+        }
+    }
+
+    /// Add the names of the rich text tags passed to an extracted message, which are included
+    /// in its value as markup when it's not in the source messages yet
+    pub fn add_tags<I>(&mut self, namespace: &str, key: &str, tags: I)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        if let Some(info) = self.message_info_mut(namespace, key) {
+            info.tags.extend(tags);
+        }
+    }
+
+    /// Add the messages, default messages, descriptions, placeholders and tags extracted from
+    /// a file
+    pub fn add_extraction(&mut self, extraction: Extraction, file_path: String) {
+        let mut locations: HashMap<(&str, &str), Location> = HashMap::new();
+        for usage in &extraction.usages {
+            locations
+                .entry((&usage.namespace, &usage.key))
+                .or_insert(usage.location);
+        }
+        // In usage order, so conflicting keys are resolved the same way on every run
+        let mut messages = Vec::new();
+        for (namespace, keys) in &extraction.translations {
+            for key in keys {
+                let location = locations.get(&(namespace.as_str(), key.as_str())).copied();
+                messages.push((namespace, key, location));
+            }
+        }
+        messages.sort_by_key(|(namespace, key, location)| {
+            (
+                location.map_or((usize::MAX, usize::MAX), |l| (l.line, l.column)),
+                *namespace,
+                *key,
+            )
+        });
+        for (namespace, key, location) in messages {
+            self.add_message(namespace.clone(), key.clone(), file_path.clone(), location);
+        }
+        for usage in &extraction.usages {
+            self.add_usage(
+                &usage.namespace,
+                &usage.key,
+                &file_path,
+                Some(usage.location),
+            );
+        }
+        for (namespace, default_messages) in extraction.default_messages {
+            for (key, default_message) in default_messages {
+                self.add_default_message(&namespace, &key, default_message);
+            }
+        }
+        for (namespace, descriptions) in extraction.descriptions {
+            for (key, description) in descriptions {
+                self.add_description(&namespace, &key, description);
+            }
+        }
+        for (namespace, placeholders) in extraction.placeholders {
+            for (key, names) in placeholders {
+                self.add_placeholders(&namespace, &key, names);
+            }
+        }
+        for (namespace, tags) in extraction.tags {
+            for (key, names) in tags {
+                self.add_tags(&namespace, &key, names);
+            }
+        }
+    }
+
+    /// The existing messages whose values don't use the same values as their usages pass
+    ///
+    /// Only messages with usages that pass an object of values are checked, other usages may
+    /// pass a variable whose values are unknown
+    pub fn placeholder_mismatches(&self) -> Vec<PlaceholderMismatch> {
+        let mut infos = Vec::new();
+        collect_message_infos(&self.extracted_messages, "", &mut infos);
+        infos
+            .into_iter()
+            .filter(|(_, info)| !info.placeholders.is_empty())
+            .filter_map(|(key, info)| {
+                let parts: Vec<&str> = key.split('.').collect();
+                let message = value_at(&self.source_messages, &parts)?.as_str()?;
+                let arguments = icu::arguments(message)?;
+                let missing: Vec<String> =
+                    arguments.difference(&info.placeholders).cloned().collect();
+                let extra: Vec<String> =
+                    info.placeholders.difference(&arguments).cloned().collect();
+                (!missing.is_empty() || !extra.is_empty()).then(|| PlaceholderMismatch {
+                    key,
+                    usage: info.usage(),
+                    missing,
+                    extra,
+                })
+            })
+            .collect()
     }
 
-    /// Get any namespace conflicts that were detected
+    /// The extracted messages whose keys break the key policy, in the order they were added
+    pub fn get_policy_violations(&self) -> &[PolicyViolation] {
+        &self.policy_violations
+    }
+
+    /// Get any namespace conflicts that were detected, sorted by namespace, key and files
     pub fn get_conflicts(&self) -> &[NamespaceConflict] {
         &self.conflicts
     }
@@ -98,112 +797,895 @@ impl MessageHandler {
         messages: HashMap<String, HashSet<String>>,
         file_path: String,
     ) {
-        for (namespace, keys) in messages {
-            for key in keys {
+        for (namespace, keys) in messages.into_iter().collect::<BTreeMap<_, _>>() {
+            for key in keys.into_iter().collect::<BTreeSet<_>>() {
                 self.add_extracted_message(namespace.clone(), key, file_path.clone());
             }
         }
     }
 
     pub fn merge_messages(&self) -> Map<String, Value> {
+        let mut merged = self.merge_extracted_messages();
+        match self.removed_keys {
+            RemovedKeys::Delete => {
+                for key in self.kept_by_strategy() {
+                    let parts: Vec<&str> = key.split('.').collect();
+                    if let Some(value) = value_at(&self.source_messages, &parts) {
+                        shape::insert_path(&mut merged, &parts, value.clone());
+                    }
+                }
+            }
+            RemovedKeys::Keep => keep_removed(&mut merged, &self.source_messages),
+            RemovedKeys::Obsolete => {
+                let mut obsolete = self.obsolete_messages().cloned().unwrap_or_default();
+                remove_used(&mut obsolete, &merged);
+                merge_layer(&mut obsolete, self.removed_messages());
+                if !obsolete.is_empty() {
+                    merged.insert(OBSOLETE_KEY.to_string(), Value::Object(obsolete));
+                }
+            }
+        }
+        if !self.add_new_keys {
+            for key in self.new_keys() {
+                let parts: Vec<&str> = key.split('.').collect();
+                remove_path(&mut merged, &parts);
+            }
+        }
+        merged
+    }
+
+    /// The source messages that are no longer used, with their values
+    fn removed_messages(&self) -> Map<String, Value> {
+        let mut removed = Map::new();
+        for key in self.removed_keys() {
+            let parts: Vec<&str> = key.split('.').collect();
+            if let Some(value) = value_at(&self.source_messages, &parts) {
+                shape::insert_path(&mut removed, &parts, value.clone());
+            }
+        }
+        removed
+    }
+
+    /// The source messages that were marked obsolete before
+    fn obsolete_messages(&self) -> Option<&Map<String, Value>> {
+        if self.removed_keys != RemovedKeys::Obsolete {
+            return None;
+        }
+        self.source_messages.get(OBSOLETE_KEY)?.as_object()
+    }
+
+    /// The extracted messages with their source values, without the unused source messages
+    fn merge_extracted_messages(&self) -> Map<String, Value> {
+        self.merge_with_renames(&self.renamed_keys())
+    }
+
+    fn merge_with_renames(&self, renames: &BTreeMap<String, String>) -> Map<String, Value> {
         let mut merged = Map::new();
-        self.merge_recursive(&self.extracted_messages, &mut merged, None);
+        self.merge_recursive(&self.extracted_messages, &mut merged, None, renames);
         merged
     }
 
+    /// Full keys of the source messages that are no longer used, e.g. `Cart.oldTitle`
+    ///
+    /// Messages that are already obsolete, were renamed or are kept by the merge strategy are
+    /// not included
+    pub fn removed_keys(&self) -> Vec<String> {
+        let kept: HashSet<String> = self.kept_by_strategy().into_iter().collect();
+        let mut removed = self.unused_or_renamed_keys();
+        removed.retain(|key| !kept.contains(key));
+        removed
+    }
+
+    fn unused_or_renamed_keys(&self) -> Vec<String> {
+        let renamed: HashSet<String> = self.renamed_keys().into_values().collect();
+        let mut unused = self.unused_keys();
+        unused.retain(|key| !renamed.contains(key));
+        unused
+    }
+
+    /// Full keys of the unused messages the merge strategy keeps, when they would be deleted
+    fn kept_by_strategy(&self) -> Vec<String> {
+        if self.removed_keys != RemovedKeys::Delete {
+            return Vec::new();
+        }
+        let mut unused = self.unused_or_renamed_keys();
+        unused.retain(|key| {
+            let parts: Vec<&str> = key.split('.').collect();
+            value_at(&self.source_messages, &parts)
+                .is_some_and(|value| !self.strategy.remove(key, value))
+        });
+        unused
+    }
+
+    /// Full keys of the source messages that are not extracted, including renamed messages
+    fn unused_keys(&self) -> Vec<String> {
+        let mut unused = Vec::new();
+        collect_removed(
+            &self.source_messages,
+            Some(&self.merge_with_renames(&BTreeMap::new())),
+            "",
+            &mut unused,
+        );
+        if self.removed_keys == RemovedKeys::Obsolete {
+            let obsolete = format!("{OBSOLETE_KEY}.");
+            unused.retain(|key| !key.starts_with(&obsolete));
+        }
+        unused
+    }
+
+    /// The messages as loaded from the source messages files
+    pub fn source_messages(&self) -> &Map<String, Value> {
+        &self.source_messages
+    }
+
+    /// A JSON Schema of the extracted messages, see [`schema::generate`]
+    pub fn schema(&self) -> Value {
+        schema::generate(&self.merge_extracted_messages())
+    }
+
+    /// How many keys were added, removed or kept compared to the source messages, by top-level
+    /// namespace
+    pub fn key_changes(&self) -> BTreeMap<String, KeyChanges> {
+        let mut changes: BTreeMap<String, KeyChanges> = BTreeMap::new();
+        let namespace = |key: &str| key.split('.').next().unwrap_or_default().to_string();
+        let mut extracted = Vec::new();
+        collect_keys(&self.extracted_messages, "", &mut extracted);
+        for key in extracted {
+            let parts: Vec<&str> = key.split('.').collect();
+            let entry = changes.entry(namespace(&key)).or_default();
+            match value_at(&self.source_messages, &parts) {
+                Some(_) => entry.unchanged += 1,
+                None => entry.added += 1,
+            }
+        }
+        for key in self.removed_keys() {
+            changes.entry(namespace(&key)).or_default().removed += 1;
+        }
+        changes
+    }
+
+    /// The old full key of every renamed message by its new full key, see
+    /// [`Self::with_translation_memory`] and [`Self::set_edited_keys`]
+    pub fn renamed_keys(&self) -> BTreeMap<String, String> {
+        let new_keys = self.new_keys();
+        let mut unused = self.unused_keys();
+        unused.retain(|key| {
+            let parts: Vec<&str> = key.split('.').collect();
+            value_at(&self.source_messages, &parts).is_some_and(Value::is_string)
+        });
+
+        let mut renamed = BTreeMap::new();
+        if self.reuse_renamed {
+            // Added messages by the file they're used in and removed messages by the file they
+            // were last used in, if that's one file
+            let mut infos = Vec::new();
+            collect_message_infos(&self.extracted_messages, "", &mut infos);
+            let files: HashMap<String, &str> = infos
+                .into_iter()
+                .map(|(key, info)| (key, info.file_path.as_str()))
+                .collect();
+            let mut candidates: BTreeMap<&str, (Vec<String>, Vec<String>)> = BTreeMap::new();
+            for key in &new_keys {
+                if let Some(file) = files.get(key) {
+                    candidates.entry(*file).or_default().0.push(key.clone());
+                }
+            }
+            for key in &unused {
+                let last_files = self.last_usages.get(key);
+                if let Some(file) = last_files
+                    .filter(|files| files.len() == 1)
+                    .and_then(BTreeSet::first)
+                {
+                    candidates.entry(file).or_default().1.push(key.clone());
+                }
+            }
+            renamed.extend(unique_pairs(candidates));
+        }
+
+        // Added and removed messages with values edited by hand by their last key
+        let reused: HashSet<&String> = renamed.values().collect();
+        let mut moved: BTreeMap<&str, (Vec<String>, Vec<String>)> = BTreeMap::new();
+        for key in &new_keys {
+            if !renamed.contains_key(key) {
+                moved.entry(last_key(key)).or_default().0.push(key.clone());
+            }
+        }
+        for key in &unused {
+            if self.edited_keys.contains(key) && !reused.contains(key) {
+                moved.entry(last_key(key)).or_default().1.push(key.clone());
+            }
+        }
+        renamed.extend(unique_pairs(moved));
+        renamed
+    }
+
+    /// Full keys of the extracted messages that have no value yet, e.g. `Cart.title`
+    fn new_keys(&self) -> Vec<String> {
+        let mut extracted = Vec::new();
+        collect_keys(&self.extracted_messages, "", &mut extracted);
+        extracted.retain(|key| {
+            let parts: Vec<&str> = key.split('.').collect();
+            value_at(&self.source_messages, &parts).is_none()
+                && self
+                    .obsolete_messages()
+                    .and_then(|obsolete| value_at(obsolete, &parts))
+                    .is_none()
+        });
+        extracted
+    }
+
+    /// Full keys of the messages whose values are generated rather than taken from the source
+    /// messages, e.g. placeholders and default messages
+    pub fn generated_keys(&self) -> Vec<String> {
+        let renamed = self.renamed_keys();
+        let mut generated = self.new_keys();
+        generated.retain(|key| !renamed.contains_key(key));
+        generated
+    }
+
     fn merge_recursive(
         &self,
         message_map: &MessageMap,
         output: &mut Map<String, Value>,
         prefix: Option<&str>,
+        renames: &BTreeMap<String, String>,
     ) {
         for (key, value) in &message_map.messages {
+            // The full path of the key, so values are found at any depth
+            let full_key = if let Some(p) = prefix {
+                format!("{}.{}", p, key)
+            } else {
+                key.clone()
+            };
             match value {
-                Either::Left(_info) => {
-                    let full_key = if let Some(p) = prefix {
-                        format!("{}.{}", p, key)
-                    } else {
-                        key.clone()
+                Either::Left(info) => {
+                    let parts: Vec<&str> = full_key.split('.').collect();
+                    // Look up in source messages, including the ones that were obsolete
+                    let source_value = value_at(&self.source_messages, &parts)
+                        .or_else(|| value_at(self.obsolete_messages()?, &parts))
+                        .cloned();
+                    let renamed_value = renames.get(&full_key).and_then(|old_key| {
+                        let parts: Vec<&str> = old_key.split('.').collect();
+                        value_at(&self.source_messages, &parts).cloned()
+                    });
+                    let candidate = MergeCandidate {
+                        key: &full_key,
+                        existing: source_value.as_ref().or(renamed_value.as_ref()),
+                        extracted: info.default_message.as_deref(),
+                        placeholders: &info.placeholders,
                     };
-
-                    // Look up in source messages
-                    if let Some(source_value) = self.lookup_in_source(&full_key, key) {
-                        output.insert(key.clone(), source_value);
+                    if let Some(value) = self.strategy.merge(&candidate) {
+                        output.insert(key.clone(), value);
+                    } else if let Some(fallback) = self.fallback.as_ref().and_then(|fallback| {
+                        let value = value_at(&fallback.messages, &parts)?.clone();
+                        Some(match value {
+                            Value::String(text) => {
+                                Value::String(format!("{}{text}", fallback.prefix))
+                            }
+                            value => value,
+                        })
+                    }) {
+                        output.insert(key.clone(), fallback);
                     } else {
-                        output.insert(key.clone(), Value::String(full_key));
+                        let value = info.default_message.clone().unwrap_or_else(|| {
+                            self.scaffold_message(&full_key, &info.placeholders, &info.tags)
+                        });
+                        output.insert(key.clone(), Value::String(value));
                     }
                 }
                 Either::Right(nested) => {
                     let mut nested_map = Map::new();
-                    self.merge_recursive(nested, &mut nested_map, Some(key));
+                    self.merge_recursive(nested, &mut nested_map, Some(&full_key), renames);
                     output.insert(key.clone(), Value::Object(nested_map));
                 }
             }
         }
     }
 
-    fn lookup_in_source(&self, full_key: &str, key: &str) -> Option<Value> {
-        let parts: Vec<&str> = full_key.split('.').collect();
-        let mut current = &self.source_messages;
+    /// Value of a new message, e.g. `Greeting.hello {name}` for `t("hello", { name })`, with
+    /// markup for the rich text tags, e.g. `Legal.terms <link></link>`
+    ///
+    /// A count or selector is scaffolded as a plural or select message for the translators to
+    /// fill in, e.g. `{count, plural, one {# Cart.items} other {# Cart.items}}`
+    fn scaffold_message(
+        &self,
+        full_key: &str,
+        placeholders: &BTreeSet<String>,
+        tags: &BTreeSet<String>,
+    ) -> String {
+        let selectors: Vec<&String> = self
+            .select_placeholders
+            .iter()
+            .filter(|name| placeholders.contains(*name))
+            .collect();
+        let mut message = placeholders
+            .iter()
+            .filter(|name| *name != PLURAL_PLACEHOLDER && !selectors.contains(name))
+            .filter(|name| !tags.contains(*name))
+            .fold(full_key.to_string(), |message, name| {
+                format!("{message} {{{name}}}")
+            });
+        for tag in tags {
+            message = format!("{message} <{tag}></{tag}>");
+        }
+        if placeholders.contains(PLURAL_PLACEHOLDER) {
+            message = format!(
+                "{{{PLURAL_PLACEHOLDER}, plural, one {{# {message}}} other {{# {message}}}}}"
+            );
+        }
+        for name in selectors.into_iter().rev() {
+            message = format!("{{{name}, select, other {{{message}}}}}");
+        }
+        message
+    }
 
-        for (i, &part) in parts.iter().enumerate() {
-            if i == parts.len() - 1 {
-                return current.get(key).cloned();
-            }
+    /// The descriptions of the extracted messages, keyed by the full message key
+    pub fn descriptions(&self) -> Map<String, Value> {
+        let mut descriptions = Map::new();
+        collect_descriptions(&self.extracted_messages, "", &mut descriptions);
+        descriptions
+    }
 
-            current = match current.get(part)?.as_object() {
-                Some(obj) => obj,
-                None => return None,
-            };
+    /// The full keys of the extracted messages every source file uses, by file
+    pub fn keys_by_file(&self) -> BTreeMap<String, BTreeSet<String>> {
+        let mut keys_by_file: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (key, usages) in &self.usages {
+            for (file, _) in usages {
+                keys_by_file
+                    .entry(file.clone())
+                    .or_default()
+                    .insert(key.clone());
+            }
         }
-        None
+        keys_by_file
     }
 
-    pub fn write_merged_messages(&self, output_path: &Path) -> Result<()> {
-        let messages = self.merge_messages();
-        let json = serde_json::to_string_pretty(&messages)?;
-        fs::write(output_path, json)?;
-        Ok(())
+    /// Where the extracted messages are used by full key, e.g. `app/page.tsx:12:18`
+    pub fn provenance(&self) -> Map<String, Value> {
+        self.usages
+            .iter()
+            .map(|(key, usages)| {
+                let mut usages = usages.clone();
+                usages.sort_by_key(|(file, location)| {
+                    (file.clone(), location.map(|l| (l.line, l.column)))
+                });
+                let usages = usages
+                    .into_iter()
+                    .map(|(file, location)| match location {
+                        Some(location) => Value::String(format!("{file}:{location}")),
+                        None => Value::String(file),
+                    })
+                    .collect();
+                (key.clone(), Value::Array(usages))
+            })
+            .collect()
     }
 
-    pub fn remove_messages_for_file(&mut self, file_path: &str) {
-        let mut new_messages = self.extracted_messages.messages.clone();
-        remove_messages(&mut new_messages, file_path);
-        self.extracted_messages.messages = new_messages;
-        // Also remove any conflicts associated with this file
-        self.conflicts
-            .retain(|conflict| !conflict.files.contains(&file_path.to_string()));
+    /// The merged messages with their keys in the configured order
+    pub fn ordered_messages(&self) -> OrderedValue {
+        self.ordered(
+            Value::Object(self.merge_messages()),
+            Some(&self.extracted_messages),
+            Some(&self.source_order),
+        )
     }
-}
 
-fn remove_messages(
-    messages: &mut HashMap<String, Either<MessageInfo, Box<MessageMap>>>,
-    file_path: &str,
-) {
-    messages.retain(|_, value| match value {
-        Either::Left(info) => info.file_path != file_path,
-        Either::Right(map) => {
-            remove_messages(&mut map.messages, file_path);
-            !map.messages.is_empty()
+    fn ordered(
+        &self,
+        value: Value,
+        messages: Option<&MessageMap>,
+        existing: Option<&KeyOrder>,
+    ) -> OrderedValue {
+        let Value::Object(map) = value else {
+            return OrderedValue::Value(value);
+        };
+        // Alphabetical to begin with, the sorts below are stable
+        let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+        match self.sort_order {
+            SortOrder::Alphabetical => {}
+            SortOrder::Usage => entries.sort_by_key(|(key, _)| {
+                messages
+                    .and_then(|messages| messages.first_added(key))
+                    .unwrap_or(usize::MAX)
+            }),
+            SortOrder::Existing => entries.sort_by_key(|(key, _)| {
+                existing
+                    .and_then(|existing| existing.position(key))
+                    .unwrap_or(usize::MAX)
+            }),
         }
-    });
-}
-
-fn load_source_messages(path: &Path) -> Result<Map<String, Value>> {
-    let content = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read source file: {}", path.display()))?;
-    let json: Value = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?;
-
-    match json {
-        Value::Object(map) => Ok(map),
-        _ => anyhow::bail!("Source file does not contain a JSON object"),
+        OrderedValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let nested = messages.and_then(|messages| match messages.messages.get(&key) {
+                        Some(Either::Right(nested)) => Some(nested.as_ref()),
+                        _ => None,
+                    });
+                    let value = self.ordered(value, nested, existing.and_then(|e| e.get(&key)));
+                    (key, value)
+                })
+                .collect(),
+        )
     }
-}
 
-#[cfg(test)]
+    /// Write the merged messages, formatted like the existing file and with the comments of the
+    /// source messages
+    ///
+    /// A path with a `{namespace}` placeholder writes a file per namespace
+    pub fn write_merged_messages(&self, output_path: &Path) -> Result<()> {
+        for (new_key, old_key) in self.renamed_keys() {
+            info!(
+                "Reusing the value of '{}' for '{}' in {}",
+                old_key,
+                new_key,
+                output_path.display()
+            );
+        }
+        for (path, content) in self.rendered_catalogs(output_path)? {
+            write_if_changed(&path, &content)?;
+        }
+        if let Some(attic_path) = &self.attic_path {
+            if self.removed_keys == RemovedKeys::Delete {
+                attic::add_to_attic(attic_path, self.removed_messages())?;
+            }
+        }
+        if let Some(provenance_path) = &self.provenance_path {
+            write_formatted(provenance_path, |format| {
+                format.to_string(&self.provenance())
+            })?;
+        }
+        if let Some(context_path) = &self.context_path {
+            write_formatted(context_path, |format| {
+                format.to_string(&self.descriptions())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// The content of every catalog file the merged messages are written to, by path
+    ///
+    /// A path with a `{namespace}` placeholder has a file per namespace. The existing file of a
+    /// namespace without messages is emptied, so its stale messages aren't loaded again
+    pub fn rendered_catalogs(&self, output_path: &Path) -> Result<Vec<(PathBuf, String)>> {
+        let messages = self.ordered_messages();
+        if !layout::has_placeholder(output_path, NAMESPACE_PLACEHOLDER) {
+            let content = self.render_catalog(output_path, "", messages)?;
+            return Ok(vec![(output_path.to_path_buf(), content)]);
+        }
+        let OrderedValue::Object(namespaces) = messages else {
+            return Ok(Vec::new());
+        };
+        let mut catalogs = Vec::new();
+        for (namespace, messages) in namespaces {
+            if !matches!(messages, OrderedValue::Object(_)) {
+                warn!(
+                    "Message '{}' has no namespace and is not written",
+                    namespace
+                );
+                continue;
+            }
+            let path = layout::fill(output_path, NAMESPACE_PLACEHOLDER, &namespace);
+            let content = self.render_catalog(&path, &namespace, messages)?;
+            catalogs.push((path, content));
+        }
+        for (namespace, path) in layout::namespace_files(output_path)? {
+            if catalogs.iter().all(|(rendered, _)| *rendered != path) {
+                let content =
+                    self.render_catalog(&path, &namespace, OrderedValue::Object(Vec::new()))?;
+                catalogs.push((path, content));
+            }
+        }
+        Ok(catalogs)
+    }
+
+    /// Render the messages below a prefix, the namespace of a file per namespace
+    fn render_catalog(
+        &self,
+        path: &Path,
+        prefix: &str,
+        mut messages: OrderedValue,
+    ) -> Result<String> {
+        if self.shape == Shape::Flat {
+            messages = shape::flatten(messages);
+        }
+        if self.entry_shape == EntryShape::Extended {
+            // Descriptions in the code replace the ones of the source messages
+            let mut descriptions = self.source_descriptions.clone();
+            descriptions.extend(self.descriptions());
+            messages = shape::extend(messages, prefix, &descriptions);
+        }
+        let no_comments = Comments::default();
+        let comments = self.source_comments.get(path).unwrap_or(&no_comments);
+        let overrides = &self.format_overrides;
+        match self.format {
+            CatalogFormat::Json => formatted_with(path, overrides, |format| {
+                format.to_string_with_comments(&messages, comments)
+            }),
+            CatalogFormat::Yaml => formatted_with(path, overrides, |format| {
+                Ok(yaml::to_string(&messages, &format.indent))
+            }),
+            CatalogFormat::Toml => {
+                formatted_with(path, overrides, |_| Ok(toml::to_string(&messages)))
+            }
+        }
+    }
+
+    pub fn remove_messages_for_file(&mut self, file_path: &str) {
+        let mut new_messages = self.extracted_messages.messages.clone();
+        remove_messages(&mut new_messages, file_path);
+        self.extracted_messages.messages = new_messages;
+        self.usages.retain(|key, usages| {
+            // Where the message was last used, in case it's renamed in the file
+            if usages.iter().any(|(file, _)| file == file_path) {
+                let files = usages.iter().map(|(file, _)| file.clone()).collect();
+                self.last_usages.insert(key.clone(), files);
+            }
+            usages.retain(|(file, _)| file != file_path);
+            !usages.is_empty()
+        });
+        self.policy_violations
+            .retain(|violation| violation.file_path != file_path);
+        // Also remove any conflicts associated with this file
+        self.conflicts
+            .retain(|conflict| !conflict.files.contains(&file_path.to_string()));
+    }
+}
+
+/// Record a conflict, unless the same key already conflicts between the same files, e.g.
+/// when watch mode processes a file again
+fn add_conflict(conflicts: &mut Vec<NamespaceConflict>, mut conflict: NamespaceConflict) {
+    conflict.files.sort();
+    match conflicts.iter_mut().find(|existing| {
+        existing.namespace == conflict.namespace
+            && existing.key == conflict.key
+            && existing.files == conflict.files
+    }) {
+        Some(existing) => {
+            for usage in conflict.usages {
+                if !existing.usages.contains(&usage) {
+                    existing.usages.push(usage);
+                }
+            }
+        }
+        None => {
+            conflicts.push(conflict);
+            conflicts.sort_by(|a, b| {
+                (&a.namespace, &a.key, &a.files).cmp(&(&b.namespace, &b.key, &b.files))
+            });
+        }
+    }
+}
+
+fn remove_messages(
+    messages: &mut BTreeMap<String, Either<MessageInfo, Box<MessageMap>>>,
+    file_path: &str,
+) {
+    messages.retain(|_, value| match value {
+        Either::Left(info) => info.file_path != file_path,
+        Either::Right(map) => {
+            remove_messages(&mut map.messages, file_path);
+            !map.messages.is_empty()
+        }
+    });
+}
+
+/// Remove the value at a path of keys, and the objects that are left empty by it
+fn remove_path(map: &mut Map<String, Value>, parts: &[&str]) -> Option<Value> {
+    let (&first, rest) = parts.split_first()?;
+    if rest.is_empty() {
+        return map.remove(first);
+    }
+    let Some(Value::Object(nested)) = map.get_mut(first) else {
+        return None;
+    };
+    let removed = remove_path(nested, rest);
+    if nested.is_empty() {
+        map.remove(first);
+    }
+    removed
+}
+
+/// Add the source messages that are missing in the merged messages
+fn keep_removed(merged: &mut Map<String, Value>, source: &Map<String, Value>) {
+    for (key, value) in source {
+        match (merged.get_mut(key), value) {
+            (Some(Value::Object(merged)), Value::Object(source)) => keep_removed(merged, source),
+            (Some(_), _) => {}
+            (None, value) => {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Remove the obsolete messages that are used again
+fn remove_used(obsolete: &mut Map<String, Value>, merged: &Map<String, Value>) {
+    obsolete.retain(|key, value| match (value, merged.get(key)) {
+        (Value::Object(obsolete), Some(Value::Object(merged))) => {
+            remove_used(obsolete, merged);
+            !obsolete.is_empty()
+        }
+        (_, merged) => merged.is_none(),
+    });
+}
+
+fn value_at<'m>(messages: &'m Map<String, Value>, parts: &[&str]) -> Option<&'m Value> {
+    let (last, parents) = parts.split_last()?;
+    let mut current = messages;
+    for part in parents {
+        current = current.get(*part)?.as_object()?;
+    }
+    current.get(*last)
+}
+
+/// Collect the full keys of the source messages that are not in the merged messages
+fn collect_removed(
+    source: &Map<String, Value>,
+    merged: Option<&Map<String, Value>>,
+    prefix: &str,
+    removed: &mut Vec<String>,
+) {
+    for (key, value) in source {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        let merged_value = merged.and_then(|merged| merged.get(key));
+        match value {
+            Value::Object(source) => collect_removed(
+                source,
+                merged_value.and_then(Value::as_object),
+                &full_key,
+                removed,
+            ),
+            _ if merged_value.is_some_and(|value| !value.is_object()) => {}
+            _ => removed.push(full_key),
+        }
+    }
+}
+
+/// Merge the messages of a catalog into the ones below it, objects are merged key by key
+fn merge_layer(base: &mut Map<String, Value>, layer: Map<String, Value>) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Object(base)), Value::Object(layer)) => merge_layer(base, layer),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn collect_descriptions(message_map: &MessageMap, prefix: &str, output: &mut Map<String, Value>) {
+    for (key, value) in &message_map.messages {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Either::Left(info) => {
+                if let Some(description) = &info.description {
+                    output.insert(full_key, Value::String(description.clone()));
+                }
+            }
+            Either::Right(nested) => collect_descriptions(nested, &full_key, output),
+        }
+    }
+}
+
+/// Collect the full keys of all messages in a message map
+fn collect_keys(message_map: &MessageMap, prefix: &str, keys: &mut Vec<String>) {
+    for (key, value) in &message_map.messages {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Either::Left(_) => keys.push(full_key),
+            Either::Right(nested) => collect_keys(nested, &full_key, keys),
+        }
+    }
+}
+
+/// Pair an added and a removed message when they're the only ones of their group
+fn unique_pairs<K>(candidates: BTreeMap<K, (Vec<String>, Vec<String>)>) -> Vec<(String, String)> {
+    candidates
+        .into_values()
+        .filter_map(
+            |(mut added, mut removed)| match (added.len(), removed.len()) {
+                (1, 1) => Some((added.pop()?, removed.pop()?)),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// The last part of a full key, e.g. `title` for `Cart.title`
+fn last_key(full_key: &str) -> &str {
+    full_key.rsplit('.').next().unwrap_or(full_key)
+}
+
+/// The files of the usages in a provenance file by full key, see [`MessageHandler::provenance`]
+fn read_last_usages(path: &Path) -> BTreeMap<String, BTreeSet<String>> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+    let provenance: Map<String, Value> = match serde_json::from_str(&content) {
+        Ok(provenance) => provenance,
+        Err(e) => {
+            warn!("Ignoring the provenance file {}: {}", path.display(), e);
+            return BTreeMap::new();
+        }
+    };
+    provenance
+        .into_iter()
+        .map(|(key, usages)| {
+            let files = usages
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(|usage| usage_file(usage).to_string())
+                .collect();
+            (key, files)
+        })
+        .collect()
+}
+
+/// The file of a usage, e.g. `app/page.tsx` for `app/page.tsx:12:18`
+fn usage_file(usage: &str) -> &str {
+    let mut parts = usage.rsplitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(column), Some(line), Some(file))
+            if column.parse::<u32>().is_ok() && line.parse::<u32>().is_ok() =>
+        {
+            file
+        }
+        _ => usage,
+    }
+}
+
+/// Collect the infos of all messages in a message map with their full keys
+fn collect_message_infos<'m>(
+    message_map: &'m MessageMap,
+    prefix: &str,
+    infos: &mut Vec<(String, &'m MessageInfo)>,
+) {
+    for (key, value) in &message_map.messages {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Either::Left(info) => infos.push((full_key, info)),
+            Either::Right(nested) => collect_message_infos(nested, &full_key, infos),
+        }
+    }
+}
+
+/// Write JSON with the indentation, line endings and trailing newline of the existing file, if
+/// any
+///
+/// The file is left alone when its content doesn't change, so watchers aren't triggered
+pub fn write_formatted(
+    path: &Path,
+    serialize: impl FnOnce(&JsonFormat) -> Result<String>,
+) -> Result<()> {
+    write_formatted_with(path, &FormatOverrides::default(), serialize)
+}
+
+/// Like [`write_formatted`], with formatting that replaces the one of the existing file
+pub fn write_formatted_with(
+    path: &Path,
+    overrides: &FormatOverrides,
+    serialize: impl FnOnce(&JsonFormat) -> Result<String>,
+) -> Result<()> {
+    let json = formatted_with(path, overrides, serialize)?;
+    write_if_changed(path, &json)
+}
+
+/// The content [`write_formatted_with`] would write
+pub fn formatted_with(
+    path: &Path,
+    overrides: &FormatOverrides,
+    serialize: impl FnOnce(&JsonFormat) -> Result<String>,
+) -> Result<String> {
+    let mut format = fs::read_to_string(path)
+        .ok()
+        .as_deref()
+        .map(JsonFormat::detect)
+        .unwrap_or_default();
+    overrides.apply(&mut format);
+    let mut json = serialize(&format)?;
+    // YAML and TOML end with a newline of their own, which is only changed when asked to
+    if overrides.final_newline.is_some() || format.line_ending == LineEnding::Crlf {
+        json = format.finish(json);
+    }
+    Ok(json)
+}
+
+/// Write a file unless it has the content already, creating its directory if needed
+fn write_if_changed(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).ok().as_deref() != Some(content) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)?;
+    }
+    Ok(())
+}
+
+/// The source messages file, JSON files may contain comments and trailing commas
+struct SourceMessages {
+    messages: Map<String, Value>,
+    order: KeyOrder,
+    comments: Comments,
+}
+
+/// The messages of a catalog's content, e.g. the one it would be written with
+pub fn parse_messages(
+    path: &Path,
+    content: &str,
+    format: CatalogFormat,
+) -> Result<Map<String, Value>> {
+    parse_source_messages(path, content, format).map(|source| source.messages)
+}
+
+fn load_source_messages(path: &Path, format: CatalogFormat) -> Result<SourceMessages> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read source file: {}", path.display()))?;
+    parse_source_messages(path, &content, format)
+}
+
+fn parse_source_messages(
+    path: &Path,
+    content: &str,
+    format: CatalogFormat,
+) -> Result<SourceMessages> {
+    let (value, order, comments) = match format {
+        CatalogFormat::Json => {
+            let jsonc = jsonc::parse(content);
+            let value: Value = serde_json::from_str(&jsonc.json)
+                .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?;
+            let order: KeyOrder = serde_json::from_str(&jsonc.json)
+                .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?;
+            (value, order, jsonc.comments)
+        }
+        CatalogFormat::Yaml => {
+            let value = yaml::parse(content)
+                .with_context(|| format!("Failed to parse YAML from: {}", path.display()))?;
+            let order = value.key_order();
+            (value.into_value(), order, Comments::default())
+        }
+        CatalogFormat::Toml => {
+            let value = toml::parse(content)
+                .with_context(|| format!("Failed to parse TOML from: {}", path.display()))?;
+            let order = value.key_order();
+            (value.into_value(), order, Comments::default())
+        }
+    };
+
+    match value {
+        Value::Object(messages) => Ok(SourceMessages {
+            messages,
+            order,
+            comments,
+        }),
+        _ => anyhow::bail!("Source file does not contain an object of messages"),
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::Casing;
+    use crate::strategy::PreferExtracted;
+    use next_intl_resolver::Extractor;
     use serde_json::json;
 
     fn create_test_message_handler() -> MessageHandler {
@@ -221,8 +1703,31 @@ mod tests {
 
         MessageHandler {
             source_messages: source_messages.as_object().unwrap().clone(),
+            format: CatalogFormat::Json,
             extracted_messages: MessageMap::default(),
             conflicts: Vec::new(),
+            key_policy: KeyPolicy::default(),
+            policy_violations: Vec::new(),
+            context_path: None,
+            provenance_path: None,
+            usages: BTreeMap::new(),
+            attic_path: None,
+            reuse_renamed: false,
+            last_usages: BTreeMap::new(),
+            add_new_keys: true,
+            edited_keys: BTreeSet::new(),
+            select_placeholders: SELECT_PLACEHOLDERS.iter().map(|s| s.to_string()).collect(),
+            sort_order: SortOrder::default(),
+            shape: Shape::default(),
+            entry_shape: EntryShape::default(),
+            format_overrides: FormatOverrides::default(),
+            source_descriptions: Map::new(),
+            source_order: KeyOrder::default(),
+            source_comments: HashMap::new(),
+            removed_keys: RemovedKeys::default(),
+            strategy: Box::new(PreferSource),
+            fallback: None,
+            added: 0,
         }
     }
 
@@ -475,4 +1980,1111 @@ mod tests {
         assert!(child.get("key1").is_none());
         assert!(child.get("key2").is_some());
     }
+
+    #[test]
+    fn test_dotted_keys_are_nested() {
+        let mut handler = create_test_message_handler();
+        handler.add_extracted_message(
+            "Cart".to_string(),
+            "section.title".to_string(),
+            "file1.ts".to_string(),
+        );
+        handler.add_extracted_message(
+            "Cart".to_string(),
+            "section.subtitle".to_string(),
+            "file1.ts".to_string(),
+        );
+
+        let merged = handler.merge_messages();
+
+        let cart = merged.get("Cart").unwrap().as_object().unwrap();
+        assert!(cart.get("section.title").is_none());
+        let section = cart.get("section").unwrap().as_object().unwrap();
+        assert_eq!(section.len(), 2);
+        assert!(section.get("title").is_some());
+        assert!(section.get("subtitle").is_some());
+        assert!(handler.get_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_dotted_key_conflicts_with_leaf() {
+        let mut handler = create_test_message_handler();
+        handler.add_extracted_message(
+            "Cart".to_string(),
+            "section".to_string(),
+            "file1.ts".to_string(),
+        );
+        handler.add_extracted_message(
+            "Cart".to_string(),
+            "section.title".to_string(),
+            "file2.ts".to_string(),
+        );
+        handler.add_extracted_message(
+            "Other".to_string(),
+            "section.title".to_string(),
+            "file1.ts".to_string(),
+        );
+        handler.add_extracted_message(
+            "Other".to_string(),
+            "section".to_string(),
+            "file2.ts".to_string(),
+        );
+
+        let conflicts = handler.get_conflicts();
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].namespace, "Cart");
+        assert_eq!(conflicts[0].key, "section");
+        assert_eq!(
+            conflicts[0].kind,
+            ConflictKind::LeafAndBranch {
+                message: "Cart.section".to_string()
+            }
+        );
+        assert_eq!(
+            conflicts[0].usages,
+            vec!["file1.ts (Cart.section)", "file2.ts (Cart.section.title)"]
+        );
+        assert!(conflicts[0].suggestion().contains("'Cart.section.label'"));
+        assert_eq!(conflicts[1].namespace, "Other");
+        assert_eq!(conflicts[1].files, vec!["file1.ts", "file2.ts"]);
+        assert_eq!(
+            conflicts[1].usages,
+            vec!["file1.ts (Other.section.title)", "file2.ts (Other.section)"]
+        );
+
+        // The existing branch is kept
+        let merged = handler.merge_messages();
+        let other = merged.get("Other").unwrap().as_object().unwrap();
+        assert!(other.get("section").unwrap().is_object());
+    }
+
+    #[test]
+    fn test_default_messages() {
+        let mut handler = create_test_message_handler();
+        let extraction = Extraction {
+            translations: HashMap::from([
+                (
+                    "namespace1".to_string(),
+                    HashSet::from(["key1".to_string()]),
+                ),
+                (
+                    "New".to_string(),
+                    HashSet::from(["title".to_string(), "other".to_string()]),
+                ),
+            ]),
+            default_messages: HashMap::from([
+                (
+                    "namespace1".to_string(),
+                    HashMap::from([("key1".to_string(), "Ignored".to_string())]),
+                ),
+                (
+                    "New".to_string(),
+                    HashMap::from([("title".to_string(), "Welcome back".to_string())]),
+                ),
+            ]),
+            ..Default::default()
+        };
+        handler.add_extraction(extraction, "file1.ts".to_string());
+
+        let merged = handler.merge_messages();
+        assert_eq!(merged["namespace1"]["key1"], "value1");
+        assert_eq!(merged["New"]["title"], "Welcome back");
+        assert_eq!(merged["New"]["other"], "New.other");
+    }
+
+    #[test]
+    fn test_descriptions() {
+        let mut handler = create_test_message_handler();
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Cart".to_string(),
+                HashSet::from(["empty".to_string(), "summary.total".to_string()]),
+            )]),
+            descriptions: HashMap::from([(
+                "Cart".to_string(),
+                HashMap::from([
+                    ("empty".to_string(), "Shown on empty cart".to_string()),
+                    ("summary.total".to_string(), "Total price".to_string()),
+                ]),
+            )]),
+            ..Default::default()
+        };
+        handler.add_extraction(extraction, "file1.ts".to_string());
+
+        let descriptions = handler.descriptions();
+        assert_eq!(descriptions.len(), 2);
+        assert_eq!(descriptions["Cart.empty"], "Shown on empty cart");
+        assert_eq!(descriptions["Cart.summary.total"], "Total price");
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut handler = create_test_message_handler();
+        let extraction = Extraction {
+            translations: HashMap::from([
+                (
+                    "namespace1".to_string(),
+                    HashSet::from(["key1".to_string()]),
+                ),
+                (
+                    "Greeting".to_string(),
+                    HashSet::from(["hello".to_string(), "welcome".to_string()]),
+                ),
+            ]),
+            default_messages: HashMap::from([(
+                "Greeting".to_string(),
+                HashMap::from([("welcome".to_string(), "Welcome {name}".to_string())]),
+            )]),
+            placeholders: HashMap::from([
+                (
+                    "namespace1".to_string(),
+                    HashMap::from([("key1".to_string(), BTreeSet::from(["name".to_string()]))]),
+                ),
+                (
+                    "Greeting".to_string(),
+                    HashMap::from([
+                        (
+                            "hello".to_string(),
+                            BTreeSet::from(["name".to_string(), "count".to_string()]),
+                        ),
+                        ("welcome".to_string(), BTreeSet::from(["name".to_string()])),
+                    ]),
+                ),
+            ]),
+            ..Default::default()
+        };
+        handler.add_extraction(extraction, "file1.ts".to_string());
+
+        let merged = handler.merge_messages();
+        assert_eq!(merged["namespace1"]["key1"], "value1");
+        assert_eq!(
+            merged["Greeting"]["hello"],
+            "{count, plural, one {# Greeting.hello {name}} other {# Greeting.hello {name}}}"
+        );
+        assert_eq!(merged["Greeting"]["welcome"], "Welcome {name}");
+    }
+
+    #[test]
+    fn test_rich_text_tags() {
+        let mut handler = create_test_message_handler();
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Legal".to_string(),
+                HashSet::from(["terms".to_string()]),
+            )]),
+            placeholders: HashMap::from([(
+                "Legal".to_string(),
+                HashMap::from([("terms".to_string(), names(&["link", "year"]))]),
+            )]),
+            tags: HashMap::from([(
+                "Legal".to_string(),
+                HashMap::from([("terms".to_string(), names(&["link"]))]),
+            )]),
+            ..Default::default()
+        };
+        handler.add_extraction(extraction, "file1.ts".to_string());
+
+        let merged = handler.merge_messages();
+        assert_eq!(merged["Legal"]["terms"], "Legal.terms {year} <link></link>");
+    }
+
+    #[test]
+    fn test_select_placeholders() {
+        let mut handler =
+            create_test_message_handler().with_select_placeholders(["role".to_string()]);
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Profile".to_string(),
+                HashSet::from(["bio".to_string(), "welcome".to_string()]),
+            )]),
+            placeholders: HashMap::from([(
+                "Profile".to_string(),
+                HashMap::from([
+                    (
+                        "bio".to_string(),
+                        BTreeSet::from(["gender".to_string(), "name".to_string()]),
+                    ),
+                    (
+                        "welcome".to_string(),
+                        BTreeSet::from(["role".to_string(), "count".to_string()]),
+                    ),
+                ]),
+            )]),
+            ..Default::default()
+        };
+        handler.add_extraction(extraction, "file1.ts".to_string());
+
+        let merged = handler.merge_messages();
+        assert_eq!(
+            merged["Profile"]["bio"],
+            "{gender, select, other {Profile.bio {name}}}"
+        );
+        assert_eq!(
+            merged["Profile"]["welcome"],
+            "{role, select, other {{count, plural, one {# Profile.welcome} other {# Profile.welcome}}}}"
+        );
+    }
+
+    #[test]
+    fn test_conflict_usages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut handler = create_test_message_handler();
+        for (file, padding) in [("first.tsx", ""), ("second.tsx", "\n\n")] {
+            let path = dir.path().join(file);
+            fs::write(
+                &path,
+                format!(
+                    "{padding}import {{ useTranslations }} from 'next-intl';\n\
+                     export function Cart() {{\n\
+                     const t = useTranslations('Cart');\n\
+                     return <p>{{t('title')}}</p>;\n\
+                     }}\n"
+                ),
+            )
+            .unwrap();
+            let extraction = Extractor::new().extract(&path).unwrap();
+            handler.add_extraction(extraction, file.to_string());
+        }
+
+        let conflicts = handler.get_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].files, vec!["first.tsx", "second.tsx"]);
+        assert_eq!(
+            conflicts[0].usages,
+            vec!["first.tsx:4:14", "second.tsx:6:14"]
+        );
+    }
+
+    #[test]
+    fn test_deterministic_output() {
+        let merged = || {
+            let mut handler = create_test_message_handler();
+            let keys = ["section", "section.title", "b", "a", "c.d", "c.e", "key1"];
+            let messages = HashMap::from([
+                (
+                    "Cart".to_string(),
+                    keys.iter().map(|key| key.to_string()).collect(),
+                ),
+                (
+                    "namespace1".to_string(),
+                    HashSet::from(["key1".to_string()]),
+                ),
+            ]);
+            handler.add_extracted_messages(messages, "file1.ts".to_string());
+            let conflicts: Vec<_> = handler
+                .get_conflicts()
+                .iter()
+                .map(|conflict| conflict.key.clone())
+                .collect();
+            (
+                serde_json::to_string_pretty(&handler.merge_messages()).unwrap(),
+                conflicts,
+            )
+        };
+
+        let first = merged();
+        for _ in 0..10 {
+            assert_eq!(merged(), first);
+        }
+        assert_eq!(first.1, vec!["section"]);
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let keys = |handler: &MessageHandler| -> Vec<String> {
+            let OrderedValue::Object(namespaces) = handler.ordered_messages() else {
+                unreachable!()
+            };
+            namespaces
+                .iter()
+                .flat_map(|(namespace, messages)| match messages {
+                    OrderedValue::Object(keys) => keys
+                        .iter()
+                        .map(|(key, _)| format!("{namespace}.{key}"))
+                        .collect(),
+                    OrderedValue::Value(_) => vec![namespace.clone()],
+                })
+                .collect()
+        };
+        let add = |handler: &mut MessageHandler| {
+            for (namespace, key) in [
+                ("namespace2", "key5"),
+                ("New", "title"),
+                ("namespace2", "key4"),
+                ("namespace1", "key1"),
+            ] {
+                handler.add_extracted_message(
+                    namespace.to_string(),
+                    key.to_string(),
+                    "file1.ts".to_string(),
+                );
+            }
+        };
+
+        let mut handler = create_test_message_handler().with_sort_order(SortOrder::Alphabetical);
+        add(&mut handler);
+        assert_eq!(
+            keys(&handler),
+            vec![
+                "New.title",
+                "namespace1.key1",
+                "namespace2.key4",
+                "namespace2.key5"
+            ]
+        );
+
+        let mut handler = create_test_message_handler().with_sort_order(SortOrder::Usage);
+        add(&mut handler);
+        assert_eq!(
+            keys(&handler),
+            vec![
+                "namespace2.key5",
+                "namespace2.key4",
+                "New.title",
+                "namespace1.key1"
+            ]
+        );
+
+        let mut handler = create_test_message_handler().with_sort_order(SortOrder::Existing);
+        handler.source_order = serde_json::from_str(
+            r#"{ "namespace2": { "key5": "", "key4": "" }, "namespace1": {} }"#,
+        )
+        .unwrap();
+        add(&mut handler);
+        assert_eq!(
+            keys(&handler),
+            vec![
+                "namespace2.key5",
+                "namespace2.key4",
+                "namespace1.key1",
+                "New.title"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_preserving_write() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        let existing = "{\n\t\"Nav\": {\n\t\t\"home\": \"Home\"\n\t},\n\t\"Cart\": {\n\t\t\"title\": \"Your cart\"\n\t}\n}\n";
+        fs::write(&path, existing).unwrap();
+
+        let mut handler = MessageHandler::new(&path).unwrap();
+        handler.add_extracted_message("Nav".to_string(), "home".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), existing);
+
+        handler.add_extracted_message("Cart".to_string(), "empty".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n\t\"Nav\": {\n\t\t\"home\": \"Home\"\n\t},\n\t\"Cart\": {\n\t\t\"title\": \"Your cart\",\n\t\t\"empty\": \"Cart.empty\"\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_jsonc_source_messages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(
+            &path,
+            r#"{
+  // Checkout flow
+  "Cart": {
+    "title": "Your cart", // Page heading
+  },
+}
+"#,
+        )
+        .unwrap();
+
+        let mut handler = MessageHandler::new(&path).unwrap();
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Cart".to_string(), "empty".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"{
+  // Checkout flow
+  "Cart": {
+    "title": "Your cart", // Page heading
+    "empty": "Cart.empty"
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_yaml_messages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.yaml");
+        fs::write(&path, "# Shop\nCart:\n    title: Your cart\n").unwrap();
+
+        let mut handler = MessageHandler::new(&path).unwrap();
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Cart".to_string(), "empty".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Cart:\n    title: Your cart\n    empty: Cart.empty\n"
+        );
+    }
+
+    #[test]
+    fn test_toml_messages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.txt");
+        fs::write(&path, "[Cart]\ntitle = \"Your cart\"\n").unwrap();
+
+        let mut handler = MessageHandler::open(&path, CatalogFormat::Toml).unwrap();
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Nav".to_string(), "home".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[Cart]\ntitle = \"Your cart\"\n\n[Nav]\nhome = \"Nav.home\"\n"
+        );
+    }
+
+    #[test]
+    fn test_flat_messages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(&path, "{\n  \"Cart.title\": \"Your cart\"\n}").unwrap();
+
+        let mut handler = MessageHandler::new(&path).unwrap();
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Nav".to_string(), "home".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"Cart.title\": \"Your cart\",\n  \"Nav.home\": \"Nav.home\"\n}"
+        );
+
+        let handler = handler.with_shape(Shape::Nested);
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"Cart\": {\n    \"title\": \"Your cart\"\n  },\n  \"Nav\": {\n    \"home\": \"Nav.home\"\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn test_source_layers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        let base = dir.path().join("base.json");
+        let overrides = dir.path().join("overrides.yaml");
+        fs::write(&path, r#"{ "Cart": { "title": "Cart" } }"#).unwrap();
+        fs::write(
+            &base,
+            r#"{ "Cart": { "title": "Basket", "empty": "Empty" } }"#,
+        )
+        .unwrap();
+        fs::write(&overrides, "Cart:\n  title: Your cart\n").unwrap();
+
+        let mut handler = MessageHandler::new(&path)
+            .unwrap()
+            .with_source_layers(&[base, overrides])
+            .unwrap();
+        for key in ["title", "empty", "total"] {
+            handler.add_extracted_message("Cart".to_string(), key.to_string(), "a.ts".to_string());
+        }
+        assert_eq!(
+            Value::Object(handler.merge_messages()),
+            json!({ "Cart": { "title": "Your cart", "empty": "Empty", "total": "Cart.total" } })
+        );
+    }
+
+    #[test]
+    fn test_namespace_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let template = dir.path().join("messages/{namespace}.json");
+        fs::create_dir_all(dir.path().join("messages")).unwrap();
+        fs::write(
+            dir.path().join("messages/Cart.json"),
+            "{\n  // Checkout\n  \"title\": \"Your cart\"\n}\n",
+        )
+        .unwrap();
+
+        let mut handler = MessageHandler::new(&template).unwrap();
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Nav".to_string(), "home".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&template).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("messages/Cart.json")).unwrap(),
+            "{\n  // Checkout\n  \"title\": \"Your cart\"\n}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("messages/Nav.json")).unwrap(),
+            "{\n  \"home\": \"Nav.home\"\n}"
+        );
+
+        // The file of a namespace whose messages are all removed is emptied
+        let mut handler = MessageHandler::new(&template).unwrap();
+        handler.add_extracted_message("Nav".to_string(), "home".to_string(), "a.ts".to_string());
+        assert_eq!(handler.removed_keys(), vec!["Cart.title"]);
+        handler.write_merged_messages(&template).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("messages/Cart.json")).unwrap(),
+            "{}"
+        );
+        let handler = MessageHandler::new(&template).unwrap();
+        assert_eq!(
+            Value::Object(handler.source_messages().clone()),
+            json!({ "Nav": { "home": "Nav.home" } })
+        );
+    }
+
+    #[test]
+    fn test_keep_removed() {
+        let mut handler = create_test_message_handler();
+        handler.add_extracted_message(
+            "namespace1".to_string(),
+            "key1".to_string(),
+            "test_file".to_string(),
+        );
+        assert_eq!(
+            handler.removed_keys(),
+            vec![
+                "namespace1.key2",
+                "namespace1.key3",
+                "namespace2.key4",
+                "namespace2.key5"
+            ]
+        );
+        assert_eq!(
+            Value::Object(handler.merge_messages()),
+            json!({ "namespace1": { "key1": "value1" } })
+        );
+
+        let handler = handler.with_removed_keys(RemovedKeys::Keep);
+        assert_eq!(
+            Value::Object(handler.merge_messages()),
+            json!({
+                "namespace1": { "key1": "value1", "key2": "value2", "key3": "value3" },
+                "namespace2": { "key4": "value4", "key5": "value5" }
+            })
+        );
+        assert_eq!(handler.removed_keys().len(), 4);
+    }
+
+    #[test]
+    fn test_obsolete_keys() {
+        let mut handler = create_test_message_handler().with_removed_keys(RemovedKeys::Obsolete);
+        handler.source_messages.insert(
+            OBSOLETE_KEY.to_string(),
+            json!({ "namespace3": { "key6": "value6", "key7": "value7" } }),
+        );
+        handler.add_extracted_message(
+            "namespace1".to_string(),
+            "key1".to_string(),
+            "test_file".to_string(),
+        );
+        handler.add_extracted_message(
+            "namespace2".to_string(),
+            "key4".to_string(),
+            "test_file".to_string(),
+        );
+        handler.add_extracted_message(
+            "namespace3".to_string(),
+            "key6".to_string(),
+            "test_file".to_string(),
+        );
+
+        assert_eq!(
+            Value::Object(handler.merge_messages()),
+            json!({
+                "namespace1": { "key1": "value1" },
+                "namespace2": { "key4": "value4" },
+                "namespace3": { "key6": "value6" },
+                "__obsolete__": {
+                    "namespace1": { "key2": "value2", "key3": "value3" },
+                    "namespace2": { "key5": "value5" },
+                    "namespace3": { "key7": "value7" }
+                }
+            })
+        );
+        assert_eq!(
+            handler.removed_keys(),
+            vec!["namespace1.key2", "namespace1.key3", "namespace2.key5"]
+        );
+    }
+
+    #[test]
+    fn test_attic() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        let attic_path = dir.path().join("messages.attic.json");
+        fs::write(&path, r#"{ "Cart": { "title": "Cart", "old": "Old" } }"#).unwrap();
+
+        let mut handler = MessageHandler::new(&path)
+            .unwrap()
+            .with_attic(attic_path.clone());
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+
+        let attic: Value = serde_json::from_str(&fs::read_to_string(&attic_path).unwrap()).unwrap();
+        let (_, removed) = attic.as_object().unwrap().iter().next().unwrap();
+        assert_eq!(removed, &json!({ "Cart": { "old": "Old" } }));
+    }
+
+    #[test]
+    fn test_translation_memory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(
+            &path,
+            r#"{ "Cart": { "title": "Warenkorb" }, "Nav": { "home": "Start", "shop": "Laden" } }"#,
+        )
+        .unwrap();
+        let provenance_path = dir.path().join("messages.meta.json");
+        fs::write(
+            &provenance_path,
+            r#"{ "Cart.title": ["a.ts:3:5"], "Nav.home": ["b.ts:1:1"], "Nav.shop": ["b.ts:2:1"] }"#,
+        )
+        .unwrap();
+
+        let mut handler = MessageHandler::new(&path)
+            .unwrap()
+            .with_provenance_output(provenance_path)
+            .with_translation_memory();
+        handler.add_extracted_message(
+            "Cart".to_string(),
+            "heading".to_string(),
+            "a.ts".to_string(),
+        );
+        handler.add_extracted_message("Nav".to_string(), "start".to_string(), "b.ts".to_string());
+        handler.add_extracted_message("Nav".to_string(), "about".to_string(), "b.ts".to_string());
+
+        assert_eq!(
+            handler.renamed_keys(),
+            BTreeMap::from([("Cart.heading".to_string(), "Cart.title".to_string())])
+        );
+        assert_eq!(
+            Value::Object(handler.merge_messages()),
+            json!({
+                "Cart": { "heading": "Warenkorb" },
+                "Nav": { "about": "Nav.about", "start": "Nav.start" }
+            })
+        );
+        assert_eq!(handler.removed_keys(), vec!["Nav.home", "Nav.shop"]);
+    }
+
+    #[test]
+    fn test_translation_memory_pairs_by_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(&path, r#"{ "Cart": { "title": "Warenkorb" } }"#).unwrap();
+        let provenance_path = dir.path().join("messages.meta.json");
+        fs::write(&provenance_path, r#"{ "Cart.title": ["cart.ts:3:5"] }"#).unwrap();
+        let handler = || {
+            MessageHandler::new(&path)
+                .unwrap()
+                .with_provenance_output(provenance_path.clone())
+                .with_translation_memory()
+        };
+
+        // The same namespace in another file is another message
+        let mut other_file = handler();
+        other_file.add_extracted_message(
+            "Cart".to_string(),
+            "heading".to_string(),
+            "banner.ts".to_string(),
+        );
+        assert!(other_file.renamed_keys().is_empty());
+
+        // A namespace renamed along with the key in the same file is the same message
+        let mut same_file = handler();
+        same_file.add_extracted_message(
+            "Basket".to_string(),
+            "heading".to_string(),
+            "cart.ts".to_string(),
+        );
+        assert_eq!(
+            same_file.renamed_keys(),
+            BTreeMap::from([("Basket.heading".to_string(), "Cart.title".to_string())])
+        );
+
+        // In watch mode, the files a message was used in before a file changed are known too
+        let mut watched = MessageHandler::new(&path)
+            .unwrap()
+            .with_translation_memory();
+        watched.add_extracted_message(
+            "Cart".to_string(),
+            "title".to_string(),
+            "page.ts".to_string(),
+        );
+        watched.remove_messages_for_file("page.ts");
+        watched.add_extracted_message(
+            "Cart".to_string(),
+            "heading".to_string(),
+            "page.ts".to_string(),
+        );
+        assert_eq!(
+            watched.renamed_keys(),
+            BTreeMap::from([("Cart.heading".to_string(), "Cart.title".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_conflicts_are_deduplicated() {
+        let mut handler = create_test_message_handler();
+        for file in ["b.ts", "a.ts", "b.ts"] {
+            handler.add_extracted_message("Nav".to_string(), "home".to_string(), file.to_string());
+        }
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "c.ts".to_string());
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+
+        let conflicts = handler.get_conflicts();
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].namespace, "Cart");
+        assert_eq!(conflicts[0].files, vec!["a.ts", "c.ts"]);
+        assert_eq!(conflicts[1].namespace, "Nav");
+        assert_eq!(conflicts[1].files, vec!["a.ts", "b.ts"]);
+        assert_eq!(conflicts[1].usage_counts(), vec![("a.ts", 1), ("b.ts", 1)]);
+    }
+
+    #[test]
+    fn test_backup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        let content = r#"{ "Cart": { "title": "Edited by hand" } }"#;
+        fs::write(&path, content).unwrap();
+
+        let handler = MessageHandler::new(&path).unwrap().with_backup().unwrap();
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("messages.json.bak")).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn test_provenance() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut handler = create_test_message_handler();
+        for file in ["b.tsx", "a.tsx"] {
+            let path = dir.path().join(file);
+            fs::write(
+                &path,
+                "import { useTranslations } from 'next-intl';\n\
+                 export function Cart() {\n\
+                 const t = useTranslations('Cart');\n\
+                 return <p title={t('title')}>{t('title')}</p>;\n\
+                 }\n",
+            )
+            .unwrap();
+            let extraction = Extractor::new().extract(&path).unwrap();
+            handler.add_extraction(extraction, file.to_string());
+        }
+        handler.add_extracted_message("Nav".to_string(), "home".to_string(), "c.tsx".to_string());
+
+        assert_eq!(
+            Value::Object(handler.provenance()),
+            json!({
+                "Cart.title": ["a.tsx:4:20", "a.tsx:4:33", "b.tsx:4:20", "b.tsx:4:33"],
+                "Nav.home": ["c.tsx"]
+            })
+        );
+        handler.remove_messages_for_file("a.tsx");
+        assert_eq!(
+            handler.provenance()["Cart.title"],
+            json!(["b.tsx:4:20", "b.tsx:4:33"])
+        );
+    }
+
+    #[test]
+    fn test_key_changes() {
+        let mut handler = create_test_message_handler();
+        handler.add_extracted_message(
+            "namespace1".to_string(),
+            "key1".to_string(),
+            "a.ts".to_string(),
+        );
+        handler.add_extracted_message("Nav".to_string(), "home".to_string(), "a.ts".to_string());
+
+        let changes = handler.key_changes();
+        assert_eq!(
+            changes["namespace1"],
+            KeyChanges {
+                added: 0,
+                removed: 2,
+                unchanged: 1
+            }
+        );
+        assert_eq!(changes["namespace2"].removed, 2);
+        assert_eq!(changes["Nav"].added, 1);
+    }
+
+    #[test]
+    fn test_key_policy() {
+        let mut handler = create_test_message_handler().with_key_policy(KeyPolicy {
+            casing: Some(Casing::Camel),
+            ..Default::default()
+        });
+        handler.add_extracted_message(
+            "Cart".to_string(),
+            "addItem".to_string(),
+            "a.ts".to_string(),
+        );
+        handler.add_extracted_message(
+            "Cart".to_string(),
+            "AddItem".to_string(),
+            "b.ts".to_string(),
+        );
+
+        let violations = handler.get_policy_violations();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].key, "AddItem");
+        assert_eq!(violations[0].usage, "b.ts");
+
+        handler.remove_messages_for_file("b.ts");
+        assert!(handler.get_policy_violations().is_empty());
+    }
+
+    #[test]
+    fn test_merge_strategy() {
+        struct KeepAll;
+        impl MergeStrategy for KeepAll {
+            fn merge(&self, candidate: &MergeCandidate) -> Option<Value> {
+                PreferExtracted.merge(candidate)
+            }
+
+            fn remove(&self, _key: &str, _value: &Value) -> bool {
+                false
+            }
+        }
+
+        let mut handler = create_test_message_handler().with_merge_strategy(Box::new(KeepAll));
+        handler.add_extracted_message(
+            "namespace1".to_string(),
+            "key1".to_string(),
+            "a.ts".to_string(),
+        );
+        handler.add_default_message("namespace1", "key1", "From the code".to_string());
+
+        let merged = handler.merge_messages();
+        assert_eq!(merged["namespace1"]["key1"], "From the code");
+        assert_eq!(merged["namespace2"]["key4"], "value4");
+        assert!(handler.removed_keys().is_empty());
+    }
+
+    #[test]
+    fn test_deeply_nested_messages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(
+            &path,
+            r#"{ "a": { "b": { "c": { "key": "Existing" }, "key": "Other" } } }"#,
+        )
+        .unwrap();
+
+        let mut handler = MessageHandler::new(&path).unwrap();
+        handler.add_extracted_message("a.b.c".to_string(), "key".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("a.b".to_string(), "key".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("a.b.c".to_string(), "new".to_string(), "a.ts".to_string());
+
+        assert_eq!(
+            Value::Object(handler.merge_messages()),
+            json!({ "a": { "b": { "c": { "key": "Existing", "new": "a.b.c.new" }, "key": "Other" } } })
+        );
+    }
+
+    #[test]
+    fn test_entry_shape() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(
+            &path,
+            r#"{ "Cart": { "title": { "message": "Cart", "description": "Heading" } } }"#,
+        )
+        .unwrap();
+
+        let mut handler = MessageHandler::new(&path).unwrap();
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Cart".to_string(),
+                HashSet::from(["title".to_string(), "empty".to_string()]),
+            )]),
+            descriptions: HashMap::from([(
+                "Cart".to_string(),
+                HashMap::from([("empty".to_string(), "Shown on empty cart".to_string())]),
+            )]),
+            ..Default::default()
+        };
+        handler.add_extraction(extraction, "file1.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            json!({ "Cart": {
+                "empty": { "message": "Cart.empty", "description": "Shown on empty cart" },
+                "title": { "message": "Cart", "description": "Heading" }
+            } })
+        );
+
+        let mut handler = MessageHandler::new(&path)
+            .unwrap()
+            .with_entry_shape(EntryShape::Simple);
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, json!({ "Cart": { "title": "Cart" } }));
+    }
+
+    #[test]
+    fn test_placeholder_mismatches() {
+        let mut handler = create_test_message_handler();
+        handler.source_messages = json!({
+            "Cart": {
+                "greeting": "Hello {name}, you have <b>{count}</b> items",
+                "total": "Total: {total}",
+                "title": "Cart {name}"
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let extraction = Extraction {
+            translations: HashMap::from([(
+                "Cart".to_string(),
+                HashSet::from([
+                    "greeting".to_string(),
+                    "total".to_string(),
+                    "title".to_string(),
+                ]),
+            )]),
+            placeholders: HashMap::from([(
+                "Cart".to_string(),
+                HashMap::from([
+                    (
+                        "greeting".to_string(),
+                        BTreeSet::from(["user".to_string(), "count".to_string(), "b".to_string()]),
+                    ),
+                    ("total".to_string(), BTreeSet::from(["total".to_string()])),
+                ]),
+            )]),
+            ..Default::default()
+        };
+        handler.add_extraction(extraction, "file1.ts".to_string());
+
+        // `title` is used without values, which may be passed in a variable
+        assert_eq!(
+            handler.placeholder_mismatches(),
+            vec![PlaceholderMismatch {
+                key: "Cart.greeting".to_string(),
+                usage: "file1.ts".to_string(),
+                missing: vec!["name".to_string()],
+                extra: vec!["user".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_format_overrides() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(&path, "{\n  \"Cart\": {\n    \"title\": \"Cart\"\n  }\n}\n").unwrap();
+
+        let mut handler =
+            MessageHandler::new(&path)
+                .unwrap()
+                .with_format_overrides(FormatOverrides {
+                    indent: Some("\t".to_string()),
+                    line_ending: Some(LineEnding::Crlf),
+                    final_newline: Some(false),
+                    ..Default::default()
+                });
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\r\n\t\"Cart\": {\r\n\t\t\"title\": \"Cart\"\r\n\t}\r\n}"
+        );
+    }
+
+    #[test]
+    fn test_without_new_keys() {
+        let mut handler = create_test_message_handler().without_new_keys();
+        handler.add_extracted_message(
+            "namespace1".to_string(),
+            "key1".to_string(),
+            "file1.ts".to_string(),
+        );
+        handler.add_extracted_message(
+            "namespace1".to_string(),
+            "new".to_string(),
+            "file1.ts".to_string(),
+        );
+        handler.add_extracted_message(
+            "added".to_string(),
+            "new".to_string(),
+            "file1.ts".to_string(),
+        );
+
+        assert_eq!(
+            Value::Object(handler.merge_messages()),
+            json!({ "namespace1": { "key1": "value1" } })
+        );
+    }
+
+    #[test]
+    fn test_keys_by_file() {
+        let mut handler = create_test_message_handler();
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Cart".to_string(), "total".to_string(), "a.ts".to_string());
+        handler.add_extracted_message("Cart".to_string(), "title".to_string(), "b.ts".to_string());
+
+        let keys_by_file = handler.keys_by_file();
+        assert_eq!(
+            keys_by_file["a.ts"],
+            BTreeSet::from(["Cart.title".to_string(), "Cart.total".to_string()])
+        );
+        assert_eq!(
+            keys_by_file["b.ts"],
+            BTreeSet::from(["Cart.title".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_move_namespace() {
+        let mut handler = create_test_message_handler();
+        handler.move_namespace("namespace1", "Shop.namespace1");
+        handler.move_namespace("missing", "Shop.missing");
+
+        assert_eq!(
+            Value::Object(handler.source_messages().clone()),
+            json!({
+                "Shop": { "namespace1": { "key1": "value1", "key2": "value2", "key3": "value3" } },
+                "namespace2": { "key4": "value4", "key5": "value5" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_format_without_extracted_messages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.json");
+        fs::write(&path, r#"{"Nav":{"home":"Home"},"Cart":{"title":"Cart"}}"#).unwrap();
+
+        let handler = MessageHandler::open(&path, CatalogFormat::Json)
+            .unwrap()
+            .with_removed_keys(RemovedKeys::Keep)
+            .with_sort_order(SortOrder::Alphabetical)
+            .with_format_overrides(FormatOverrides {
+                indent: Some("  ".to_string()),
+                ..Default::default()
+            });
+        handler.write_merged_messages(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"Cart\": {\n    \"title\": \"Cart\"\n  },\n  \"Nav\": {\n    \"home\": \"Home\"\n  }\n}"
+        );
+    }
 }
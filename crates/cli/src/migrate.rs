@@ -0,0 +1,368 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::shape;
+
+/// Plural suffixes of i18next keys with the ICU selector of their option, in ICU order. i18next
+/// uses `_zero` for a count of 0 in every language
+const I18NEXT_PLURAL_SUFFIXES: [(&str, &str); 6] = [
+    ("zero", "=0"),
+    ("one", "one"),
+    ("two", "two"),
+    ("few", "few"),
+    ("many", "many"),
+    ("other", "other"),
+];
+
+/// Catalogs of another library to convert to next-intl messages
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Migration {
+    /// The output of `formatjs extract`
+    Formatjs { input: PathBuf, ids: IdRule },
+    /// An i18next JSON catalog, below the namespace if any
+    I18next {
+        input: PathBuf,
+        namespace: Option<String>,
+    },
+}
+
+impl Migration {
+    /// The nested messages of the input and their descriptions by full key
+    pub fn messages(&self) -> Result<(Map<String, Value>, Map<String, Value>)> {
+        match self {
+            Self::Formatjs { input, ids } => formatjs(read_object(input)?, ids),
+            Self::I18next { input, namespace } => {
+                let mut messages = i18next(read_object(input)?);
+                if let Some(namespace) = namespace {
+                    let parts: Vec<&str> = namespace.split('.').collect();
+                    let mut nested = Map::new();
+                    shape::insert_path(&mut nested, &parts, Value::Object(messages));
+                    messages = nested;
+                }
+                Ok((messages, Map::new()))
+            }
+        }
+    }
+}
+
+/// How the ids of another library map to the keys of next-intl messages
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdRule {
+    /// Separates the namespaces of an id, e.g. `_` for `cart_title`
+    pub separator: String,
+    /// Namespace of every message, e.g. `Legacy`
+    pub namespace: Option<String>,
+}
+
+impl IdRule {
+    /// The full key of an id, e.g. `Legacy.cart.title` for `cart_title`
+    pub fn key(&self, id: &str) -> String {
+        let key = if self.separator.is_empty() {
+            id.to_string()
+        } else {
+            id.split(self.separator.as_str())
+                .collect::<Vec<_>>()
+                .join(".")
+        };
+        match &self.namespace {
+            Some(namespace) => format!("{namespace}.{key}"),
+            None => key,
+        }
+    }
+}
+
+fn read_object(path: &Path) -> Result<Map<String, Value>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    match serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?
+    {
+        Value::Object(object) => Ok(object),
+        _ => Err(anyhow!("{} is not an object", path.display())),
+    }
+}
+
+/// Convert the messages that `formatjs extract` writes by id, e.g.
+/// `{"cart.title": {"defaultMessage": "Cart", "description": "Heading"}}`, or the ones it
+/// compiles to, e.g. `{"cart.title": "Cart"}`
+pub fn formatjs(
+    extracted: Map<String, Value>,
+    ids: &IdRule,
+) -> Result<(Map<String, Value>, Map<String, Value>)> {
+    let mut messages = Map::new();
+    let mut descriptions = Map::new();
+    for (id, entry) in extracted {
+        let key = ids.key(&id);
+        let message = match entry {
+            Value::String(message) => message,
+            Value::Object(mut entry) => {
+                if let Some(description) = entry.remove("description") {
+                    descriptions.insert(key.clone(), description);
+                }
+                match entry.remove("defaultMessage") {
+                    Some(Value::String(message)) => message,
+                    _ => return Err(anyhow!("Message '{id}' has no defaultMessage")),
+                }
+            }
+            _ => return Err(anyhow!("Message '{id}' is not a string or an object")),
+        };
+        insert_message(&mut messages, &key, Value::String(message));
+    }
+    Ok((messages, descriptions))
+}
+
+/// Convert an i18next catalog to ICU messages, e.g. `Hello {{name}}` to `Hello {name}`
+///
+/// Keys with plural suffixes become one plural message, e.g. `item_one` and `item_other` to
+/// `item` with `{count, plural, one {# item} other {# items}}`, `_ordinal_` ones a
+/// `selectordinal` message and i18next v3 `item` and `item_plural` the `one` and `other` option.
+/// Dotted keys are nested
+pub fn i18next(catalog: Map<String, Value>) -> Map<String, Value> {
+    convert_i18next(shape::unflatten(catalog))
+}
+
+/// The options of a plural message, converted from the i18next keys with plural suffixes
+struct Plural {
+    key: String,
+    ordinal: bool,
+    /// ICU selector and text, e.g. `one` and `# item`
+    options: Vec<(&'static str, String)>,
+}
+
+fn convert_i18next(catalog: Map<String, Value>) -> Map<String, Value> {
+    // i18next v3 plurals, the key without suffix is the singular
+    let v3_plurals: Vec<String> = catalog
+        .keys()
+        .filter_map(|key| key.strip_suffix("_plural"))
+        .filter(|base| catalog.get(*base).is_some_and(Value::is_string))
+        .map(str::to_string)
+        .collect();
+
+    let mut messages = Map::new();
+    let mut plurals: Vec<Plural> = Vec::new();
+    for (key, value) in catalog {
+        let text = match value {
+            Value::Object(nested) => {
+                messages.insert(key, Value::Object(convert_i18next(nested)));
+                continue;
+            }
+            Value::String(text) => text,
+            value => {
+                messages.insert(key, value);
+                continue;
+            }
+        };
+        let plural = if v3_plurals.contains(&key) {
+            Some((key.clone(), false, "one"))
+        } else if let Some(base) = key
+            .strip_suffix("_plural")
+            .filter(|base| v3_plurals.iter().any(|plural| plural == base))
+        {
+            Some((base.to_string(), false, "other"))
+        } else {
+            plural_suffix(&key)
+        };
+        match plural {
+            Some((base, ordinal, selector)) => {
+                let option = (selector, icu_text(&text, true));
+                match plurals
+                    .iter_mut()
+                    .find(|plural| plural.key == base && plural.ordinal == ordinal)
+                {
+                    Some(plural) => plural.options.push(option),
+                    None => plurals.push(Plural {
+                        key: base,
+                        ordinal,
+                        options: vec![option],
+                    }),
+                }
+            }
+            None => {
+                messages.insert(key, Value::String(icu_text(&text, false)));
+            }
+        }
+    }
+
+    for Plural {
+        key,
+        ordinal,
+        mut options,
+    } in plurals
+    {
+        options.sort_by_key(|(selector, _)| {
+            I18NEXT_PLURAL_SUFFIXES
+                .iter()
+                .position(|(_, icu)| icu == selector)
+        });
+        if !options.iter().any(|(selector, _)| *selector == "other") {
+            // ICU needs an `other` option, the last form is the closest
+            if let Some((selector, _)) = options.last_mut() {
+                *selector = "other";
+            }
+        }
+        if messages.contains_key(&key) {
+            warn!(
+                "Plural message '{}' conflicts with another message, left out",
+                key
+            );
+            continue;
+        }
+        let kind = if ordinal { "selectordinal" } else { "plural" };
+        let options: Vec<String> = options
+            .into_iter()
+            .map(|(selector, text)| format!("{selector} {{{text}}}"))
+            .collect();
+        let message = format!("{{count, {kind}, {}}}", options.join(" "));
+        messages.insert(key, Value::String(message));
+    }
+    messages
+}
+
+/// The key, whether it's ordinal and the ICU selector of a key with an i18next v4 plural
+/// suffix, e.g. `item`, `false` and `one` for `item_one`
+fn plural_suffix(key: &str) -> Option<(String, bool, &'static str)> {
+    let (rest, suffix) = key.rsplit_once('_')?;
+    let &(_, selector) = I18NEXT_PLURAL_SUFFIXES
+        .iter()
+        .find(|(i18next, _)| *i18next == suffix)?;
+    let (base, ordinal) = match rest.strip_suffix("_ordinal") {
+        Some(base) => (base, true),
+        None => (rest, false),
+    };
+    if base.is_empty() {
+        return None;
+    }
+    Some((base.to_string(), ordinal, selector))
+}
+
+/// Replace the i18next interpolations of a text by ICU arguments, e.g. `{{name}}` by `{name}`,
+/// and quote the characters that are special in ICU
+///
+/// In plural options `{{count}}` is `#`. Formats other than `number` are left out, like the
+/// one of `{{date, LL}}`, as ICU has its own
+fn icu_text(text: &str, in_plural: bool) -> String {
+    if text.contains("$t(") {
+        warn!(
+            "Nested translations aren't supported by next-intl: {}",
+            text
+        );
+    }
+    let mut icu = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(interpolation) = rest.strip_prefix("{{") {
+            if let Some(end) = interpolation.find("}}") {
+                let (name, format) = match interpolation[..end].split_once(',') {
+                    Some((name, format)) => (name, Some(format.trim())),
+                    None => (&interpolation[..end], None),
+                };
+                // `{{- name}}` is left unescaped by i18next, which React does anyway
+                let name = name.trim().trim_start_matches('-').trim();
+                match format {
+                    None if in_plural && name == "count" => icu.push('#'),
+                    Some("number") => icu.push_str(&format!("{{{name}, number}}")),
+                    _ => icu.push_str(&format!("{{{name}}}")),
+                }
+                rest = &interpolation[end + 2..];
+                continue;
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '{' | '}' => icu.push_str(&format!("'{c}'")),
+            '#' if in_plural => icu.push_str("'#'"),
+            // An apostrophe before a special character would quote it
+            '\'' if rest.starts_with(['{', '}', '\'', '#']) => icu.push_str("''"),
+            c => icu.push(c),
+        }
+    }
+    icu
+}
+
+/// Insert a message at its full key, unless another message is in the way
+fn insert_message(messages: &mut Map<String, Value>, key: &str, value: Value) {
+    let parts: Vec<&str> = key.split('.').collect();
+    let mut current = &*messages;
+    for (index, part) in parts.iter().enumerate() {
+        match current.get(*part) {
+            Some(Value::Object(nested)) if index + 1 < parts.len() => current = nested,
+            Some(_) => {
+                warn!("Message '{}' conflicts with another message, left out", key);
+                return;
+            }
+            None => break,
+        }
+    }
+    shape::insert_path(messages, &parts, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_formatjs() {
+        let extracted = json!({
+            "cart_title": { "defaultMessage": "Cart", "description": "Heading" },
+            "cart_total": { "defaultMessage": "Total: {total, number}" },
+            "home": "Home"
+        });
+        let ids = IdRule {
+            separator: "_".to_string(),
+            namespace: Some("Legacy".to_string()),
+        };
+        let (messages, descriptions) =
+            formatjs(extracted.as_object().unwrap().clone(), &ids).unwrap();
+        assert_eq!(
+            Value::Object(messages),
+            json!({ "Legacy": {
+                "cart": { "title": "Cart", "total": "Total: {total, number}" },
+                "home": "Home"
+            } })
+        );
+        assert_eq!(
+            Value::Object(descriptions),
+            json!({ "Legacy.cart.title": "Heading" })
+        );
+
+        let extracted = json!({ "title": { "description": "No message" } });
+        assert!(formatjs(extracted.as_object().unwrap().clone(), &ids).is_err());
+    }
+
+    #[test]
+    fn test_i18next() {
+        let catalog = json!({
+            "greeting": "Hello {{name}}, it's {{- html}}!",
+            "total": "Total: {{amount, number}} on {{date, LL}}",
+            "item_one": "{{count}} item",
+            "item_other": "{{count}} items",
+            "item_zero": "No items",
+            "place_ordinal_one": "{{count}}st",
+            "place_ordinal_other": "{{count}}th",
+            "file": "One file",
+            "file_plural": "{{count}} files",
+            "braces": "Use { and } or '{'",
+            "Nav.home": "Home",
+            "Cart": { "title": "Cart" }
+        });
+        let messages = i18next(catalog.as_object().unwrap().clone());
+        assert_eq!(
+            Value::Object(messages.clone()),
+            json!({
+                "greeting": "Hello {name}, it's {html}!",
+                "total": "Total: {amount, number} on {date}",
+                "item": "{count, plural, =0 {No items} one {# item} other {# items}}",
+                "place": "{count, selectordinal, one {#st} other {#th}}",
+                "file": "{count, plural, one {One file} other {# files}}",
+                "braces": "Use '{' and '}' or '''{''",
+                "Nav": { "home": "Home" },
+                "Cart": { "title": "Cart" }
+            })
+        );
+        assert!(crate::icu::check_messages(&messages).is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+use clap::ValueEnum;
+use serde::de::{Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// How the keys of the written messages are ordered, at every level
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Alphabetically
+    Alphabetical,
+    /// In the order the keys are first used in the source files
+    Usage,
+    /// In the order of the existing messages, new keys are added at the end alphabetically
+    #[default]
+    Existing,
+}
+
+/// The order of the keys in a messages file, at every level
+///
+/// `serde_json` sorts the keys of the objects it parses, so the order is read separately
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyOrder {
+    keys: Vec<(String, KeyOrder)>,
+}
+
+impl KeyOrder {
+    /// Position of a key at this level
+    pub fn position(&self, key: &str) -> Option<usize> {
+        self.keys.iter().position(|(k, _)| k == key)
+    }
+
+    /// The order of the keys nested below a key
+    pub fn get(&self, key: &str) -> Option<&KeyOrder> {
+        self.keys
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, order)| order)
+    }
+
+    /// The order of dot-delimited keys once they're nested, e.g. `Cart.title` as `title` in
+    /// `Cart`
+    pub fn unflatten(&self) -> KeyOrder {
+        let mut unflattened = KeyOrder::default();
+        for (key, nested) in &self.keys {
+            let mut current = &mut unflattened;
+            for part in key.split('.') {
+                let index = match current.position(part) {
+                    Some(index) => index,
+                    None => {
+                        current.keys.push((part.to_string(), KeyOrder::default()));
+                        current.keys.len() - 1
+                    }
+                };
+                current = &mut current.keys[index].1;
+            }
+            current.keys.extend(nested.unflatten().keys);
+        }
+        unflattened
+    }
+}
+
+impl FromIterator<(String, KeyOrder)> for KeyOrder {
+    fn from_iter<I: IntoIterator<Item = (String, KeyOrder)>>(keys: I) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyOrder {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyOrderVisitor;
+
+        impl<'de> Visitor<'de> for KeyOrderVisitor {
+            type Value = KeyOrder;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON value")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeyOrder, A::Error> {
+                let mut keys = Vec::new();
+                while let Some((key, order)) = map.next_entry::<String, KeyOrder>()? {
+                    keys.push((key, order));
+                }
+                Ok(KeyOrder { keys })
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<KeyOrder, A::Error> {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(KeyOrder::default())
+            }
+
+            fn visit_str<E>(self, _: &str) -> Result<KeyOrder, E> {
+                Ok(KeyOrder::default())
+            }
+
+            fn visit_bool<E>(self, _: bool) -> Result<KeyOrder, E> {
+                Ok(KeyOrder::default())
+            }
+
+            fn visit_i64<E>(self, _: i64) -> Result<KeyOrder, E> {
+                Ok(KeyOrder::default())
+            }
+
+            fn visit_u64<E>(self, _: u64) -> Result<KeyOrder, E> {
+                Ok(KeyOrder::default())
+            }
+
+            fn visit_f64<E>(self, _: f64) -> Result<KeyOrder, E> {
+                Ok(KeyOrder::default())
+            }
+
+            fn visit_unit<E>(self) -> Result<KeyOrder, E> {
+                Ok(KeyOrder::default())
+            }
+        }
+
+        deserializer.deserialize_any(KeyOrderVisitor)
+    }
+}
+
+/// A JSON value whose objects keep their keys in the given order when serialized
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderedValue {
+    Value(Value),
+    Object(Vec<(String, OrderedValue)>),
+}
+
+impl OrderedValue {
+    /// The plain JSON value, without the order of the keys
+    pub fn into_value(self) -> Value {
+        match self {
+            Self::Value(value) => value,
+            Self::Object(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// A plain JSON value with its keys in an order, keys without a position come last,
+    /// alphabetically
+    pub fn with_order(value: Value, order: &KeyOrder) -> Self {
+        let Value::Object(map) = value else {
+            return Self::Value(value);
+        };
+        let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+        entries.sort_by_key(|(key, _)| order.position(key).unwrap_or(usize::MAX));
+        Self::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let nested = order.get(&key).cloned().unwrap_or_default();
+                    let value = Self::with_order(value, &nested);
+                    (key, value)
+                })
+                .collect(),
+        )
+    }
+
+    /// The order of the keys, at every level
+    pub fn key_order(&self) -> KeyOrder {
+        match self {
+            Self::Value(_) => KeyOrder::default(),
+            Self::Object(entries) => KeyOrder {
+                keys: entries
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.key_order()))
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl Serialize for OrderedValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Value(value) => value.serialize(serializer),
+            Self::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_order() {
+        let order: KeyOrder = serde_json::from_str(
+            r#"{ "b": { "y": 1, "x": [{ "z": true }] }, "a": "value", "c": null }"#,
+        )
+        .unwrap();
+        assert_eq!(order.position("b"), Some(0));
+        assert_eq!(order.position("c"), Some(2));
+        assert_eq!(order.position("missing"), None);
+        assert_eq!(order.get("b").unwrap().position("x"), Some(1));
+
+        let ordered = OrderedValue::Object(vec![
+            ("b".to_string(), OrderedValue::Value(Value::from(1))),
+            ("a".to_string(), OrderedValue::Value(Value::from("value"))),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&ordered).unwrap(),
+            r#"{"b":1,"a":"value"}"#
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::files::FilePattern;
+
+/// The namespaces of the catalog and the files that may use them, e.g. to let a team own a
+/// part of the catalog
+///
+/// Read from a JSON file that maps namespaces to path globs, a namespace ending in `.*` also
+/// covers the namespaces below it:
+///
+/// ```json
+/// {
+///   "Checkout.*": "app/checkout/**",
+///   "Nav": ["components/nav/**", "app/layout.tsx"]
+/// }
+/// ```
+///
+/// The most specific rule of a namespace applies, namespaces without a rule may be used anywhere
+#[derive(Debug, Default)]
+pub struct Ownership {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// The namespace as written in the rules, e.g. `Checkout.*`
+    namespace: String,
+    paths: Vec<String>,
+    patterns: Vec<FilePattern>,
+}
+
+/// A namespace used in a file that doesn't own it
+#[derive(Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub namespace: String,
+    /// The namespace of the rule that applies, e.g. `Checkout.*`
+    pub rule: String,
+    /// Where the namespace may be used
+    pub paths: Vec<String>,
+}
+
+impl Ownership {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))?;
+        let Value::Object(rules) = value else {
+            return Err(anyhow!(
+                "Invalid {}: expected an object of namespaces",
+                path.display()
+            ));
+        };
+
+        let mut ownership = Self::default();
+        for (namespace, paths) in rules {
+            let paths = match paths {
+                Value::String(path) => vec![path],
+                Value::Array(paths) => paths
+                    .into_iter()
+                    .map(|path| match path {
+                        Value::String(path) => Ok(path),
+                        _ => Err(anyhow!("Invalid paths of {namespace}, expected strings")),
+                    })
+                    .collect::<Result<_>>()?,
+                _ => return Err(anyhow!("Invalid paths of {namespace}, expected strings")),
+            };
+            ownership.add_rule(namespace, paths)?;
+        }
+        Ok(ownership)
+    }
+
+    /// Only let the files matching one of the path globs use a namespace
+    pub fn add_rule(&mut self, namespace: String, paths: Vec<String>) -> Result<()> {
+        let patterns = paths
+            .iter()
+            .map(|path| FilePattern::new(path))
+            .collect::<Result<_>>()?;
+        self.rules.push(Rule {
+            namespace,
+            paths,
+            patterns,
+        });
+        Ok(())
+    }
+
+    /// The namespaces a file uses without owning them
+    pub fn violations<'n>(
+        &self,
+        file_path: &Path,
+        namespaces: impl IntoIterator<Item = &'n str>,
+    ) -> Vec<Violation> {
+        // The globs are relative to the working directory, like the pattern of the source files
+        let file_path = file_path.strip_prefix(".").unwrap_or(file_path);
+        let mut violations = Vec::new();
+        for namespace in namespaces {
+            let Some(rule) = self.rule(namespace) else {
+                continue;
+            };
+            if !rule
+                .patterns
+                .iter()
+                .any(|pattern| pattern.matches_path(file_path))
+            {
+                violations.push(Violation {
+                    namespace: namespace.to_string(),
+                    rule: rule.namespace.clone(),
+                    paths: rule.paths.clone(),
+                });
+            }
+        }
+        violations
+    }
+
+    /// The most specific rule that covers a namespace
+    fn rule(&self, namespace: &str) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .filter(|rule| match rule.namespace.strip_suffix(".*") {
+                Some(parent) => {
+                    namespace == parent
+                        || namespace
+                            .strip_prefix(parent)
+                            .is_some_and(|rest| rest.starts_with('.'))
+                }
+                None => namespace == rule.namespace,
+            })
+            .max_by_key(|rule| {
+                // An exact namespace is more specific than the same namespace with `.*`
+                let exact = !rule.namespace.ends_with(".*");
+                (rule.namespace.trim_end_matches(".*").len(), exact)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violations() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("ownership.json");
+        fs::write(
+            &path,
+            r#"{
+                "Checkout.*": "app/checkout/**",
+                "Checkout.Shared": ["app/**", "components/**"],
+                "Nav": ["components/nav/**"]
+            }"#,
+        )
+        .unwrap();
+        let ownership = Ownership::from_file(&path).unwrap();
+
+        let namespaces = [
+            "Checkout",
+            "Checkout.Summary",
+            "Checkout.Shared",
+            "Nav",
+            "Cart",
+        ];
+        assert!(ownership
+            .violations(Path::new("./app/checkout/page.tsx"), namespaces)
+            .iter()
+            .all(|violation| violation.namespace == "Nav"));
+
+        let violations = ownership.violations(Path::new("app/cart/page.tsx"), namespaces);
+        let namespaces: Vec<&str> = violations
+            .iter()
+            .map(|violation| violation.namespace.as_str())
+            .collect();
+        assert_eq!(namespaces, vec!["Checkout", "Checkout.Summary", "Nav"]);
+        assert_eq!(violations[1].rule, "Checkout.*");
+        assert_eq!(violations[1].paths, vec!["app/checkout/**"]);
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use std::fmt;
+
+/// Casing every part of a message key must use
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Casing {
+    /// e.g. `addToCart`
+    Camel,
+    /// e.g. `AddToCart`
+    Pascal,
+    /// e.g. `add_to_cart`
+    Snake,
+    /// e.g. `add-to-cart`
+    Kebab,
+}
+
+impl Casing {
+    fn matches(&self, part: &str) -> bool {
+        let Some(first) = part.chars().next() else {
+            return false;
+        };
+        match self {
+            Self::Camel => {
+                first.is_ascii_lowercase() && part.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+            Self::Pascal => {
+                first.is_ascii_uppercase() && part.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+            Self::Snake => part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+            Self::Kebab => part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
+        }
+    }
+}
+
+impl fmt::Display for Casing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Camel => "camelCase",
+            Self::Pascal => "PascalCase",
+            Self::Snake => "snake_case",
+            Self::Kebab => "kebab-case",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Characters allowed in the parts of namespaces and keys, written like a regex character
+/// class without brackets, e.g. `a-zA-Z0-9_`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharSet {
+    spec: String,
+    ranges: Vec<(char, char)>,
+}
+
+impl CharSet {
+    pub fn new(spec: &str) -> Result<Self> {
+        let chars: Vec<char> = spec.chars().collect();
+        let mut ranges = Vec::new();
+        let mut index = 0;
+        while index < chars.len() {
+            match chars.get(index + 1..=index + 2) {
+                Some(&['-', end]) => {
+                    if end < chars[index] {
+                        return Err(anyhow!("Invalid character range {}-{}", chars[index], end));
+                    }
+                    ranges.push((chars[index], end));
+                    index += 3;
+                }
+                _ => {
+                    ranges.push((chars[index], chars[index]));
+                    index += 1;
+                }
+            }
+        }
+        Ok(Self {
+            spec: spec.to_string(),
+            ranges,
+        })
+    }
+
+    fn contains(&self, c: char) -> bool {
+        self.ranges
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&c))
+    }
+}
+
+/// Constraints the keys of extracted messages must meet
+#[derive(Debug, Clone, Default)]
+pub struct KeyPolicy {
+    /// Maximum number of parts of the full key, e.g. 3 for `Checkout.Summary.total`
+    pub max_depth: Option<usize>,
+    /// Characters allowed in namespaces and keys
+    pub chars: Option<CharSet>,
+    /// Casing of the parts of the key, namespaces are not checked
+    pub casing: Option<Casing>,
+}
+
+/// How a message key breaks a [`KeyPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyProblem {
+    TooDeep { depth: usize, max_depth: usize },
+    InvalidChars { part: String, chars: String },
+    Casing { part: String, casing: Casing },
+}
+
+impl fmt::Display for PolicyProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooDeep { depth, max_depth } => {
+                write!(
+                    f,
+                    "is nested {depth} levels deep, at most {max_depth} are allowed"
+                )
+            }
+            Self::InvalidChars { part, chars } => {
+                write!(f, "'{part}' has characters outside of [{chars}]")
+            }
+            Self::Casing { part, casing } => write!(f, "'{part}' is not {casing}"),
+        }
+    }
+}
+
+impl KeyPolicy {
+    /// The ways a key in a namespace breaks the policy
+    pub fn check(&self, namespace: &str, key: &str) -> Vec<PolicyProblem> {
+        let namespace_parts = namespace.split('.').filter(|part| !part.is_empty());
+        let key_parts: Vec<&str> = key.split('.').collect();
+        let mut problems = Vec::new();
+
+        let depth = namespace_parts.clone().count() + key_parts.len();
+        if let Some(max_depth) = self.max_depth.filter(|max_depth| depth > *max_depth) {
+            problems.push(PolicyProblem::TooDeep { depth, max_depth });
+        }
+        if let Some(chars) = &self.chars {
+            for part in namespace_parts.chain(key_parts.iter().copied()) {
+                if !part.chars().all(|c| chars.contains(c)) {
+                    problems.push(PolicyProblem::InvalidChars {
+                        part: part.to_string(),
+                        chars: chars.spec.clone(),
+                    });
+                }
+            }
+        }
+        if let Some(casing) = self.casing {
+            for part in &key_parts {
+                if !casing.matches(part) {
+                    problems.push(PolicyProblem::Casing {
+                        part: part.to_string(),
+                        casing,
+                    });
+                }
+            }
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let policy = KeyPolicy {
+            max_depth: Some(3),
+            chars: Some(CharSet::new("a-zA-Z0-9_").unwrap()),
+            casing: Some(Casing::Camel),
+        };
+        assert!(policy.check("Checkout.Summary", "total").is_empty());
+        assert_eq!(
+            policy.check("Checkout.Summary", "total.net"),
+            vec![PolicyProblem::TooDeep {
+                depth: 4,
+                max_depth: 3
+            }]
+        );
+        assert_eq!(
+            policy.check("Cart", "Add-item"),
+            vec![
+                PolicyProblem::InvalidChars {
+                    part: "Add-item".to_string(),
+                    chars: "a-zA-Z0-9_".to_string()
+                },
+                PolicyProblem::Casing {
+                    part: "Add-item".to_string(),
+                    casing: Casing::Camel
+                },
+            ]
+        );
+        assert_eq!(
+            policy.check("Cart", "Title")[0].to_string(),
+            "'Title' is not camelCase"
+        );
+    }
+
+    #[test]
+    fn test_char_set() {
+        let chars = CharSet::new("a-z_-").unwrap();
+        assert!("snake_case-key".chars().all(|c| chars.contains(c)));
+        assert!(!chars.contains('A'));
+        assert!(CharSet::new("z-a").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use serde_json::{json, Map, Value};
+
+const SCHEMA_VERSION: &str = "http://json-schema.org/draft-07/schema#";
+
+/// A JSON Schema of the messages a catalog needs: every namespace is an object with exactly
+/// the extracted keys, and every message is a string
+pub fn generate(messages: &Map<String, Value>) -> Value {
+    let mut schema = object_schema(messages);
+    if let Value::Object(schema) = &mut schema {
+        schema.insert(
+            "$schema".to_string(),
+            Value::String(SCHEMA_VERSION.to_string()),
+        );
+    }
+    schema
+}
+
+fn object_schema(messages: &Map<String, Value>) -> Value {
+    let properties: Map<String, Value> = messages
+        .iter()
+        .map(|(key, value)| {
+            let schema = match value {
+                Value::Object(nested) => object_schema(nested),
+                _ => json!({ "type": "string" }),
+            };
+            (key.clone(), schema)
+        })
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": messages.keys().collect::<Vec<_>>(),
+        "additionalProperties": false,
+    })
+}
+
+/// A way messages don't match a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// The value has another type than the schema expects, e.g. a string instead of a namespace
+    TypeMismatch {
+        key: String,
+        expected: String,
+        found: String,
+    },
+    /// The key is not in the schema
+    UnexpectedKey { key: String },
+    /// The schema requires the key
+    MissingKey { key: String },
+}
+
+/// Validate messages against a schema generated with [`generate`]
+///
+/// Only the parts of JSON Schema used by generated schemas are supported: `type`,
+/// `properties`, `required` and `additionalProperties`
+pub fn validate(schema: &Value, messages: &Map<String, Value>) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    validate_object(schema, messages, "", &mut errors);
+    errors
+}
+
+fn validate_value(schema: &Value, value: &Value, key: &str, errors: &mut Vec<SchemaError>) {
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let found = type_name(value);
+        if found != expected {
+            errors.push(SchemaError::TypeMismatch {
+                key: key.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            });
+            return;
+        }
+    }
+    if let Value::Object(object) = value {
+        validate_object(schema, object, key, errors);
+    }
+}
+
+fn validate_object(
+    schema: &Value,
+    object: &Map<String, Value>,
+    key: &str,
+    errors: &mut Vec<SchemaError>,
+) {
+    let full_key = |name: &str| {
+        if key.is_empty() {
+            name.to_string()
+        } else {
+            format!("{key}.{name}")
+        }
+    };
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for name in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !object.contains_key(name) {
+            errors.push(SchemaError::MissingKey {
+                key: full_key(name),
+            });
+        }
+    }
+    let allows_additional = schema.get("additionalProperties") != Some(&Value::Bool(false));
+    for (name, value) in object {
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(schema) => validate_value(schema, value, &full_key(name), errors),
+            None if !allows_additional => errors.push(SchemaError::UnexpectedKey {
+                key: full_key(name),
+            }),
+            None => {}
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_generate() {
+        let schema = generate(&messages(json!({ "Cart": { "title": "Cart" } })));
+        assert_eq!(schema["$schema"], SCHEMA_VERSION);
+        assert_eq!(schema["required"], json!(["Cart"]));
+        assert_eq!(
+            schema["properties"]["Cart"],
+            json!({
+                "type": "object",
+                "properties": { "title": { "type": "string" } },
+                "required": ["title"],
+                "additionalProperties": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = generate(&messages(json!({
+            "Cart": { "title": "", "items": { "one": "" } },
+            "Nav": { "home": "" }
+        })));
+        let errors = validate(
+            &schema,
+            &messages(json!({
+                "Cart": { "title": { "short": "Cart" }, "items": "Items", "old": "Old" }
+            })),
+        );
+        assert_eq!(
+            errors,
+            vec![
+                SchemaError::MissingKey {
+                    key: "Nav".to_string()
+                },
+                SchemaError::TypeMismatch {
+                    key: "Cart.items".to_string(),
+                    expected: "object".to_string(),
+                    found: "string".to_string()
+                },
+                SchemaError::UnexpectedKey {
+                    key: "Cart.old".to_string()
+                },
+                SchemaError::TypeMismatch {
+                    key: "Cart.title".to_string(),
+                    expected: "string".to_string(),
+                    found: "object".to_string()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+use crate::order::OrderedValue;
+
+/// How the keys of a messages file are laid out
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Shape {
+    /// Nested objects, e.g. `{"Cart": {"title": "Cart"}}`
+    #[default]
+    Nested,
+    /// A single object with dot-delimited keys, e.g. `{"Cart.title": "Cart"}`
+    Flat,
+}
+
+impl Shape {
+    /// Flat if the keys contain dots and none of the values are objects with messages
+    pub fn detect(messages: &Map<String, Value>) -> Self {
+        let dotted = messages.keys().any(|key| key.contains('.'));
+        let nested = messages
+            .values()
+            .any(|value| value.as_object().is_some_and(|map| !map.is_empty()));
+        if dotted && !nested {
+            Self::Flat
+        } else {
+            Self::Nested
+        }
+    }
+}
+
+/// How every message is written
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EntryShape {
+    /// Just the value, e.g. `{"title": "Cart"}`
+    #[default]
+    Simple,
+    /// The value with the description for translators, like formatjs, e.g.
+    /// `{"title": {"message": "Cart", "description": "Heading of the cart page"}}`
+    Extended,
+}
+
+/// Whether an object is a message in the extended shape rather than a namespace
+fn is_entry(map: &Map<String, Value>) -> bool {
+    map.get("message").is_some_and(Value::is_string)
+        && map
+            .keys()
+            .all(|key| key == "message" || key == "description")
+}
+
+/// Replace the messages in the extended shape by their values and collect their descriptions
+/// by full key, e.g. `Cart.title`
+///
+/// The shape is extended if any message is
+pub fn simplify(
+    messages: Map<String, Value>,
+    prefix: &str,
+    descriptions: &mut Map<String, Value>,
+) -> (Map<String, Value>, EntryShape) {
+    let mut shape = EntryShape::Simple;
+    let mut simple = Map::new();
+    for (key, value) in messages {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        let value = match value {
+            Value::Object(mut map) if is_entry(&map) => {
+                shape = EntryShape::Extended;
+                if let Some(description) = map.remove("description") {
+                    descriptions.insert(full_key, description);
+                }
+                map.remove("message").unwrap_or_default()
+            }
+            Value::Object(map) => {
+                let (map, nested_shape) = simplify(map, &full_key, descriptions);
+                if nested_shape == EntryShape::Extended {
+                    shape = EntryShape::Extended;
+                }
+                Value::Object(map)
+            }
+            value => value,
+        };
+        simple.insert(key, value);
+    }
+    (simple, shape)
+}
+
+/// Write every message in the extended shape, with its description by full key if any
+///
+/// Empty objects are namespaces without messages, so they're kept
+pub fn extend(
+    value: OrderedValue,
+    prefix: &str,
+    descriptions: &Map<String, Value>,
+) -> OrderedValue {
+    match value {
+        OrderedValue::Object(entries) if !entries.is_empty() => OrderedValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let full_key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    (key, extend(value, &full_key, descriptions))
+                })
+                .collect(),
+        ),
+        OrderedValue::Object(entries) => OrderedValue::Object(entries),
+        value => {
+            let mut entry = vec![("message".to_string(), value)];
+            if let Some(description) = descriptions.get(prefix) {
+                entry.push((
+                    "description".to_string(),
+                    OrderedValue::Value(description.clone()),
+                ));
+            }
+            OrderedValue::Object(entry)
+        }
+    }
+}
+
+/// Join the keys of nested objects with dots, keeping their order
+///
+/// Empty objects are kept as values so that they survive a round trip
+pub fn flatten(value: OrderedValue) -> OrderedValue {
+    let OrderedValue::Object(entries) = value else {
+        return value;
+    };
+    let mut flat = Vec::new();
+    flatten_into(entries, "", &mut flat);
+    OrderedValue::Object(flat)
+}
+
+fn flatten_into(
+    entries: Vec<(String, OrderedValue)>,
+    prefix: &str,
+    flat: &mut Vec<(String, OrderedValue)>,
+) {
+    for (key, value) in entries {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            OrderedValue::Object(nested) if !nested.is_empty() => flatten_into(nested, &key, flat),
+            value => flat.push((key, value)),
+        }
+    }
+}
+
+/// Nest dot-delimited keys, e.g. `Cart.title` to `title` in `Cart`
+///
+/// Keys that would nest below a message instead of an object are left out, like next-intl
+/// can't resolve them either
+pub fn unflatten(messages: Map<String, Value>) -> Map<String, Value> {
+    let mut nested = Map::new();
+    for (key, value) in messages {
+        let value = match value {
+            Value::Object(map) => Value::Object(unflatten(map)),
+            value => value,
+        };
+        let parts: Vec<&str> = key.split('.').collect();
+        insert_path(&mut nested, &parts, value);
+    }
+    nested
+}
+
+/// Insert a value at a path of keys, unless a message is in the way
+pub fn insert_path(map: &mut Map<String, Value>, parts: &[&str], value: Value) {
+    let Some((&first, rest)) = parts.split_first() else {
+        return;
+    };
+    if !rest.is_empty() {
+        let entry = map
+            .entry(first)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(nested) = entry {
+            insert_path(nested, rest, value);
+        }
+        return;
+    }
+    match (map.get_mut(first), value) {
+        (Some(Value::Object(existing)), Value::Object(value)) => existing.extend(value),
+        (Some(_), _) => {}
+        (None, value) => {
+            map.insert(first.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect() {
+        let detect = |value: Value| Shape::detect(value.as_object().unwrap());
+        assert_eq!(
+            detect(json!({ "Cart.title": "Cart", "Nav": {} })),
+            Shape::Flat
+        );
+        assert_eq!(
+            detect(json!({ "Cart": { "title": "Cart" } })),
+            Shape::Nested
+        );
+        assert_eq!(detect(json!({ "title": "Title" })), Shape::Nested);
+        assert_eq!(detect(json!({})), Shape::Nested);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let value = OrderedValue::Object(vec![
+            (
+                "Cart".to_string(),
+                OrderedValue::Object(vec![
+                    ("title".to_string(), OrderedValue::Value(json!("Cart"))),
+                    (
+                        "checkout".to_string(),
+                        OrderedValue::Object(vec![(
+                            "pay".to_string(),
+                            OrderedValue::Value(json!("Pay")),
+                        )]),
+                    ),
+                ]),
+            ),
+            ("Nav".to_string(), OrderedValue::Object(Vec::new())),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&flatten(value.clone())).unwrap(),
+            r#"{"Cart.title":"Cart","Cart.checkout.pay":"Pay","Nav":{}}"#
+        );
+
+        let flat = flatten(value.clone()).into_value();
+        let Value::Object(flat) = flat else {
+            unreachable!()
+        };
+        assert_eq!(Value::Object(unflatten(flat)), value.into_value());
+    }
+
+    #[test]
+    fn test_entry_shape() {
+        let messages = json!({
+            "Cart": {
+                "title": { "message": "Cart", "description": "Heading" },
+                "empty": { "message": "Empty" },
+                "total": "Total"
+            },
+            "Nav": {}
+        });
+        let Value::Object(messages) = messages else {
+            unreachable!()
+        };
+        let mut descriptions = Map::new();
+        let (simple, entry_shape) = simplify(messages, "", &mut descriptions);
+        assert_eq!(entry_shape, EntryShape::Extended);
+        assert_eq!(
+            Value::Object(simple.clone()),
+            json!({ "Cart": { "title": "Cart", "empty": "Empty", "total": "Total" }, "Nav": {} })
+        );
+        assert_eq!(
+            Value::Object(descriptions.clone()),
+            json!({ "Cart.title": "Heading" })
+        );
+
+        let (_, entry_shape) = simplify(simple.clone(), "", &mut Map::new());
+        assert_eq!(entry_shape, EntryShape::Simple);
+
+        let value = OrderedValue::Object(vec![
+            (
+                "Cart".to_string(),
+                OrderedValue::Object(vec![
+                    ("title".to_string(), OrderedValue::Value(json!("Cart"))),
+                    ("total".to_string(), OrderedValue::Value(json!("Total"))),
+                ]),
+            ),
+            ("Nav".to_string(), OrderedValue::Object(Vec::new())),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&extend(value, "", &descriptions)).unwrap(),
+            r#"{"Cart":{"title":{"message":"Cart","description":"Heading"},"total":{"message":"Total"}},"Nav":{}}"#
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use clap::ValueEnum;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, BufRead, Write};
+
+/// What is known about a used message when it is merged
+#[derive(Debug, Clone, Copy)]
+pub struct MergeCandidate<'a> {
+    /// Full key of the message, e.g. `Cart.title`
+    pub key: &'a str,
+    /// The value in the catalog, if the message exists
+    pub existing: Option<&'a Value>,
+    /// The default message declared next to the usage in the code
+    pub extracted: Option<&'a str>,
+    /// Names of the values passed to the usages, e.g. `name` for `t("greeting", { name })`
+    pub placeholders: &'a BTreeSet<String>,
+}
+
+/// Decides how existing values and the messages extracted from the code are combined
+///
+/// A strategy may be asked about the same message more than once per run and should answer
+/// the same way every time
+pub trait MergeStrategy {
+    /// The value of a used message, `None` takes it from the default locale or scaffolds it
+    fn merge(&self, candidate: &MergeCandidate) -> Option<Value>;
+
+    /// Whether a message that is no longer used is removed from the catalog
+    fn remove(&self, _key: &str, _value: &Value) -> bool {
+        true
+    }
+}
+
+/// Keep the values in the catalog, default messages only fill new messages
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PreferSource;
+
+impl MergeStrategy for PreferSource {
+    fn merge(&self, candidate: &MergeCandidate) -> Option<Value> {
+        candidate.existing.cloned()
+    }
+}
+
+/// Overwrite the values in the catalog with the default messages in the code, e.g. for the
+/// catalog of the locale the code is written in
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PreferExtracted;
+
+impl MergeStrategy for PreferExtracted {
+    fn merge(&self, candidate: &MergeCandidate) -> Option<Value> {
+        candidate
+            .extracted
+            .map(|message| Value::String(message.to_string()))
+            .or_else(|| candidate.existing.cloned())
+    }
+}
+
+/// Reads a line of input into a string, like [`BufRead::read_line`]
+type ReadLine = dyn FnMut(&mut String) -> io::Result<usize>;
+
+/// Ask whether to take the default message when it differs from the value in the catalog, and
+/// whether to remove messages that are no longer used
+pub struct Interactive<W> {
+    read_line: RefCell<Box<ReadLine>>,
+    output: RefCell<W>,
+    /// Answers by question, so every question is asked once
+    answers: RefCell<HashMap<String, bool>>,
+}
+
+impl Interactive<io::Stderr> {
+    /// Ask on the terminal, stdin is only locked while reading an answer so the strategies of
+    /// multiple catalogs can share it
+    pub fn terminal() -> Self {
+        Self {
+            read_line: RefCell::new(Box::new(|line| io::stdin().read_line(line))),
+            output: RefCell::new(io::stderr()),
+            answers: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<W: Write> Interactive<W> {
+    pub fn new(mut input: impl BufRead + 'static, output: W) -> Self {
+        Self {
+            read_line: RefCell::new(Box::new(move |line| input.read_line(line))),
+            output: RefCell::new(output),
+            answers: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Ask a yes or no question, an empty or unreadable answer is the default
+    fn ask(&self, question: String, default: bool) -> bool {
+        if let Some(answer) = self.answers.borrow().get(&question) {
+            return *answer;
+        }
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        let mut line = String::new();
+        let asked = {
+            let mut output = self.output.borrow_mut();
+            write!(output, "{question} {hint} ").and_then(|_| output.flush())
+        };
+        let answer = asked
+            .and_then(|_| (self.read_line.borrow_mut())(&mut line))
+            .map(|_| match line.trim().to_lowercase().as_str() {
+                "y" | "yes" => true,
+                "n" | "no" => false,
+                _ => default,
+            })
+            .unwrap_or(default);
+        self.answers.borrow_mut().insert(question, answer);
+        answer
+    }
+}
+
+impl<W: Write> MergeStrategy for Interactive<W> {
+    fn merge(&self, candidate: &MergeCandidate) -> Option<Value> {
+        match (candidate.existing, candidate.extracted) {
+            (Some(Value::String(existing)), Some(extracted)) if existing != extracted => {
+                let question = format!(
+                    "Message '{}' is \"{existing}\" in the catalog and \"{extracted}\" in the \
+                     code, use the code's?",
+                    candidate.key
+                );
+                if self.ask(question, false) {
+                    Some(Value::String(extracted.to_string()))
+                } else {
+                    candidate.existing.cloned()
+                }
+            }
+            _ => candidate.existing.cloned(),
+        }
+    }
+
+    fn remove(&self, key: &str, value: &Value) -> bool {
+        self.ask(
+            format!("Message '{key}' ({value}) is no longer used, remove it?"),
+            true,
+        )
+    }
+}
+
+/// The merge strategies that can be chosen on the command line
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BuiltinStrategy {
+    /// Keep the values in the catalog
+    #[default]
+    PreferSource,
+    /// Overwrite the values in the catalog with the default messages in the code
+    PreferExtracted,
+    /// Ask on the terminal when the values differ and before removing unused messages
+    Interactive,
+}
+
+impl BuiltinStrategy {
+    pub fn strategy(self) -> Box<dyn MergeStrategy> {
+        match self {
+            Self::PreferSource => Box::new(PreferSource),
+            Self::PreferExtracted => Box::new(PreferExtracted),
+            Self::Interactive => Box::new(Interactive::terminal()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_interactive() {
+        let placeholders = BTreeSet::new();
+        let existing = json!("Old");
+        let candidate = MergeCandidate {
+            key: "Cart.title",
+            existing: Some(&existing),
+            extracted: Some("New"),
+            placeholders: &placeholders,
+        };
+        let mut output = Vec::new();
+        let strategy = Interactive::new("y\nn\n".as_bytes(), &mut output);
+
+        assert_eq!(strategy.merge(&candidate), Some(json!("New")));
+        // The answer is remembered
+        assert_eq!(strategy.merge(&candidate), Some(json!("New")));
+        assert!(!strategy.remove("Cart.old", &existing));
+        // No more input takes the default
+        assert!(strategy.remove("Cart.older", &existing));
+        drop(strategy);
+        assert_eq!(
+            String::from_utf8(output).unwrap().matches("[Y/n]").count(),
+            2
+        );
+
+        assert_eq!(PreferSource.merge(&candidate), Some(json!("Old")));
+        assert_eq!(PreferExtracted.merge(&candidate), Some(json!("New")));
+    }
+}
@@ -0,0 +1,64 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Today's date in UTC as `YYYY-MM-DD`
+pub fn today() -> String {
+    date(now_seconds() / SECONDS_PER_DAY)
+}
+
+/// The current time in UTC as an RFC 3339 timestamp, e.g. `2024-05-01T09:30:00Z`
+pub fn now() -> String {
+    timestamp(now_seconds())
+}
+
+fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn timestamp(seconds: u64) -> String {
+    let time = seconds % SECONDS_PER_DAY;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        date(seconds / SECONDS_PER_DAY),
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// The date of a number of days since 1970-01-01, see
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn date(days: u64) -> String {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(19_844), "2024-05-01");
+        assert_eq!(timestamp(1_714_555_800), "2024-05-01T09:30:00Z");
+    }
+}
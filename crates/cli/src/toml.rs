@@ -0,0 +1,246 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::{Number, Value};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table};
+
+use crate::order::OrderedValue;
+
+/// Parse a TOML message catalog
+///
+/// Dates have no JSON value and are an error, catalogs don't use them
+pub fn parse(content: &str) -> Result<OrderedValue> {
+    let document: DocumentMut = content.parse()?;
+    table_entries(document.as_table())
+}
+
+/// Serialize a message catalog to TOML
+///
+/// The values of a table come before its nested tables, as TOML requires. TOML has no null,
+/// so null values are left out
+pub fn to_string(value: &OrderedValue) -> String {
+    let mut document = DocumentMut::new();
+    if let OrderedValue::Object(entries) = value {
+        fill_table(document.as_table_mut(), entries);
+    }
+    document.to_string()
+}
+
+fn table_entries(table: &Table) -> Result<OrderedValue> {
+    let mut entries = Vec::new();
+    for (key, item) in table.iter() {
+        let value = match item {
+            Item::None => continue,
+            Item::Value(value) => ordered(value)?,
+            Item::Table(table) => table_entries(table)?,
+            Item::ArrayOfTables(tables) => OrderedValue::Value(Value::Array(
+                tables
+                    .iter()
+                    .map(|table| table_entries(table).map(OrderedValue::into_value))
+                    .collect::<Result<_>>()?,
+            )),
+        };
+        entries.push((key.to_string(), value));
+    }
+    Ok(OrderedValue::Object(entries))
+}
+
+/// A TOML value, with the order of the keys of inline tables
+fn ordered(value: &toml_edit::Value) -> Result<OrderedValue> {
+    let toml_edit::Value::InlineTable(table) = value else {
+        return Ok(OrderedValue::Value(json(value)?));
+    };
+    let mut entries = Vec::new();
+    for (key, value) in table.iter() {
+        entries.push((key.to_string(), ordered(value)?));
+    }
+    Ok(OrderedValue::Object(entries))
+}
+
+fn json(value: &toml_edit::Value) -> Result<Value> {
+    Ok(match value {
+        toml_edit::Value::String(text) => Value::String(text.value().clone()),
+        toml_edit::Value::Integer(number) => Value::Number((*number.value()).into()),
+        toml_edit::Value::Float(number) => Number::from_f64(*number.value())
+            .map(Value::Number)
+            .ok_or_else(|| anyhow!("{} has no JSON value", number.value()))?,
+        toml_edit::Value::Boolean(value) => Value::Bool(*value.value()),
+        toml_edit::Value::Datetime(date) => bail!("Dates are not supported, found {}", date),
+        toml_edit::Value::Array(items) => {
+            Value::Array(items.iter().map(json).collect::<Result<_>>()?)
+        }
+        toml_edit::Value::InlineTable(_) => ordered(value)?.into_value(),
+    })
+}
+
+fn fill_table(table: &mut Table, entries: &[(String, OrderedValue)]) {
+    for (key, value) in entries {
+        match value {
+            OrderedValue::Object(entries) => {
+                let mut nested = Table::new();
+                // Tables with only nested tables are declared by the headers of those
+                nested.set_implicit(!entries.is_empty());
+                fill_table(&mut nested, entries);
+                table.insert(key, Item::Table(nested));
+            }
+            OrderedValue::Value(Value::Null) => {}
+            OrderedValue::Value(value) => {
+                table.insert(key, Item::Value(toml_value(value)));
+            }
+        }
+    }
+}
+
+/// A JSON value as TOML, which the callers leave out if it's null
+fn toml_value(value: &Value) -> toml_edit::Value {
+    match value {
+        Value::String(text) => text.as_str().into(),
+        Value::Bool(value) => (*value).into(),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => integer.into(),
+            None => number.as_f64().unwrap_or_default().into(),
+        },
+        Value::Array(items) => items
+            .iter()
+            .filter(|item| !item.is_null())
+            .map(toml_value)
+            .collect::<Array>()
+            .into(),
+        Value::Object(map) => map
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key.as_str(), toml_value(value)))
+            .collect::<InlineTable>()
+            .into(),
+        Value::Null => "".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse() {
+        let value = parse(
+            r#"# Shop messages
+title = "Shop"
+
+[Cart]
+title = "Your cart" # shown as heading
+"empty state" = 'Nothing in C:\cart'
+count = 3
+items = ["one", "two",]
+nested.deep = true
+description = """
+First line
+Second line"""
+
+[Cart.checkout]
+pay = "Pay \u00e9"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            value.clone().into_value(),
+            json!({
+                "title": "Shop",
+                "Cart": {
+                    "title": "Your cart",
+                    "empty state": "Nothing in C:\\cart",
+                    "count": 3,
+                    "items": ["one", "two"],
+                    "nested": { "deep": true },
+                    "description": "First line\nSecond line",
+                    "checkout": { "pay": "Pay é" }
+                }
+            })
+        );
+        assert_eq!(
+            value.key_order().get("Cart").unwrap().position("count"),
+            Some(2)
+        );
+        assert!(parse("[Cart]\ntitle = \"a\"\ntitle = \"b\"\n").is_err());
+        assert!(parse("date = 1979-05-27\n").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = OrderedValue::Object(vec![
+            (
+                "Cart".to_string(),
+                OrderedValue::Object(vec![
+                    (
+                        "checkout".to_string(),
+                        OrderedValue::Object(vec![(
+                            "pay".to_string(),
+                            OrderedValue::Value(json!("Pay")),
+                        )]),
+                    ),
+                    (
+                        "title".to_string(),
+                        OrderedValue::Value(json!("Your \"cart\"")),
+                    ),
+                    (
+                        "empty state".to_string(),
+                        OrderedValue::Value(json!("a\nb")),
+                    ),
+                ]),
+            ),
+            (
+                "Nav".to_string(),
+                OrderedValue::Object(vec![(
+                    "links".to_string(),
+                    OrderedValue::Object(vec![(
+                        "home".to_string(),
+                        OrderedValue::Value(json!("Home")),
+                    )]),
+                )]),
+            ),
+        ]);
+        let toml = to_string(&value);
+        assert_eq!(
+            toml,
+            "[Cart]\ntitle = 'Your \"cart\"'\n\"empty state\" = \"\"\"\na\nb\"\"\"\n\n[Cart.checkout]\npay = \"Pay\"\n\n[Nav.links]\nhome = \"Home\"\n"
+        );
+        assert_eq!(parse(&toml).unwrap().into_value(), value.into_value());
+    }
+
+    #[test]
+    fn test_quoted_keys_and_escapes() {
+        let value = OrderedValue::Object(vec![(
+            "Cart.v2".to_string(),
+            OrderedValue::Object(vec![
+                (
+                    "empty state".to_string(),
+                    OrderedValue::Value(json!("Tab\there, \"quoted\" and C:\\cart")),
+                ),
+                (
+                    "größe".to_string(),
+                    OrderedValue::Value(json!("Bell \u{7} and delete \u{7f}")),
+                ),
+                ("".to_string(), OrderedValue::Value(json!("é 🛒"))),
+            ]),
+        )]);
+        let toml = to_string(&value);
+        assert_eq!(
+            toml,
+            "[\"Cart.v2\"]\n\"empty state\" = 'Tab\there, \"quoted\" and C:\\cart'\n\"größe\" = \"Bell \\u0007 and delete \\u007F\"\n\"\" = \"é 🛒\"\n"
+        );
+        assert_eq!(parse(&toml).unwrap(), value);
+
+        let value = parse(
+            "a = \"\"\"Say \"hi\"\"\"\"\nb = '''It's ''quoted'''''\nc = { nested.deep = true, \"x.y\" = 1 }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            value.into_value(),
+            json!({
+                "a": "Say \"hi\"",
+                "b": "It's ''quoted''",
+                "c": { "nested": { "deep": true }, "x.y": 1 }
+            })
+        );
+        assert!(parse("c = { a = 1, a = 2 }\n").is_err());
+    }
+}
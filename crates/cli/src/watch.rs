@@ -1,47 +1,69 @@
 use anyhow::{Context, Result};
-use glob::Pattern;
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::{Path, PathBuf};
-use tracing::{debug, error, info};
+use std::path::PathBuf;
+use tracing::{debug, error, info, warn};
 
-use crate::messages::MessageHandler;
-use next_intl_resolver::extract_translations;
+use crate::catalogs::Catalogs;
+use crate::diagnostics::{
+    warn_catalog_usages, warn_dynamic_keys, warn_hardcoded_strings, warn_import_misuses,
+    warn_parse_errors, warn_unresolved_namespaces,
+};
+use crate::files::{find_files, FilePattern};
+use next_intl_resolver::Extractor;
 
 fn process_file_change(
     path: &PathBuf,
-    message_handler: &mut MessageHandler,
-    output_path: &Path,
+    catalogs: &mut Catalogs,
+    extractor: &Extractor,
 ) -> Result<()> {
     info!("Processing changed file: {:?}", path);
 
-    let translations = extract_translations(path).context("Failed to extract translations")?;
+    let extraction = extractor
+        .extract(path)
+        .context("Failed to extract translations")?;
 
-    message_handler.add_extracted_messages(translations, path.to_string_lossy().to_string());
-    message_handler.write_merged_messages(output_path)?;
+    // Files are often saved halfway through an edit, keep their messages until they parse again
+    if !extraction.parse_errors.is_empty() {
+        warn_parse_errors(path, &extraction.parse_errors);
+        warn!(
+            "Keeping the previous messages of {:?} until it parses",
+            path
+        );
+        return Ok(());
+    }
+
+    warn_dynamic_keys(path, &extraction.dynamic_keys);
+    warn_unresolved_namespaces(path, &extraction.unresolved_namespaces);
+    warn_hardcoded_strings(path, &extraction.hardcoded_strings);
+    warn_catalog_usages(path, &extraction.catalog_usages);
+    warn_import_misuses(path, &extraction.import_misuses);
+
+    catalogs.add_extraction(extraction, path.to_string_lossy().to_string());
+    catalogs.write_merged_messages()?;
     info!("Successfully updated translations from {:?}", path);
     Ok(())
 }
 
-fn process_file_removal(
-    path: &PathBuf,
-    message_handler: &mut MessageHandler,
-    output_path: &Path,
-) -> Result<()> {
+fn process_file_removal(path: &PathBuf, catalogs: &mut Catalogs) -> Result<()> {
     info!("Processing removed file: {:?}", path);
 
-    message_handler.remove_messages_for_file(path.to_string_lossy().as_ref());
-    message_handler.write_merged_messages(output_path)?;
+    catalogs.remove_messages_for_file(path.to_string_lossy().as_ref());
+    catalogs.write_merged_messages()?;
     info!("Successfully removed translations from {:?}", path);
     Ok(())
 }
 
-/// Watch for file changes and update the message handler with new translations
+/// Watch for file changes and update the message handler with new translations, files that
+/// match an ignored pattern are left out
 pub fn watch(
     pattern: &str,
-    output_path: &Path,
-    message_handler: &mut MessageHandler,
+    ignored: &[String],
+    catalogs: &mut Catalogs,
+    extractor: &Extractor,
 ) -> Result<()> {
-    let glob_pattern = Pattern::new(pattern).context("Failed to create glob pattern")?;
+    let glob_pattern = FilePattern::new(pattern)
+        .and_then(|pattern| pattern.with_ignored(ignored))
+        .context("Failed to create glob pattern")?;
     debug!("Created glob pattern: {:?}", glob_pattern);
 
     let (tx, rx) = std::sync::mpsc::channel();
@@ -65,15 +87,13 @@ pub fn watch(
     info!("Started watching for file changes in {:?}...", current_dir);
 
     // Process initial files that match the pattern
-    for entry in glob::glob(pattern)?.flatten() {
-        if entry.is_file() {
-            debug!("Processing initial file: {:?}", entry);
-            process_file_change(&entry, message_handler, output_path)?;
-        }
+    for entry in find_files(pattern, ignored)? {
+        debug!("Processing initial file: {:?}", entry);
+        process_file_change(&entry, catalogs, extractor)?;
     }
 
     // Write initial state
-    message_handler.write_merged_messages(output_path)?;
+    catalogs.write_merged_messages()?;
 
     for Event { kind, paths, .. } in rx.into_iter().flatten() {
         for path in paths {
@@ -88,14 +108,14 @@ pub fn watch(
                 EventKind::Create(_) | EventKind::Modify(_) => {
                     if path.is_file() {
                         debug!("Processing changed file: {:?}", path);
-                        process_file_change(&path, message_handler, output_path)
+                        process_file_change(&path, catalogs, extractor)
                     } else {
                         Ok(())
                     }
                 }
                 EventKind::Remove(_) => {
                     debug!("Processing removed file: {:?}", path);
-                    process_file_removal(&path, message_handler, output_path)
+                    process_file_removal(&path, catalogs)
                 }
                 _ => Ok(()),
             };
@@ -112,24 +132,27 @@ pub fn watch(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::format::CatalogFormat;
     use std::fs;
     use tempfile::TempDir;
 
-    fn setup_test_env() -> Result<(TempDir, PathBuf, MessageHandler)> {
+    fn setup_test_env() -> Result<(TempDir, PathBuf, Catalogs)> {
         let temp_dir = TempDir::new()?;
         let output_path = temp_dir.path().join("messages.json");
 
         // Create initial messages.json
         fs::write(&output_path, "{}")?;
 
-        let message_handler = MessageHandler::new(&output_path)?;
+        let catalogs = Catalogs::open(&output_path, &[], CatalogFormat::Json, |handler, _| {
+            Ok(handler)
+        })?;
 
-        Ok((temp_dir, output_path, message_handler))
+        Ok((temp_dir, output_path, catalogs))
     }
 
     #[test]
     fn test_watch_file_creation() -> Result<()> {
-        let (temp_dir, output_path, mut message_handler) = setup_test_env()?;
+        let (temp_dir, output_path, mut catalogs) = setup_test_env()?;
 
         // Create a new file
         let test_file = temp_dir.path().join("test.tsx");
@@ -147,7 +170,7 @@ mod tests {
         )?;
 
         // Process the file
-        process_file_change(&test_file, &mut message_handler, &output_path)?;
+        process_file_change(&test_file, &mut catalogs, &Extractor::new())?;
 
         // Verify the messages were extracted
         let messages = fs::read_to_string(&output_path)?;
@@ -166,7 +189,7 @@ mod tests {
 
     #[test]
     fn test_watch_file_modification() -> Result<()> {
-        let (temp_dir, output_path, mut message_handler) = setup_test_env()?;
+        let (temp_dir, output_path, mut catalogs) = setup_test_env()?;
 
         // Create initial file
         let test_file = temp_dir.path().join("test.tsx");
@@ -184,7 +207,7 @@ mod tests {
         )?;
 
         // Process initial file
-        process_file_change(&test_file, &mut message_handler, &output_path)?;
+        process_file_change(&test_file, &mut catalogs, &Extractor::new())?;
 
         // Verify initial messages
         let messages = fs::read_to_string(&output_path)?;
@@ -213,7 +236,7 @@ mod tests {
         )?;
 
         // Process modified file
-        process_file_change(&test_file, &mut message_handler, &output_path)?;
+        process_file_change(&test_file, &mut catalogs, &Extractor::new())?;
 
         // Verify the messages were updated
         let messages = fs::read_to_string(&output_path)?;
@@ -236,7 +259,7 @@ mod tests {
 
     #[test]
     fn test_watch_file_deletion() -> Result<()> {
-        let (temp_dir, output_path, mut message_handler) = setup_test_env()?;
+        let (temp_dir, output_path, mut catalogs) = setup_test_env()?;
 
         // Create initial file
         let test_file = temp_dir.path().join("test.tsx");
@@ -254,7 +277,7 @@ mod tests {
         )?;
 
         // Process initial file
-        process_file_change(&test_file, &mut message_handler, &output_path)?;
+        process_file_change(&test_file, &mut catalogs, &Extractor::new())?;
 
         // Verify initial messages
         let messages = fs::read_to_string(&output_path)?;
@@ -273,7 +296,7 @@ mod tests {
         fs::remove_file(&test_file)?;
 
         // Process file removal
-        process_file_removal(&test_file, &mut message_handler, &output_path)?;
+        process_file_removal(&test_file, &mut catalogs)?;
 
         // Verify the messages were removed
         let messages = fs::read_to_string(&output_path)?;
@@ -292,7 +315,7 @@ mod tests {
 
     #[test]
     fn test_watch_pattern_matching() -> Result<()> {
-        let (temp_dir, output_path, mut message_handler) = setup_test_env()?;
+        let (temp_dir, output_path, mut catalogs) = setup_test_env()?;
 
         // Create a .tsx file (should be processed)
         let tsx_file = temp_dir.path().join("test.tsx");
@@ -323,10 +346,10 @@ mod tests {
         )?;
 
         // Process both files
-        let pattern = Pattern::new("**/*.tsx")?;
+        let pattern = glob::Pattern::new("**/*.tsx")?;
         for file in [&tsx_file, &ts_file] {
             if pattern.matches_path(file) {
-                process_file_change(file, &mut message_handler, &output_path)?;
+                process_file_change(file, &mut catalogs, &Extractor::new())?;
             }
         }
 
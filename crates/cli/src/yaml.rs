@@ -0,0 +1,653 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+
+use crate::order::OrderedValue;
+
+/// Words that YAML 1.1 or 1.2 reads as something other than a string when they're not
+/// quoted, in any case, e.g. `NULL` or `True`
+const RESERVED: [&str; 11] = [
+    "true", "false", "null", "~", "yes", "no", "on", "off", "y", "n", "<<",
+];
+
+/// Parse a YAML message catalog
+///
+/// Only the subset catalogs use is supported: nested block mappings with plain, quoted and
+/// block (`|`, `>`) scalars, sequences of scalars and of mappings, and JSON-like flow
+/// collections. Plain scalars may continue on the lines below, quoted ones can't. Anything
+/// else, e.g. anchors, aliases, tags or several documents, is an error rather than read
+/// differently than YAML does
+pub fn parse(content: &str) -> Result<OrderedValue> {
+    let lines: Vec<&str> = content.lines().collect();
+    check_single_document(&lines)?;
+    let mut parser = YamlParser {
+        lines,
+        pos: 0,
+        item: None,
+    };
+    let Some((indent, content)) = parser.peek() else {
+        return Ok(OrderedValue::Object(Vec::new()));
+    };
+    if content.starts_with(['{', '[']) {
+        parser.pos += 1;
+        let value = parse_scalar(content, parser.pos)?;
+        if parser.peek().is_some() {
+            bail!("Unexpected content after line {}", parser.pos);
+        }
+        return Ok(value);
+    }
+    let entries = parser.parse_mapping(indent)?;
+    if parser.peek().is_some() {
+        bail!("Unexpected indentation on line {}", parser.pos + 1);
+    }
+    Ok(OrderedValue::Object(entries))
+}
+
+/// Serialize a message catalog to YAML, indenting nested mappings with `indent`
+pub fn to_string(value: &OrderedValue, indent: &str) -> String {
+    let mut output = String::new();
+    match value {
+        OrderedValue::Object(entries) if !entries.is_empty() => {
+            write_mapping(&mut output, entries, indent, 0)
+        }
+        value => {
+            output.push_str(&scalar(value));
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Fail on the start of a second document, which would otherwise be merged into the first
+fn check_single_document(lines: &[&str]) -> Result<()> {
+    let (mut has_content, mut ended) = (false, false);
+    for (index, line) in lines.iter().enumerate() {
+        let line = line.trim_end();
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let is_start = line == "---" || line.starts_with("--- ");
+        if ended || (is_start && has_content) {
+            bail!(
+                "Only a single document is supported, another one starts on line {}",
+                index + 1
+            );
+        }
+        ended = line == "...";
+        has_content = line != "---";
+    }
+    Ok(())
+}
+
+/// Fail on YAML node properties and complex keys, e.g. `&anchor`, `*alias`, `!tag` or `? key`
+fn check_supported(content: &str, line: usize) -> Result<()> {
+    let feature = match content.chars().next() {
+        Some('&') => "Anchors",
+        Some('*') => "Aliases",
+        Some('!') => "Tags",
+        Some('?') if content == "?" || content.starts_with("? ") => "Complex keys",
+        _ => return Ok(()),
+    };
+    bail!("{feature} aren't supported, on line {line}")
+}
+
+struct YamlParser<'s> {
+    lines: Vec<&'s str>,
+    pos: usize,
+    /// The line and column of a mapping in a sequence, e.g. `- title: Cart`, which starts
+    /// after the `- `
+    item: Option<(usize, usize)>,
+}
+
+impl<'s> YamlParser<'s> {
+    /// The indentation and content of the next line that isn't empty or a comment
+    fn peek(&mut self) -> Option<(usize, &'s str)> {
+        while let Some(line) = self.lines.get(self.pos) {
+            let start = match self.item {
+                Some((pos, column)) if pos == self.pos => column,
+                _ => 0,
+            };
+            let content = line[start..].trim_start_matches(' ');
+            let trimmed = content.trim_end();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+                self.pos += 1;
+                continue;
+            }
+            if trimmed == "..." {
+                self.pos = self.lines.len();
+                return None;
+            }
+            return Some((line.len() - content.len(), trimmed));
+        }
+        None
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<Vec<(String, OrderedValue)>> {
+        let mut entries = Vec::new();
+        while let Some((line_indent, content)) = self.peek() {
+            if line_indent < indent {
+                break;
+            }
+            let line = self.pos + 1;
+            if line_indent > indent {
+                bail!("Unexpected indentation on line {line}");
+            }
+            if content.starts_with('-') {
+                bail!("Expected a key on line {line}");
+            }
+            check_supported(content, line)?;
+            let (key, rest) = split_key(content, line)?;
+            self.pos += 1;
+
+            let rest = strip_comment(rest);
+            check_supported(rest, line)?;
+            let value = if rest.is_empty() {
+                match self.peek() {
+                    Some((next, content)) if next >= indent && is_sequence_item(content) => {
+                        OrderedValue::Value(self.parse_sequence(next)?)
+                    }
+                    Some((next, _)) if next > indent => {
+                        OrderedValue::Object(self.parse_mapping(next)?)
+                    }
+                    _ => OrderedValue::Value(Value::Null),
+                }
+            } else if rest.starts_with(['|', '>']) {
+                OrderedValue::Value(Value::String(self.parse_block_scalar(rest, indent)))
+            } else if let Some(text) = self.parse_plain_lines(rest, indent) {
+                OrderedValue::Value(Value::String(text))
+            } else {
+                parse_scalar(rest, line)?
+            };
+            entries.push((key, value));
+        }
+        Ok(entries)
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<Value> {
+        let mut items = Vec::new();
+        while let Some((line_indent, content)) = self.peek() {
+            if line_indent != indent || !is_sequence_item(content) {
+                break;
+            }
+            let line = self.pos + 1;
+            let item = strip_comment(content[1..].trim_start());
+            check_supported(item, line)?;
+            if split_key(item, line).is_ok() && !item.starts_with(['{', '[']) {
+                // The other keys of the mapping are indented like its first one
+                let column = line_indent + content.len() - content[1..].trim_start().len();
+                self.item = Some((self.pos, column));
+                items.push(OrderedValue::Object(self.parse_mapping(column)?).into_value());
+                continue;
+            }
+            if item.is_empty() {
+                // A mapping on the lines below a `-` of its own
+                let pos = self.pos;
+                self.pos += 1;
+                match self.peek() {
+                    Some((next, content)) if next > indent && !is_sequence_item(content) => {
+                        items.push(OrderedValue::Object(self.parse_mapping(next)?).into_value());
+                        continue;
+                    }
+                    _ => self.pos = pos,
+                }
+            }
+            match parse_scalar(item, line)? {
+                OrderedValue::Value(value) => items.push(value),
+                OrderedValue::Object(_) => items.push(Value::Object(Default::default())),
+            }
+            self.pos += 1;
+        }
+        Ok(Value::Array(items))
+    }
+
+    /// Read the lines below a plain scalar that it continues on, if any, folded like YAML does:
+    /// lines are joined with a space and empty lines are kept as line breaks
+    fn parse_plain_lines(&mut self, first: &str, parent_indent: usize) -> Option<String> {
+        if first.starts_with(['"', '\'', '{', '[']) {
+            return None;
+        }
+        let mut text = first.to_string();
+        let mut breaks = 0;
+        let mut continued = false;
+        for (pos, line) in self.lines.iter().enumerate().skip(self.pos) {
+            let content = line.trim();
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            if content.is_empty() {
+                breaks += 1;
+                continue;
+            }
+            // A key is an error of its own, a comment ends the scalar
+            if indent <= parent_indent || content.starts_with('#') || split_key(content, 0).is_ok()
+            {
+                break;
+            }
+            match breaks {
+                0 => text.push(' '),
+                breaks => text.push_str(&"\n".repeat(breaks)),
+            }
+            text.push_str(strip_comment(content));
+            breaks = 0;
+            continued = true;
+            self.pos = pos + 1;
+        }
+        continued.then_some(text)
+    }
+
+    /// Read a `|` or `>` block scalar whose header is on the current line
+    fn parse_block_scalar(&mut self, header: &str, parent_indent: usize) -> String {
+        let folded = header.starts_with('>');
+        let mut lines = Vec::new();
+        let mut block_indent = None;
+        while let Some(line) = self.lines.get(self.pos) {
+            let content = line.trim_start_matches(' ');
+            let indent = line.len() - content.len();
+            if content.trim().is_empty() {
+                lines.push("");
+            } else if indent > parent_indent {
+                let block_indent = *block_indent.get_or_insert(indent);
+                lines.push(&line[block_indent.min(indent)..]);
+            } else {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        let trailing_empty = lines
+            .iter()
+            .rev()
+            .take_while(|line| line.is_empty())
+            .count();
+        lines.truncate(lines.len() - trailing_empty);
+        let mut text = if folded {
+            let mut text = String::new();
+            for (index, line) in lines.iter().enumerate() {
+                if index > 0 {
+                    let previous = lines[index - 1];
+                    text.push(if previous.is_empty() || line.is_empty() {
+                        '\n'
+                    } else {
+                        ' '
+                    });
+                }
+                text.push_str(line);
+            }
+            text
+        } else {
+            lines.join("\n")
+        };
+
+        if header.contains('+') {
+            text.push_str(&"\n".repeat(trailing_empty + 1));
+        } else if !header.contains('-') && !text.is_empty() {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn is_sequence_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Split `key: value` into the key and the rest of the line
+fn split_key(content: &str, line: usize) -> Result<(String, &str)> {
+    let (key, rest) = if content.starts_with(['"', '\'']) {
+        let (key, rest) = read_quoted(content, line)?;
+        let rest = rest.trim_start();
+        let rest = rest
+            .strip_prefix(':')
+            .ok_or_else(|| anyhow!("Expected `:` after the key on line {line}"))?;
+        (key, rest)
+    } else {
+        let end = content
+            .match_indices(':')
+            .map(|(index, _)| index)
+            .find(|&index| matches!(content[index + 1..].chars().next(), None | Some(' ')))
+            .ok_or_else(|| anyhow!("Expected `key: value` on line {line}"))?;
+        (content[..end].trim_end().to_string(), &content[end + 1..])
+    };
+    Ok((key, rest.trim()))
+}
+
+/// Read a quoted string at the start of `content`, returning it with the rest of the content
+fn read_quoted(content: &str, line: usize) -> Result<(String, &str)> {
+    if let Some(single) = content.strip_prefix('\'') {
+        let mut value = String::new();
+        let mut chars = single.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if c == '\'' {
+                if chars.next_if(|&(_, c)| c == '\'').is_some() {
+                    value.push('\'');
+                    continue;
+                }
+                return Ok((value, &single[index + 1..]));
+            }
+            value.push(c);
+        }
+    } else {
+        let mut escaped = false;
+        for (index, c) in content.char_indices().skip(1) {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    let value = serde_json::from_str(&content[..=index])
+                        .map_err(|e| anyhow!("Invalid string on line {line}: {e}"))?;
+                    return Ok((value, &content[index + 1..]));
+                }
+                _ => {}
+            }
+        }
+    }
+    bail!("Unterminated string on line {line}, quoted strings can't continue on the next line")
+}
+
+/// Remove a ` # comment` from the end of a value
+fn strip_comment(content: &str) -> &str {
+    if content.starts_with('#') {
+        return "";
+    }
+    if content.starts_with(['"', '\'']) {
+        return content;
+    }
+    match content.find(" #") {
+        Some(index) => content[..index].trim_end(),
+        None => content,
+    }
+}
+
+fn parse_scalar(content: &str, line: usize) -> Result<OrderedValue> {
+    if content.starts_with(['"', '\'']) {
+        let (value, rest) = read_quoted(content, line)?;
+        if !strip_comment(rest.trim()).is_empty() {
+            bail!("Unexpected content after the string on line {line}");
+        }
+        return Ok(OrderedValue::Value(Value::String(value)));
+    }
+    if content == "{}" {
+        return Ok(OrderedValue::Object(Vec::new()));
+    }
+    if content.starts_with(['{', '[']) {
+        let value = serde_json::from_str(content).map_err(|_| {
+            anyhow!("Only JSON-like flow collections are supported, on line {line}")
+        })?;
+        return Ok(OrderedValue::Value(value));
+    }
+    let value = match content {
+        "null" | "Null" | "NULL" | "~" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        _ => match content.parse::<serde_json::Number>() {
+            Ok(number) => Value::Number(number),
+            Err(_) => Value::String(content.to_string()),
+        },
+    };
+    Ok(OrderedValue::Value(value))
+}
+
+fn write_mapping(
+    output: &mut String,
+    entries: &[(String, OrderedValue)],
+    indent: &str,
+    depth: usize,
+) {
+    let prefix = indent.repeat(depth);
+    for (key, value) in entries {
+        output.push_str(&prefix);
+        output.push_str(&string(key));
+        output.push(':');
+        match value {
+            OrderedValue::Object(entries) if !entries.is_empty() => {
+                output.push('\n');
+                write_mapping(output, entries, indent, depth + 1);
+            }
+            OrderedValue::Value(Value::Array(items)) if !items.is_empty() => {
+                for item in items {
+                    output.push('\n');
+                    output.push_str(&prefix);
+                    output.push_str(indent);
+                    output.push_str("- ");
+                    output.push_str(&scalar(&OrderedValue::Value(item.clone())));
+                }
+                output.push('\n');
+            }
+            value => {
+                output.push(' ');
+                output.push_str(&scalar(value));
+                output.push('\n');
+            }
+        }
+    }
+}
+
+fn scalar(value: &OrderedValue) -> String {
+    match value {
+        OrderedValue::Object(_) => "{}".to_string(),
+        OrderedValue::Value(Value::String(text)) => string(text),
+        // JSON is valid YAML for the rest, including nested collections in sequences
+        OrderedValue::Value(value) => value.to_string(),
+    }
+}
+
+/// A string as a plain scalar if YAML reads it back unchanged, double quoted otherwise
+fn string(text: &str) -> String {
+    let needs_quotes = text.is_empty()
+        || text != text.trim()
+        || text.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        || text.contains(": ")
+        || text.contains(" #")
+        || text.ends_with(':')
+        || text.chars().any(char::is_control)
+        || RESERVED.contains(&text.to_lowercase().as_str())
+        || text.parse::<f64>().is_ok()
+        || is_number_like(text);
+    if needs_quotes {
+        Value::String(text.to_string()).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Whether YAML 1.1 or 1.2 may read a plain scalar as a number or a date, e.g. `1_000`,
+/// `0x1F`, `0o17`, `1:30`, `.inf` or `2024-01-31`
+///
+/// Loosely, so that anything the int, float and timestamp types of either version match is
+/// quoted, e.g. also `1-2`
+fn is_number_like(text: &str) -> bool {
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if matches!(unsigned.to_lowercase().as_str(), ".inf" | ".nan") {
+        return true;
+    }
+    unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && unsigned
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || " _.:+-xXoOtTzZ".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse() {
+        let value = parse(
+            r#"# Shop messages
+Cart:
+  title: Your cart # shown as heading
+  empty: "Nothing here: yet"
+  'quoted key': 'It''s {count}'
+  items:
+    - one
+    - "two"
+  nested:
+    deep: 1
+  description: |
+    First line
+    Second line
+  folded: >-
+    Folded
+    text
+Nav: {}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            value.clone().into_value(),
+            json!({
+                "Cart": {
+                    "title": "Your cart",
+                    "empty": "Nothing here: yet",
+                    "quoted key": "It's {count}",
+                    "items": ["one", "two"],
+                    "nested": { "deep": 1 },
+                    "description": "First line\nSecond line\n",
+                    "folded": "Folded text"
+                },
+                "Nav": {}
+            })
+        );
+        assert_eq!(
+            value.key_order().get("Cart").unwrap().position("title"),
+            Some(0)
+        );
+        assert!(parse("Cart:\n  title: a\n    deep: b\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_multi_line_and_sequences() {
+        let value = parse(
+            r#"Cart:
+  notice: Orders before noon
+    ship the same day
+
+    and arrive tomorrow
+  steps:
+    - title: Address
+      hint: Where to ship
+    - title: Payment
+    -
+      title: Review
+  links:
+  - Home
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value.into_value(),
+            json!({
+                "Cart": {
+                    "notice": "Orders before noon ship the same day\nand arrive tomorrow",
+                    "steps": [
+                        { "title": "Address", "hint": "Where to ship" },
+                        { "title": "Payment" },
+                        { "title": "Review" }
+                    ],
+                    "links": ["Home"]
+                }
+            })
+        );
+        assert!(parse("Cart:\n  steps:\n    - title: a\n        hint: b\n").is_err());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert_eq!(
+            parse("---\nCart:\n  title: Cart\n...\n")
+                .unwrap()
+                .into_value(),
+            json!({ "Cart": { "title": "Cart" } })
+        );
+        for yaml in [
+            // Several documents
+            "Cart:\n  title: Cart\n---\nNav:\n  home: Home\n",
+            "Cart: {}\n...\n---\nNav: {}\n",
+            // Anchors, aliases and tags
+            "Base: &base\n  title: Cart\nCart:\n  <<: *base\n",
+            "Cart:\n  title: !!str Cart\n",
+            "Cart:\n  items:\n    - *item\n",
+            "? Cart\n: Title\n",
+            // Quoted strings on several lines
+            "Cart:\n  title: \"Your\n    cart\"\n",
+        ] {
+            assert!(parse(yaml).is_err(), "{yaml}");
+        }
+        // Plain text that only contains the characters is fine
+        assert_eq!(
+            parse("Cart: Tom & Jerry *new*\n").unwrap().into_value(),
+            json!({ "Cart": "Tom & Jerry *new*" })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = OrderedValue::Object(vec![
+            (
+                "Cart".to_string(),
+                OrderedValue::Object(vec![
+                    (
+                        "title".to_string(),
+                        OrderedValue::Value(json!("Your {count} items")),
+                    ),
+                    ("yes".to_string(), OrderedValue::Value(json!("no"))),
+                    ("multi".to_string(), OrderedValue::Value(json!("a\nb"))),
+                    (
+                        "colon".to_string(),
+                        OrderedValue::Value(json!("Note: this")),
+                    ),
+                    ("items".to_string(), OrderedValue::Value(json!(["1", 2]))),
+                ]),
+            ),
+            ("Nav".to_string(), OrderedValue::Object(Vec::new())),
+        ]);
+        let yaml = to_string(&value, "  ");
+        assert_eq!(
+            yaml,
+            "Cart:\n  title: Your {count} items\n  \"yes\": \"no\"\n  multi: \"a\\nb\"\n  colon: \"Note: this\"\n  items:\n    - \"1\"\n    - 2\nNav: {}\n"
+        );
+        assert_eq!(parse(&yaml).unwrap(), value);
+        assert_eq!(to_string(&OrderedValue::Object(Vec::new()), "  "), "{}\n");
+    }
+
+    #[test]
+    fn test_special_scalars() {
+        for text in [
+            ".inf",
+            "-.Inf",
+            ".NaN",
+            "0x1F",
+            "0o17",
+            "017",
+            "1_000",
+            "1e3",
+            ".5",
+            "1:30",
+            "2024-01-31",
+            "NULL",
+            "True",
+            "Off",
+            "<<",
+        ] {
+            let value =
+                OrderedValue::Object(vec![(text.to_string(), OrderedValue::Value(json!(text)))]);
+            let yaml = to_string(&value, "  ");
+            assert_eq!(yaml, format!("\"{text}\": \"{text}\"\n"));
+            assert_eq!(parse(&yaml).unwrap(), value);
+        }
+
+        // Text that only starts like a number stays plain
+        for text in [
+            "10 items",
+            "1st",
+            ".env file",
+            "v1.2",
+            "Infinity and beyond",
+        ] {
+            assert_eq!(string(text), text);
+        }
+    }
+}
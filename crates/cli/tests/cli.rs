@@ -0,0 +1,319 @@
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+const CART: &str = r#"import { useTranslations } from 'next-intl';
+
+export function Cart() {
+    const t = useTranslations('Cart');
+    return <h1>{t('title')}</h1>;
+}
+"#;
+
+const BANNER: &str = r#"import { useTranslations } from 'next-intl';
+
+export function Banner() {
+    return <p>Free shipping</p>;
+}
+"#;
+
+const CATALOG: &str = "{\n  \"Cart\": {\n    \"title\": \"Cart\"\n  }\n}\n";
+
+/// A project with a component and the English catalog of its messages
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/cart.tsx"), CART).unwrap();
+    fs::write(dir.path().join("en.json"), CATALOG).unwrap();
+    dir
+}
+
+/// The CLI in a directory, without colors or logs of the environment
+fn command(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_next-intl-extractor-cli"));
+    command
+        .current_dir(dir)
+        .env("NO_COLOR", "1")
+        .env_remove("RUST_LOG");
+    command
+}
+
+/// Run the CLI in a project with its catalog and components
+fn run(dir: &Path, args: &[&str]) -> Output {
+    command(dir)
+        .args(args)
+        .args(["--output-path", "en.json", "--pattern", "src/**/*.tsx"])
+        .output()
+        .unwrap()
+}
+
+fn read(dir: &Path, path: &str) -> String {
+    fs::read_to_string(dir.join(path)).unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_graph_json() {
+    let dir = project();
+    let output = run(dir.path(), &["graph", "--graph-format", "json"]);
+    assert!(output.status.success());
+
+    // Nothing but the graph is printed
+    let graph: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        graph,
+        json!({ "files": { "src/cart.tsx": ["Cart"] }, "shared": [] })
+    );
+}
+
+#[test]
+fn test_extract_writes_catalog() {
+    let dir = project();
+    fs::write(dir.path().join("en.json"), "{}").unwrap();
+    let output = run(dir.path(), &["extract"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+    let catalog: Value = serde_json::from_str(&read(dir.path(), "en.json")).unwrap();
+    assert_eq!(catalog, json!({ "Cart": { "title": "Cart.title" } }));
+}
+
+#[test]
+fn test_extract_fix_rewrites_sources() {
+    let dir = project();
+    fs::write(dir.path().join("src/banner.tsx"), BANNER).unwrap();
+    let output = run(dir.path(), &["extract", "--fix"]);
+    assert!(output.status.success());
+
+    assert_eq!(
+        read(dir.path(), "src/banner.tsx"),
+        r#"import { useTranslations } from 'next-intl';
+
+export function Banner() {
+    const t = useTranslations('Banner');
+    return <p>{t('freeShipping')}</p>;
+}
+"#
+    );
+    let catalog: Value = serde_json::from_str(&read(dir.path(), "en.json")).unwrap();
+    assert_eq!(
+        catalog,
+        json!({ "Cart": { "title": "Cart" }, "Banner": { "freeShipping": "Free shipping" } })
+    );
+}
+
+#[test]
+fn test_extract_fix_conflicts_write_nothing() {
+    let dir = project();
+    let summary = CART
+        .replace("Cart()", "Summary()")
+        .replace("</h1>", " Total</h1>");
+    fs::write(dir.path().join("src/summary.tsx"), &summary).unwrap();
+    let output = run(dir.path(), &["extract", "--fix"]);
+    assert!(!output.status.success());
+
+    // `Cart.title` is used in both files, so neither the sources nor the catalog are written
+    assert_eq!(read(dir.path(), "src/summary.tsx"), summary);
+    assert_eq!(read(dir.path(), "en.json"), CATALOG);
+}
+
+#[test]
+fn test_dynamic_keys() {
+    let dir = project();
+    fs::write(
+        dir.path().join("src/cart.tsx"),
+        CART.replace("{t('title')}", "{t('title')}{t(key)}"),
+    )
+    .unwrap();
+    let output = run(dir.path(), &["extract", "--dynamic-keys", "error"]);
+    assert!(!output.status.success());
+    assert_eq!(read(dir.path(), "en.json"), CATALOG);
+
+    let output = run(dir.path(), &["extract", "--dynamic-keys", "ignore"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_merge() {
+    let dir = project();
+    fs::write(dir.path().join("base.json"), r#"{ "a": "A", "b": "B" }"#).unwrap();
+    fs::write(dir.path().join("ours.json"), r#"{ "a": "Ours", "b": "B" }"#).unwrap();
+    fs::write(
+        dir.path().join("theirs.json"),
+        r#"{ "a": "A", "b": "Theirs" }"#,
+    )
+    .unwrap();
+    let output = command(dir.path())
+        .args(["merge", "base.json", "ours.json", "theirs.json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let merged: Value = serde_json::from_str(&read(dir.path(), "ours.json")).unwrap();
+    assert_eq!(merged, json!({ "a": "Ours", "b": "Theirs" }));
+    // Nothing is extracted
+    assert_eq!(read(dir.path(), "en.json"), CATALOG);
+}
+
+#[test]
+fn test_check_writes_nothing() {
+    let dir = project();
+    fs::write(dir.path().join("src/banner.tsx"), BANNER).unwrap();
+    fs::write(
+        dir.path().join("src/cart.tsx"),
+        CART.replace("{t('title')}", "{t('title')}{t('total')}"),
+    )
+    .unwrap();
+    let output = run(dir.path(), &["check", "--fix"]);
+    assert!(!output.status.success());
+
+    // Only the summary of the catalog that would change is printed, the errors are logged
+    assert_eq!(
+        stdout(&output),
+        "en.json: +2 -1 lines\n  + Cart.total: \"Cart.total\"\n  1 added, 0 removed, 0 changed\n"
+    );
+    assert_eq!(read(dir.path(), "src/banner.tsx"), BANNER);
+    assert_eq!(read(dir.path(), "en.json"), CATALOG);
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let dir = project();
+    fs::write(dir.path().join("src/banner.tsx"), BANNER).unwrap();
+    let output = run(dir.path(), &["extract", "--dry-run", "--fix"]);
+    assert!(output.status.success());
+
+    assert_eq!(
+        stdout(&output),
+        r#"--- a/src/banner.tsx
++++ b/src/banner.tsx
+@@ -1,5 +1,6 @@
+ import { useTranslations } from 'next-intl';
+ 
+ export function Banner() {
+-    return <p>Free shipping</p>;
++    const t = useTranslations('Banner');
++    return <p>{t('freeShipping')}</p>;
+ }
+--- a/en.json
++++ b/en.json
+@@ -1,5 +1,8 @@
+ {
+   "Cart": {
+     "title": "Cart"
++  },
++  "Banner": {
++    "freeShipping": "Free shipping"
+   }
+ }
+"#
+    );
+    assert_eq!(read(dir.path(), "src/banner.tsx"), BANNER);
+    assert_eq!(read(dir.path(), "en.json"), CATALOG);
+}
+
+#[test]
+fn test_read_only_modes() {
+    let dir = project();
+    fs::write(dir.path().join("src/banner.tsx"), BANNER).unwrap();
+
+    let output = run(dir.path(), &["stats"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "en.json\n  Cart: 0 added, 0 removed, 1 unchanged\n"
+    );
+
+    let output = run(dir.path(), &["find", "Cart.title"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "src/cart.tsx:5:19\n");
+
+    // `--fix` only rewrites the files in the modes that write them
+    let output = run(dir.path(), &["coverage", "--fix"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "Coverage by directory\n  src: 1 translated, 1 hard-coded (50.0%)\nTotal: 1 translated, 1 hard-coded (50.0%)\n"
+    );
+
+    assert_eq!(read(dir.path(), "src/banner.tsx"), BANNER);
+    assert_eq!(read(dir.path(), "src/cart.tsx"), CART);
+    assert_eq!(read(dir.path(), "en.json"), CATALOG);
+}
+
+#[test]
+fn test_extract_only_options() {
+    let dir = project();
+    fs::write(dir.path().join("src/banner.tsx"), BANNER).unwrap();
+
+    // Other subcommands don't take the options of `extract` instead of doing what they say
+    let output = run(dir.path(), &["stats", "-w"]);
+    assert!(!output.status.success());
+    assert_eq!(stdout(&output), "");
+
+    for args in [
+        &["diff", "en.json", "en.json", "--check"][..],
+        &["fmt", "--check", "--sort", "alphabetical"],
+    ] {
+        let output = run(dir.path(), args);
+        assert!(!output.status.success());
+        assert_eq!(stdout(&output), "");
+    }
+
+    // The subcommands that write fail with `--dry-run` instead of writing anyway
+    for args in [
+        &["move-namespace", "Cart", "Shop.Cart", "--dry-run", "--fix"][..],
+        &["prune", "--dry-run"],
+    ] {
+        let output = run(dir.path(), args);
+        assert!(!output.status.success());
+        assert_eq!(stdout(&output), "");
+    }
+
+    assert_eq!(read(dir.path(), "src/banner.tsx"), BANNER);
+    assert_eq!(read(dir.path(), "src/cart.tsx"), CART);
+    assert_eq!(read(dir.path(), "en.json"), CATALOG);
+}
+
+#[test]
+fn test_config_file_with_subcommand() {
+    let dir = project();
+    fs::write(
+        dir.path().join("next-intl-extractor.config.json"),
+        r#"{ "outputPath": "en.json", "pattern": "src/**/*.tsx", "watch": true }"#,
+    )
+    .unwrap();
+    // `watch` only applies to `extract`
+    let output = command(dir.path()).arg("stats").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "en.json\n  Cart: 0 added, 0 removed, 1 unchanged\n"
+    );
+}
+
+#[test]
+fn test_package_config_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let package = dir.path().join("packages/shop");
+    fs::create_dir_all(package.join("src")).unwrap();
+    fs::write(package.join("src/cart.tsx"), CART).unwrap();
+    fs::write(
+        package.join("next-intl-extractor.config.json"),
+        r#"{ "outputPath": "messages.json" }"#,
+    )
+    .unwrap();
+
+    // The files of the package are extracted from its directory, with its config file
+    let output = command(dir.path())
+        .args(["--pattern", "**/*.tsx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let catalog: Value = serde_json::from_str(&read(&package, "messages.json")).unwrap();
+    assert_eq!(catalog, json!({ "Cart": { "title": "Cart.title" } }));
+    assert!(!dir.path().join("messages.json").exists());
+}
@@ -0,0 +1,902 @@
+use anyhow::{anyhow, Result};
+use oxc::{
+    allocator::Allocator,
+    ast::{
+        ast::{
+            Argument, BindingPatternKind, ExportDefaultDeclaration, ExportDefaultDeclarationKind,
+            Expression, FormalParameters, Function, FunctionBody, ImportDeclarationSpecifier,
+            Program, Statement, VariableDeclarator,
+        },
+        visit::walk,
+        Visit,
+    },
+    parser::Parser,
+    span::{GetSpan, SourceType, Span},
+    syntax::scope::ScopeFlags,
+};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::convention::namespace_from_path;
+use crate::hardcoded::{HardcodedString, HardcodedStringVisitor};
+use crate::visitor::{is_next_intl_module, unwrap_component_wrappers, TranslatorFactory};
+use crate::NamespaceUsage;
+
+/// Name of the translator that is declared in components that don't have one yet
+const TRANSLATOR_NAME: &str = "t";
+
+/// The result of wrapping the hard-coded strings of a file in translator calls
+#[derive(Debug, Default)]
+pub struct Fix {
+    /// The rewritten source text
+    pub source_text: String,
+    /// The generated messages by namespace and key, with the original text as value
+    pub messages: HashMap<String, HashMap<String, String>>,
+}
+
+impl Fix {
+    /// Whether any string was wrapped
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// The translator factories a file imports from next-intl by their local name, and the names
+/// bound by its other imports
+#[derive(Debug, Default)]
+struct Imports {
+    factories: HashMap<String, TranslatorFactory>,
+    /// Local names of next-intl namespace imports, e.g. `intl` for `import * as intl from
+    /// "next-intl"`
+    namespaces: Vec<String>,
+    bound: HashSet<String>,
+}
+
+impl Imports {
+    fn collect(program: &Program) -> Self {
+        let mut imports = Self::default();
+        for statement in &program.body {
+            let Statement::ImportDeclaration(import) = statement else {
+                continue;
+            };
+            let is_next_intl = is_next_intl_module(import.source.value.as_str());
+            for specifier in import.specifiers.iter().flatten() {
+                let local = match specifier {
+                    ImportDeclarationSpecifier::ImportSpecifier(specifier) => {
+                        let factory =
+                            TranslatorFactory::from_export_name(specifier.imported.name().as_str());
+                        match factory {
+                            Some(factory) if is_next_intl => {
+                                imports
+                                    .factories
+                                    .insert(specifier.local.name.to_string(), factory);
+                                continue;
+                            }
+                            _ => &specifier.local,
+                        }
+                    }
+                    ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                        if is_next_intl {
+                            imports.namespaces.push(specifier.local.name.to_string());
+                            continue;
+                        }
+                        &specifier.local
+                    }
+                    ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                        &specifier.local
+                    }
+                };
+                imports.bound.insert(local.name.to_string());
+            }
+        }
+        imports
+    }
+
+    /// The next-intl factory that a callee refers to, e.g. `useT` for `import {
+    /// useTranslations as useT } from "next-intl"` or `intl.useTranslations`
+    fn factory(&self, callee: &Expression) -> Option<TranslatorFactory> {
+        match callee {
+            Expression::Identifier(ident) => self.factories.get(ident.name.as_str()).cloned(),
+            Expression::StaticMemberExpression(member) => match &member.object {
+                Expression::Identifier(object)
+                    if self
+                        .namespaces
+                        .iter()
+                        .any(|name| object.name == name.as_str()) =>
+                {
+                    TranslatorFactory::from_export_name(member.property.name.as_str())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The callee that calls a factory and whether it must be imported first, `None` if another
+    /// import binds the name of the factory
+    fn callee(&self, factory: &TranslatorFactory, name: &str) -> Option<(String, bool)> {
+        if let Some((local, _)) = self.factories.iter().find(|(_, f)| *f == factory) {
+            return Some((local.clone(), false));
+        }
+        if let Some(namespace) = self.namespaces.first() {
+            return Some((format!("{namespace}.{name}"), false));
+        }
+        (!self.bound.contains(name)).then(|| (name.to_string(), true))
+    }
+}
+
+/// The message keys that are taken in each namespace, so generated keys don't overwrite the
+/// messages of the catalog or the ones generated for other files
+#[derive(Debug, Default)]
+pub struct MessageKeys {
+    namespaces: HashMap<String, HashSet<String>>,
+}
+
+impl MessageKeys {
+    /// Take the keys of a message catalog, e.g. `title` in the `Cart` namespace for
+    /// `{"Cart": {"title": "Your cart"}}`
+    pub fn add_catalog(&mut self, messages: &Map<String, Value>) {
+        self.add_messages("", messages);
+    }
+
+    fn add_messages(&mut self, namespace: &str, messages: &Map<String, Value>) {
+        for (key, value) in messages {
+            self.namespaces
+                .entry(namespace.to_string())
+                .or_default()
+                .insert(key.clone());
+            if let Value::Object(nested) = value {
+                let nested_namespace = if namespace.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{namespace}.{key}")
+                };
+                self.add_messages(&nested_namespace, nested);
+            }
+        }
+    }
+
+    /// Take a new key for a text in a namespace, numbered if the key generated from the text
+    /// is taken, e.g. `yourCart2`
+    fn take(&mut self, namespace: &str, text: &str) -> String {
+        let keys = self.namespaces.entry(namespace.to_string()).or_default();
+        let base = generate_key(text);
+        let mut key = base.clone();
+        let mut suffix = 2;
+        while keys.contains(&key) {
+            key = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        keys.insert(key.clone());
+        key
+    }
+}
+
+/// A component that hard-coded strings can be moved out of
+#[derive(Debug)]
+struct Component {
+    name: String,
+    body: Span,
+    is_async: bool,
+    /// Where a translator declaration can be inserted
+    insert_at: u32,
+    /// The translator that is already declared in the component and its namespace
+    translator: Option<(String, String)>,
+    /// Whether `t` is already used for something else than a translator
+    name_taken: bool,
+}
+
+/// Collects the components of a file, which are functions with a PascalCase name and a body
+#[derive(Debug)]
+struct ComponentCollector<'i> {
+    components: Vec<Component>,
+    default_export_name: String,
+    imports: &'i Imports,
+}
+
+impl ComponentCollector<'_> {
+    fn push(&mut self, name: &str, params: &FormalParameters, body: &FunctionBody, is_async: bool) {
+        if !name.starts_with(|c: char| c.is_ascii_uppercase()) && name != self.default_export_name {
+            return;
+        }
+        let Some(first_statement) = body.statements.first() else {
+            return;
+        };
+
+        let mut translator = None;
+        let mut name_taken = params.items.iter().any(|param| {
+            param
+                .pattern
+                .get_binding_identifier()
+                .is_some_and(|ident| ident.name == TRANSLATOR_NAME)
+        });
+        for statement in &body.statements {
+            let Statement::VariableDeclaration(declaration) = statement else {
+                continue;
+            };
+            for decl in &declaration.declarations {
+                let Some(ident) = decl.id.get_binding_identifier() else {
+                    continue;
+                };
+                match translator_declaration(decl, self.imports) {
+                    Some(namespace) if translator.is_none() => {
+                        translator = Some((ident.name.to_string(), namespace));
+                    }
+                    _ if ident.name == TRANSLATOR_NAME => name_taken = true,
+                    _ => {}
+                }
+            }
+        }
+
+        self.components.push(Component {
+            name: name.to_string(),
+            body: body.span,
+            is_async,
+            insert_at: first_statement.span().start,
+            translator,
+            name_taken,
+        });
+    }
+
+    /// The index of the innermost component that contains a span
+    fn component_at(&self, span: Span) -> Option<usize> {
+        self.components
+            .iter()
+            .enumerate()
+            .filter(|(_, component)| {
+                component.body.start <= span.start && span.end <= component.body.end
+            })
+            .min_by_key(|(_, component)| component.body.size())
+            .map(|(index, _)| index)
+    }
+}
+
+impl<'a> Visit<'a> for ComponentCollector<'_> {
+    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
+        if let (Some(ident), Some(body)) = (&it.id, &it.body) {
+            self.push(ident.name.as_str(), &it.params, body, it.r#async);
+        }
+        walk::walk_function(self, it, flags);
+    }
+
+    fn visit_variable_declarator(&mut self, it: &VariableDeclarator<'a>) {
+        if let (BindingPatternKind::BindingIdentifier(ident), Some(init)) = (&it.id.kind, &it.init)
+        {
+            self.visit_component_expression(ident.name.as_str(), init);
+        }
+        walk::walk_variable_declarator(self, it);
+    }
+
+    fn visit_export_default_declaration(&mut self, it: &ExportDefaultDeclaration<'a>) {
+        let name = self.default_export_name.clone();
+        match &it.declaration {
+            ExportDefaultDeclarationKind::FunctionDeclaration(func) if func.id.is_none() => {
+                if let Some(body) = &func.body {
+                    self.push(&name, &func.params, body, func.r#async);
+                }
+            }
+            kind => {
+                if let Some(expr) = kind.as_expression() {
+                    self.visit_component_expression(&name, expr);
+                }
+            }
+        }
+        walk::walk_export_default_declaration(self, it);
+    }
+}
+
+impl ComponentCollector<'_> {
+    /// Arrow functions and function expressions, possibly wrapped in `memo()` or `forwardRef()`
+    fn visit_component_expression(&mut self, name: &str, expr: &Expression) {
+        match unwrap_component_wrappers(expr) {
+            // Arrow functions with an expression body have no place for a translator
+            Expression::ArrowFunctionExpression(arrow) if !arrow.expression => {
+                self.push(name, &arrow.params, &arrow.body, arrow.r#async)
+            }
+            Expression::FunctionExpression(func) if func.id.is_none() => {
+                if let Some(body) = &func.body {
+                    self.push(name, &func.params, body, func.r#async);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The namespace of a `useTranslations("Namespace")` or `await getTranslations("Namespace")`
+/// declaration, with the factory imported from next-intl
+fn translator_declaration(decl: &VariableDeclarator, imports: &Imports) -> Option<String> {
+    let call_expr = match decl.init.as_ref()? {
+        Expression::CallExpression(call_expr) => call_expr,
+        Expression::AwaitExpression(await_expr) => match &await_expr.argument {
+            Expression::CallExpression(call_expr) => call_expr,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let is_factory = matches!(
+        imports.factory(&call_expr.callee),
+        Some(TranslatorFactory::UseTranslations | TranslatorFactory::GetTranslations)
+    );
+    match call_expr.arguments.first() {
+        Some(Argument::StringLiteral(lit)) if is_factory => Some(lit.value.to_string()),
+        _ => None,
+    }
+}
+
+/// A namespace moved in a source file
+#[derive(Debug, Default)]
+pub struct NamespaceMove {
+    /// The rewritten source text, `None` if no translator literal uses the namespace
+    pub source_text: Option<String>,
+    /// Translators of the namespace that can't be rewritten, e.g. `useTranslations(NAMESPACE)`
+    pub unmovable: Vec<NamespaceUsage>,
+}
+
+/// Move the translators of a namespace and of the namespaces below it to another namespace,
+/// e.g. `useTranslations('Cart.Summary')` to `useTranslations('Shop.Cart.Summary')` for `Cart`
+/// to `Shop.Cart`
+///
+/// `translators` are the translator namespaces of the file, see
+/// [`Extraction::translator_namespaces`], so aliased imports and custom factories are moved
+/// too. Only namespaces written as a literal are rewritten, the others are returned
+pub fn move_namespace(
+    source_text: &str,
+    translators: &[NamespaceUsage],
+    from: &str,
+    to: &str,
+) -> NamespaceMove {
+    let mut edits: Vec<(u32, u32, String)> = Vec::new();
+    let mut unmovable = Vec::new();
+    for translator in translators {
+        let rest = match translator.namespace.strip_prefix(from) {
+            Some(rest) if rest.is_empty() || rest.starts_with('.') => rest,
+            _ => continue,
+        };
+        let Some(span) = translator.literal else {
+            unmovable.push(translator.clone());
+            continue;
+        };
+        // Keep the quotes of the literal
+        let quote = &source_text[span.start as usize..span.start as usize + 1];
+        edits.push((span.start, span.end, format!("{quote}{to}{rest}{quote}")));
+    }
+    if edits.is_empty() {
+        return NamespaceMove {
+            source_text: None,
+            unmovable,
+        };
+    }
+
+    edits.sort_by_key(|(start, end, _)| std::cmp::Reverse((*start, *end)));
+    let mut moved = source_text.to_string();
+    for (start, end, text) in edits {
+        moved.replace_range(start as usize..end as usize, &text);
+    }
+    NamespaceMove {
+        source_text: Some(moved),
+        unmovable,
+    }
+}
+
+/// Generate a camelCase message key from the first words of a text
+///
+/// e.g. `Welcome back, friend!` becomes `welcomeBackFriend`
+pub fn generate_key(text: &str) -> String {
+    let key: String = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(4)
+        .enumerate()
+        .map(|(index, word)| {
+            let word = word.to_lowercase();
+            if index == 0 {
+                return word;
+            }
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    if key.is_empty() {
+        "text".to_string()
+    } else {
+        key
+    }
+}
+
+/// Wrap the hard-coded strings of a file in translator calls
+///
+/// Strings are moved to the namespace of the component's translator. Components without one
+/// get a translator of the namespace derived from the file path with `template`, see
+/// [`namespace_from_path`], or named after the component if the path has none. Its factory is
+/// imported unless another import binds its name, which leaves the component alone, as are
+/// strings outside of components.
+///
+/// The same text gets the same key within the file, and keys taken in `keys` aren't reused
+pub fn fix_hardcoded_strings(
+    file_path: &Path,
+    source_text: &str,
+    template: &str,
+    keys: &mut MessageKeys,
+) -> Result<Fix> {
+    let source_type = SourceType::from_path(file_path).map_err(|error| anyhow!("{}", &*error))?;
+    let allocator = Allocator::default();
+    let ret = Parser::new(&allocator, source_text, source_type).parse();
+    if !ret.errors.is_empty() {
+        return Err(anyhow!(
+            "Failed to parse {}, not fixing hard-coded strings",
+            file_path.display()
+        ));
+    }
+    let program = ret.program;
+
+    let mut hardcoded_string_visitor = HardcodedStringVisitor::new();
+    hardcoded_string_visitor.visit_program(&program);
+    let imports = Imports::collect(&program);
+    let mut collector = ComponentCollector {
+        components: Vec::new(),
+        default_export_name: file_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        imports: &imports,
+    };
+    collector.visit_program(&program);
+    let path_namespace = namespace_from_path(template, file_path);
+
+    let mut edits: Vec<(u32, u32, String)> = Vec::new();
+    let mut messages: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut keys_by_text: HashMap<(String, String), String> = HashMap::new();
+    let mut declared: Vec<usize> = Vec::new();
+    let (mut use_translations, mut get_translations) = (false, false);
+
+    for string in hardcoded_string_visitor.strings() {
+        let Some(index) = collector.component_at(string.span) else {
+            continue;
+        };
+        let component = &collector.components[index];
+
+        let (translator, namespace) = match &component.translator {
+            Some((translator, namespace)) => (translator.clone(), namespace.clone()),
+            None if component.name_taken => continue,
+            None => {
+                let (factory, name) = if component.is_async {
+                    (TranslatorFactory::GetTranslations, "getTranslations")
+                } else {
+                    (TranslatorFactory::UseTranslations, "useTranslations")
+                };
+                let Some((callee, needs_import)) = imports.callee(&factory, name) else {
+                    continue;
+                };
+                let namespace = path_namespace
+                    .clone()
+                    .unwrap_or_else(|| component.name.clone());
+                if !declared.contains(&index) {
+                    declared.push(index);
+                    if component.is_async {
+                        get_translations |= needs_import;
+                    } else {
+                        use_translations |= needs_import;
+                    }
+                    let call = if component.is_async {
+                        format!("await {callee}")
+                    } else {
+                        callee
+                    };
+                    let indent = line_indent(source_text, component.insert_at);
+                    edits.push((
+                        component.insert_at,
+                        component.insert_at,
+                        format!(
+                            "const {} = {}('{}');\n{}",
+                            TRANSLATOR_NAME, call, namespace, indent
+                        ),
+                    ));
+                }
+                (TRANSLATOR_NAME.to_string(), namespace)
+            }
+        };
+
+        let key = keys_by_text
+            .entry((namespace.clone(), string.text.clone()))
+            .or_insert_with(|| keys.take(&namespace, &string.text))
+            .clone();
+        messages
+            .entry(namespace)
+            .or_default()
+            .insert(key.clone(), string.text.clone());
+        edits.push(replacement(source_text, string, &translator, &key));
+    }
+
+    // Imports go after directives such as "use client"
+    let import_at = program
+        .directives
+        .last()
+        .map_or(0, |directive| line_end(source_text, directive.span.end));
+    if get_translations {
+        edits.push(import_edit(
+            &program,
+            "next-intl/server",
+            "getTranslations",
+            import_at,
+        ));
+    }
+    if use_translations {
+        edits.push(import_edit(
+            &program,
+            "next-intl",
+            "useTranslations",
+            import_at,
+        ));
+    }
+
+    // Apply the edits from the end so the offsets of the other edits stay valid
+    edits.sort_by_key(|(start, end, _)| std::cmp::Reverse((*start, *end)));
+    let mut fixed = source_text.to_string();
+    for (start, end, text) in edits {
+        fixed.replace_range(start as usize..end as usize, &text);
+    }
+
+    Ok(Fix {
+        source_text: fixed,
+        messages,
+    })
+}
+
+/// The edit that imports a next-intl function
+///
+/// The function is added to an existing import of the module, e.g. `import { useLocale } from
+/// 'next-intl'`, so the module isn't imported twice
+fn import_edit(
+    program: &Program,
+    source: &str,
+    function: &str,
+    import_at: u32,
+) -> (u32, u32, String) {
+    let mut existing = None;
+    for statement in &program.body {
+        let Statement::ImportDeclaration(import) = statement else {
+            continue;
+        };
+        if import.source.value != source || import.import_kind.is_type() {
+            continue;
+        }
+        let mut last_specifier = None;
+        for specifier in import.specifiers.iter().flatten() {
+            if let ImportDeclarationSpecifier::ImportSpecifier(specifier) = specifier {
+                last_specifier = Some(specifier.span.end);
+            }
+        }
+        existing = existing.or(last_specifier);
+    }
+    match existing {
+        Some(end) => (end, end, format!(", {function}")),
+        None => (
+            import_at,
+            import_at,
+            format!("import {{ {function} }} from '{source}';\n"),
+        ),
+    }
+}
+
+/// The edit that replaces a hard-coded string with a translator call
+fn replacement(
+    source_text: &str,
+    string: &HardcodedString,
+    translator: &str,
+    key: &str,
+) -> (u32, u32, String) {
+    let call = format!("{}('{}')", translator, key);
+    // JSX text and attribute values without braces, e.g. `alt="Logo"`, need an expression
+    // container, while attribute values in braces, e.g. `alt={"Logo"}`, don't
+    let in_braces = source_text[..string.span.start as usize]
+        .trim_end()
+        .ends_with('{');
+    let text = if in_braces {
+        call
+    } else {
+        format!("{{{}}}", call)
+    };
+    (string.span.start, string.span.end, text)
+}
+
+/// The whitespace at the start of the line of an offset
+fn line_indent(source_text: &str, offset: u32) -> &str {
+    let line_start = source_text[..offset as usize]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line = &source_text[line_start..offset as usize];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// The offset after the end of the line of an offset
+fn line_end(source_text: &str, offset: u32) -> u32 {
+    source_text[offset as usize..]
+        .find('\n')
+        .map_or(source_text.len() as u32, |index| offset + index as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convention::DEFAULT_NAMESPACE_TEMPLATE;
+    use crate::Extractor;
+
+    fn fixed(file_path: &str, source: &str) -> Fix {
+        fix_hardcoded_strings(
+            Path::new(file_path),
+            source,
+            DEFAULT_NAMESPACE_TEMPLATE,
+            &mut MessageKeys::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_generate_key() {
+        assert_eq!(generate_key("Welcome back, friend!"), "welcomeBackFriend");
+        assert_eq!(generate_key("Save"), "save");
+        assert_eq!(generate_key("One two three four five"), "oneTwoThreeFour");
+        assert_eq!(generate_key("..."), "text");
+    }
+
+    #[test]
+    fn test_fix_hardcoded_strings() {
+        let source = r#"'use client';
+
+import { useState } from 'react';
+
+export function Cart() {
+    const [open] = useState(false);
+    return (
+        <div title="Your cart">
+            <h1>Your cart</h1>
+            <img alt={"Empty cart"} src="/empty.png" />
+        </div>
+    );
+}
+
+export const Summary = () => {
+    const t = useTranslations('Summary');
+    return <p title={t('title')}>Total price</p>;
+};
+
+const helper = () => <p>Not a component</p>;
+"#;
+        let fix = fixed("cart.tsx", source);
+
+        assert_eq!(
+            fix.source_text,
+            r#"'use client';
+import { useTranslations } from 'next-intl';
+
+import { useState } from 'react';
+
+export function Cart() {
+    const t = useTranslations('Cart');
+    const [open] = useState(false);
+    return (
+        <div title={t('yourCart')}>
+            <h1>{t('yourCart')}</h1>
+            <img alt={t('emptyCart')} src="/empty.png" />
+        </div>
+    );
+}
+
+export const Summary = () => {
+    const t = useTranslations('Summary');
+    return <p title={t('title')}>Total price</p>;
+};
+
+const helper = () => <p>Not a component</p>;
+"#
+        );
+        // `useTranslations` isn't imported from next-intl in `Summary`, which declares `t`
+        assert_eq!(fix.messages.len(), 1);
+        assert_eq!(fix.messages["Cart"].len(), 2);
+        assert_eq!(fix.messages["Cart"]["yourCart"], "Your cart");
+        assert_eq!(fix.messages["Cart"]["emptyCart"], "Empty cart");
+    }
+
+    #[test]
+    fn test_fix_server_components() {
+        let source = r#"export default async function () {
+    return <h1>Welcome</h1>;
+}
+"#;
+        let fix = fixed("app/page.tsx", source);
+        assert_eq!(
+            fix.source_text,
+            r#"import { getTranslations } from 'next-intl/server';
+export default async function () {
+    const t = await getTranslations('page');
+    return <h1>{t('welcome')}</h1>;
+}
+"#
+        );
+        assert_eq!(fix.messages["page"]["welcome"], "Welcome");
+    }
+
+    #[test]
+    fn test_fix_existing_imports() {
+        let source = r#"import { useTranslations } from 'next-intl';
+
+export function Cart() {
+    return <h1>Cart</h1>;
+}
+"#;
+        let fix = fixed("cart.tsx", source);
+        assert_eq!(
+            fix.source_text,
+            r#"import { useTranslations } from 'next-intl';
+
+export function Cart() {
+    const t = useTranslations('Cart');
+    return <h1>{t('cart')}</h1>;
+}
+"#
+        );
+
+        let source = r#"import { getTranslations } from 'next-intl/server';
+
+export default async function () {
+    return <h1>Welcome</h1>;
+}
+"#;
+        let fix = fixed("app/page.tsx", source);
+        assert_eq!(
+            fix.source_text,
+            r#"import { getTranslations } from 'next-intl/server';
+
+export default async function () {
+    const t = await getTranslations('page');
+    return <h1>{t('welcome')}</h1>;
+}
+"#
+        );
+
+        // Added to the import of the module instead of importing it twice
+        let source = r#"import { useLocale } from 'next-intl';
+
+export function Cart() {
+    const locale = useLocale();
+    return <h1 lang={locale}>Cart</h1>;
+}
+"#;
+        let fix = fixed("cart.tsx", source);
+        assert!(fix
+            .source_text
+            .starts_with("import { useLocale, useTranslations } from 'next-intl';\n\n"));
+    }
+
+    #[test]
+    fn test_fix_imported_factories() {
+        // Aliased imports are recognized as translators
+        let source = r#"import { useTranslations as useT } from 'next-intl';
+
+export function Cart() {
+    const t = useT('Shop');
+    return <h1>Your cart</h1>;
+}
+"#;
+        let fix = fixed("cart.tsx", source);
+        assert!(fix.source_text.contains("<h1>{t('yourCart')}</h1>"));
+        assert_eq!(fix.messages["Shop"]["yourCart"], "Your cart");
+
+        // A factory of the same name from another module isn't a translator, and isn't
+        // imported twice
+        let source = r#"import { useTranslations } from './i18n';
+
+export function Cart() {
+    const t = useTranslations('Shop');
+    return <h1>Your cart</h1>;
+}
+"#;
+        let fix = fixed("cart.tsx", source);
+        assert_eq!(fix.source_text, source);
+        assert!(fix.is_empty());
+    }
+
+    #[test]
+    fn test_fix_namespaces_from_path() {
+        let mut keys = MessageKeys::default();
+        keys.add_catalog(
+            serde_json::json!({ "Cart": { "title": "Cart" } })
+                .as_object()
+                .unwrap(),
+        );
+
+        let page = r#"export default function Page() {
+    return <h1>Title</h1>;
+}
+"#;
+        let fix = fix_hardcoded_strings(
+            Path::new("app/(shop)/cart/page.tsx"),
+            page,
+            DEFAULT_NAMESPACE_TEMPLATE,
+            &mut keys,
+        )
+        .unwrap();
+        // The key of the catalog isn't overwritten
+        assert!(fix
+            .source_text
+            .contains("const t = useTranslations('Cart');"));
+        assert_eq!(fix.messages["Cart"]["title2"], "Title");
+
+        // Nor the key generated for the other file of the namespace
+        let summary = r#"export function Summary() {
+    return <p>Title</p>;
+}
+"#;
+        let fix = fix_hardcoded_strings(
+            Path::new("app/(shop)/cart/summary.tsx"),
+            summary,
+            DEFAULT_NAMESPACE_TEMPLATE,
+            &mut keys,
+        )
+        .unwrap();
+        assert_eq!(fix.messages["Cart"]["title3"], "Title");
+    }
+
+    #[test]
+    fn test_move_namespace() {
+        let source = r#"import { useTranslations as useT } from 'next-intl';
+import { getTranslations } from 'next-intl/server';
+
+const NAMESPACE = 'Cart';
+
+export function Cart() {
+    const t = useT('Cart');
+    const summary = useT("Cart.Summary");
+    const total = useT(`Cart.Total`);
+    const other = useT('CartItem');
+    const constant = useT(NAMESPACE);
+    return <h1>{t('title')}</h1>;
+}
+
+export async function generateMetadata() {
+    const t = await getTranslations({ locale: 'en', namespace: 'Cart' });
+}
+"#;
+        let extraction = Extractor::new()
+            .extract_from_source(source, SourceType::tsx())
+            .unwrap();
+        let moved = move_namespace(
+            source,
+            &extraction.translator_namespaces,
+            "Cart",
+            "Shop.Cart",
+        );
+        assert_eq!(
+            moved.source_text.unwrap(),
+            r#"import { useTranslations as useT } from 'next-intl';
+import { getTranslations } from 'next-intl/server';
+
+const NAMESPACE = 'Cart';
+
+export function Cart() {
+    const t = useT('Shop.Cart');
+    const summary = useT("Shop.Cart.Summary");
+    const total = useT(`Shop.Cart.Total`);
+    const other = useT('CartItem');
+    const constant = useT(NAMESPACE);
+    return <h1>{t('title')}</h1>;
+}
+
+export async function generateMetadata() {
+    const t = await getTranslations({ locale: 'en', namespace: 'Shop.Cart' });
+}
+"#
+        );
+        // The namespace of the constant is reported instead
+        assert_eq!(moved.unmovable.len(), 1);
+        assert_eq!(moved.unmovable[0].location.line, 11);
+
+        let moved = move_namespace(source, &extraction.translator_namespaces, "Nav", "Menu");
+        assert!(moved.source_text.is_none() && moved.unmovable.is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+use oxc::{ast::ast::Program, span::Span};
+use std::collections::HashSet;
+
+/// Prefix of the default message annotation, e.g. `t("title") /* default: "Welcome back" */`
+pub const DEFAULT_MESSAGE_PREFIX: &str = "default:";
+
+/// Directive to skip the translator calls on the next line
+pub const IGNORE_NEXT_LINE_DIRECTIVE: &str = "intl-extractor-ignore-next-line";
+
+/// Directive to skip a whole file
+pub const IGNORE_FILE_DIRECTIVE: &str = "intl-extractor-ignore-file";
+
+/// Directive to declare the keys a dynamic key can have, e.g. `// intl-extractor-keys: a, b`
+pub const KEYS_DIRECTIVE: &str = "intl-extractor-keys:";
+
+/// The comment directives that control the extraction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directives {
+    /// Skip the translator calls on the next line, [`IGNORE_NEXT_LINE_DIRECTIVE`] by default
+    pub ignore_next_line: String,
+    /// Skip the whole file, [`IGNORE_FILE_DIRECTIVE`] by default
+    pub ignore_file: String,
+    /// Prefix of the keys a dynamic key can have, [`KEYS_DIRECTIVE`] by default
+    pub keys: String,
+}
+
+impl Directives {
+    /// Whether a comment is one of the directives rather than a description
+    pub fn is_directive(&self, text: &str) -> bool {
+        let text = text.trim();
+        text == self.ignore_next_line || text == self.ignore_file || text.starts_with(&self.keys)
+    }
+}
+
+impl Default for Directives {
+    fn default() -> Self {
+        Self {
+            ignore_next_line: IGNORE_NEXT_LINE_DIRECTIVE.to_string(),
+            ignore_file: IGNORE_FILE_DIRECTIVE.to_string(),
+            keys: KEYS_DIRECTIVE.to_string(),
+        }
+    }
+}
+
+/// A comment in the source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceComment {
+    /// Span of the comment including its delimiters
+    pub span: Span,
+    /// Text of the comment without its delimiters
+    pub text: String,
+}
+
+/// The comments of a program, used to find annotations next to translator calls
+#[derive(Debug, Default)]
+pub struct Comments {
+    comments: Vec<SourceComment>,
+    source_text: String,
+    /// Offsets at which the lines of the source text start
+    line_starts: Vec<u32>,
+    /// Lines (0-based) that are skipped with an ignore directive
+    ignored_lines: HashSet<usize>,
+    ignore_file: bool,
+}
+
+impl Comments {
+    pub fn collect(program: &Program) -> Self {
+        Self::collect_with(program, &Directives::default())
+    }
+
+    /// Collect the comments of a program, with other ignore directives than the default ones
+    pub fn collect_with(program: &Program, directives: &Directives) -> Self {
+        let comments = program
+            .comments
+            .iter()
+            .map(|comment| SourceComment {
+                span: comment.span,
+                text: comment
+                    .content_span()
+                    .source_text(program.source_text)
+                    .to_string(),
+            })
+            .collect();
+        let line_starts = std::iter::once(0)
+            .chain(
+                program
+                    .source_text
+                    .match_indices('\n')
+                    .map(|(index, _)| index as u32 + 1),
+            )
+            .collect();
+
+        let mut comments = Self {
+            comments,
+            source_text: program.source_text.to_string(),
+            line_starts,
+            ignored_lines: HashSet::new(),
+            ignore_file: false,
+        };
+        for comment in &comments.comments {
+            let text = comment.text.trim();
+            if text == directives.ignore_file {
+                comments.ignore_file = true;
+            } else if text == directives.ignore_next_line {
+                let line = comments.line(comment.span.end);
+                comments.ignored_lines.insert(line + 1);
+            }
+        }
+        comments
+    }
+
+    /// The line (0-based) of an offset in the source text
+    fn line(&self, offset: u32) -> usize {
+        self.line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1)
+    }
+
+    /// Whether the file is skipped with `// intl-extractor-ignore-file`
+    pub fn is_file_ignored(&self) -> bool {
+        self.ignore_file
+    }
+
+    /// Whether a span starts on a line that is skipped with `// intl-extractor-ignore-next-line`
+    pub fn is_ignored(&self, span: Span) -> bool {
+        self.ignored_lines.contains(&self.line(span.start))
+    }
+
+    /// The comment directly after a span on the same statement, e.g. `t("key") /* comment */`
+    ///
+    /// Only whitespace and a `;` or `,` may be in between
+    pub fn trailing(&self, span: Span) -> Option<&SourceComment> {
+        let index = self
+            .comments
+            .partition_point(|comment| comment.span.start < span.end);
+        let comment = self.comments.get(index)?;
+        let between = self
+            .source_text
+            .get(span.end as usize..comment.span.start as usize)?;
+        let is_adjacent = !between.contains('\n')
+            && between
+                .chars()
+                .all(|c| c.is_whitespace() || c == ';' || c == ',');
+        is_adjacent.then_some(comment)
+    }
+
+    /// The comment on its own line directly above a span, e.g. `/** Shown on empty cart */`
+    pub fn leading(&self, span: Span) -> Option<&SourceComment> {
+        let index = self
+            .comments
+            .partition_point(|comment| comment.span.end <= span.start);
+        let comment = self.comments.get(index.checked_sub(1)?)?;
+
+        let between = self
+            .source_text
+            .get(comment.span.end as usize..span.start as usize)?;
+        if between.matches('\n').count() != 1 {
+            return None;
+        }
+
+        // Skip comments that trail code on the line above, e.g. `t("a"); // comment`
+        let line_start = self.source_text[..comment.span.start as usize]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let before = &self.source_text[line_start..comment.span.start as usize];
+        before
+            .chars()
+            .all(|c| c.is_whitespace() || c == '{')
+            .then_some(comment)
+    }
+}
+
+/// The text of a (JSDoc) comment without the leading `*` of every line, on a single line
+pub fn comment_description(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse an annotation such as `default: "Welcome back"` into its value
+///
+/// The value can be quoted with `"`, `'` or `` ` ``, otherwise the rest of the comment is used
+pub fn parse_annotation(text: &str, prefix: &str) -> Option<String> {
+    let value = text.trim().strip_prefix(prefix)?.trim();
+    let unquoted = ['"', '\'', '`'].iter().find_map(|&quote| {
+        value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+    });
+    Some(unquoted.unwrap_or(value).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxc::{allocator::Allocator, parser::Parser, span::SourceType};
+
+    #[test]
+    fn test_trailing_comments() {
+        let source = r#"
+            t('title') /* default: "Welcome back" */;
+            t('subtitle'); // default: Sign in
+            t('spaced');
+
+            // default: not trailing
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let comments = Comments::collect(&ret.program);
+
+        let call_span = |call: &str| {
+            let start = source.find(call).unwrap() as u32;
+            Span::new(start, start + call.len() as u32)
+        };
+        let trailing = |call: &str| {
+            comments
+                .trailing(call_span(call))
+                .map(|comment| comment.text.as_str())
+        };
+
+        assert_eq!(trailing("t('title')"), Some(r#" default: "Welcome back" "#));
+        assert_eq!(trailing("t('subtitle')"), Some(" default: Sign in"));
+        assert_eq!(trailing("t('spaced')"), None);
+    }
+
+    #[test]
+    fn test_leading_comments() {
+        let source = r#"
+            /** Shown on empty cart */
+            const empty = t('empty');
+            // Button label
+            const label = t('label'); // default: Save
+            const next = t('next');
+
+            {/* JSX comment */}
+            {t('jsx')}
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let comments = Comments::collect(&ret.program);
+
+        let leading = |call: &str| {
+            let start = source.find(call).unwrap() as u32;
+            comments
+                .leading(Span::new(start, start + call.len() as u32))
+                .map(|comment| comment_description(&comment.text))
+        };
+
+        assert_eq!(
+            leading("t('empty')").as_deref(),
+            Some("Shown on empty cart")
+        );
+        assert_eq!(leading("t('label')").as_deref(), Some("Button label"));
+        assert_eq!(leading("t('next')"), None);
+        assert_eq!(leading("t('jsx')").as_deref(), Some("JSX comment"));
+    }
+
+    #[test]
+    fn test_comment_description() {
+        assert_eq!(
+            comment_description("*\n * Shown when the cart\n * is empty\n "),
+            "Shown when the cart is empty"
+        );
+    }
+
+    #[test]
+    fn test_ignore_directives() {
+        let source = "t('a');\n// intl-extractor-ignore-next-line\nt('b');\nt('c');\n";
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let comments = Comments::collect(&ret.program);
+
+        let is_ignored = |call: &str| {
+            let start = source.find(call).unwrap() as u32;
+            comments.is_ignored(Span::new(start, start + call.len() as u32))
+        };
+        assert!(!is_ignored("t('a')"));
+        assert!(is_ignored("t('b')"));
+        assert!(!is_ignored("t('c')"));
+        assert!(!comments.is_file_ignored());
+
+        let source = "/* intl-extractor-ignore-file */\nt('a');";
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        assert!(Comments::collect(&ret.program).is_file_ignored());
+
+        let directives = Directives {
+            ignore_file: "i18n-skip-file".to_string(),
+            ..Directives::default()
+        };
+        assert!(!Comments::collect_with(&ret.program, &directives).is_file_ignored());
+        let source = "// i18n-skip-file\nt('a');";
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        assert!(Comments::collect_with(&ret.program, &directives).is_file_ignored());
+        assert!(directives.is_directive(" i18n-skip-file "));
+        assert!(directives.is_directive(" intl-extractor-keys: a, b"));
+        assert!(!directives.is_directive(" Shown on empty cart"));
+    }
+
+    #[test]
+    fn test_parse_annotation() {
+        let parse = |text| parse_annotation(text, DEFAULT_MESSAGE_PREFIX);
+        assert_eq!(
+            parse(r#" default: "Welcome back" "#),
+            Some("Welcome back".to_string())
+        );
+        assert_eq!(parse(" default: 'It''s me' "), Some("It''s me".to_string()));
+        assert_eq!(parse(" default: Sign in"), Some("Sign in".to_string()));
+        assert_eq!(parse(" TODO: translate"), None);
+    }
+}
@@ -0,0 +1,255 @@
+use oxc::ast::{
+    ast::{
+        BindingPatternKind, Expression, ObjectPropertyKind, Program, TSEnumDeclaration,
+        VariableDeclaration, VariableDeclarationKind,
+    },
+    visit::walk,
+    Visit,
+};
+use oxc::syntax::operator::BinaryOperator;
+use std::collections::HashMap;
+
+/// A value that is known at compile time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constant {
+    /// e.g. `const NS = "Checkout";`
+    String(String),
+    /// e.g. `const KEYS = { SUBMIT: "submit" } as const;`
+    Object(HashMap<String, Constant>),
+}
+
+impl Constant {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Constant::String(value) => Some(value),
+            Constant::Object(_) => None,
+        }
+    }
+
+    /// Get a property of an object constant
+    pub fn get(&self, key: &str) -> Option<&Constant> {
+        match self {
+            Constant::Object(properties) => properties.get(key),
+            Constant::String(_) => None,
+        }
+    }
+}
+
+/// Constants declared in (or imported into) a source file, e.g. `const NS = "Checkout";`
+///
+/// Used to resolve keys and namespaces that are passed as identifiers instead of literals
+#[derive(Debug, Default, Clone)]
+pub struct Constants {
+    values: HashMap<String, Constant>,
+}
+
+impl Constants {
+    /// Collect all `const` declarations with a static value from a program
+    ///
+    /// Constants are collected in declaration order, so a constant can be built from constants
+    /// that are declared before it
+    pub fn collect(program: &Program) -> Self {
+        Self::collect_with(program, Self::default())
+    }
+
+    /// Collect all `const` declarations on top of already known constants, e.g. imported ones
+    pub fn collect_with(program: &Program, constants: Constants) -> Self {
+        let mut collector = ConstantCollector { constants };
+        collector.visit_program(program);
+        collector.constants
+    }
+
+    pub fn insert(&mut self, name: String, value: Constant) {
+        self.values.insert(name, value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Constant> {
+        self.values.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Constant)> {
+        self.values.iter()
+    }
+
+    /// Convert all constants into a single object constant, e.g. for `import * as keys`
+    pub fn into_object(self) -> Constant {
+        Constant::Object(self.values)
+    }
+
+    /// Resolve an expression to a string that is known at compile time
+    pub fn resolve(&self, expr: &Expression) -> Option<String> {
+        match self.resolve_value(expr)? {
+            Constant::String(value) => Some(value),
+            Constant::Object(_) => None,
+        }
+    }
+
+    /// Resolve an expression to a value that is known at compile time
+    pub fn resolve_value(&self, expr: &Expression) -> Option<Constant> {
+        match expr {
+            Expression::StringLiteral(str_lit) => Some(Constant::String(str_lit.value.to_string())),
+            // Template literals with known substitutions, e.g. `useTranslations(`${BASE}.details`)`
+            Expression::TemplateLiteral(template) => {
+                let mut value = String::new();
+                for (index, quasi) in template.quasis.iter().enumerate() {
+                    value.push_str(quasi.value.cooked.as_deref().unwrap_or(&quasi.value.raw));
+                    if let Some(expr) = template.expressions.get(index) {
+                        value.push_str(&self.resolve(expr)?);
+                    }
+                }
+                Some(Constant::String(value))
+            }
+            // String concatenation, e.g. `t("form." + "submit")`
+            Expression::BinaryExpression(binary) if binary.operator == BinaryOperator::Addition => {
+                let left = self.resolve(&binary.left)?;
+                let right = self.resolve(&binary.right)?;
+                Some(Constant::String(left + &right))
+            }
+            // Known constants, e.g. `useTranslations(NS)`
+            Expression::Identifier(ident) => self.get(ident.name.as_str()).cloned(),
+            // Object literals, e.g. `{ SUBMIT: "submit" }`
+            Expression::ObjectExpression(obj) => {
+                let mut properties = HashMap::new();
+                for prop in &obj.properties {
+                    match prop {
+                        ObjectPropertyKind::ObjectProperty(prop) => {
+                            let key = prop.key.static_name();
+                            let value = self.resolve_value(&prop.value);
+                            if let (Some(key), Some(value)) = (key, value) {
+                                properties.insert(key.to_string(), value);
+                            }
+                        }
+                        ObjectPropertyKind::SpreadProperty(spread) => {
+                            if let Some(Constant::Object(spread)) =
+                                self.resolve_value(&spread.argument)
+                            {
+                                properties.extend(spread);
+                            }
+                        }
+                    }
+                }
+                Some(Constant::Object(properties))
+            }
+            // Property access on object constants, e.g. `t(KEYS.SUBMIT)`
+            Expression::StaticMemberExpression(member) => self
+                .resolve_value(&member.object)?
+                .get(member.property.name.as_str())
+                .cloned(),
+            Expression::ComputedMemberExpression(member) => {
+                let property = self.resolve(&member.expression)?;
+                self.resolve_value(&member.object)?.get(&property).cloned()
+            }
+            Expression::ParenthesizedExpression(paren) => self.resolve_value(&paren.expression),
+            // TypeScript assertions, e.g. `"Checkout" as const`
+            Expression::TSAsExpression(ts_as) => self.resolve_value(&ts_as.expression),
+            Expression::TSSatisfiesExpression(ts_satisfies) => {
+                self.resolve_value(&ts_satisfies.expression)
+            }
+            _ => None,
+        }
+    }
+}
+
+struct ConstantCollector {
+    constants: Constants,
+}
+
+impl<'a> Visit<'a> for ConstantCollector {
+    fn visit_variable_declaration(&mut self, it: &VariableDeclaration<'a>) {
+        if it.kind == VariableDeclarationKind::Const {
+            for decl in &it.declarations {
+                if let (BindingPatternKind::BindingIdentifier(ident), Some(init)) =
+                    (&decl.id.kind, &decl.init)
+                {
+                    if let Some(value) = self.constants.resolve_value(init) {
+                        self.constants.insert(ident.name.to_string(), value);
+                    }
+                }
+            }
+        }
+        walk::walk_variable_declaration(self, it);
+    }
+
+    /// TypeScript enums with string members, e.g. `enum Labels { Save = "save" }`
+    fn visit_ts_enum_declaration(&mut self, it: &TSEnumDeclaration<'a>) {
+        let mut members = HashMap::new();
+        for member in &it.members {
+            if let Some(value) = member
+                .initializer
+                .as_ref()
+                .and_then(|init| self.constants.resolve(init))
+            {
+                members.insert(member.id.static_name().to_string(), Constant::String(value));
+            }
+        }
+        self.constants
+            .insert(it.id.name.to_string(), Constant::Object(members));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxc::{allocator::Allocator, parser::Parser, span::SourceType};
+
+    fn get_str<'c>(constants: &'c Constants, name: &str) -> Option<&'c str> {
+        constants.get(name).and_then(Constant::as_str)
+    }
+
+    #[test]
+    fn test_collect_constants() {
+        let allocator = Allocator::default();
+        let source = r#"
+            const NS = "Checkout";
+            const NESTED = NS + ".details";
+            const TYPED = `Typed` as const;
+            const TEMPLATE = `${NS}.${NESTED}`;
+            const UNKNOWN = `${NS}.${unknown}`;
+            let mutable = "mutable";
+            const dynamic = getNamespace();
+
+            function Component() {
+                const LOCAL = "Local";
+            }
+        "#;
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let constants = Constants::collect(&ret.program);
+
+        assert_eq!(get_str(&constants, "NS"), Some("Checkout"));
+        assert_eq!(get_str(&constants, "NESTED"), Some("Checkout.details"));
+        assert_eq!(get_str(&constants, "TYPED"), Some("Typed"));
+        assert_eq!(
+            get_str(&constants, "TEMPLATE"),
+            Some("Checkout.Checkout.details")
+        );
+        assert_eq!(get_str(&constants, "UNKNOWN"), None);
+        assert_eq!(get_str(&constants, "LOCAL"), Some("Local"));
+        assert_eq!(get_str(&constants, "mutable"), None);
+        assert_eq!(get_str(&constants, "dynamic"), None);
+    }
+
+    #[test]
+    fn test_collect_object_constants() {
+        let allocator = Allocator::default();
+        let source = r#"
+            const KEYS = { SUBMIT: "submit", nested: { CANCEL: "cancel" } } as const;
+            const MORE = { ...KEYS, "quoted-key": "quoted" };
+            const SUBMIT = KEYS.SUBMIT;
+            const CANCEL = MORE["nested"].CANCEL;
+        "#;
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let constants = Constants::collect(&ret.program);
+
+        assert_eq!(get_str(&constants, "SUBMIT"), Some("submit"));
+        assert_eq!(get_str(&constants, "CANCEL"), Some("cancel"));
+        let more = constants.get("MORE").unwrap();
+        assert_eq!(
+            more.get("quoted-key").and_then(Constant::as_str),
+            Some("quoted")
+        );
+        assert_eq!(
+            more.get("SUBMIT").and_then(Constant::as_str),
+            Some("submit")
+        );
+    }
+}
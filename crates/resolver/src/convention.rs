@@ -0,0 +1,116 @@
+use std::path::{Component, Path};
+
+/// Template for namespaces derived from the file path, e.g. `app/cart/page.tsx` to `Cart`
+pub const DEFAULT_NAMESPACE_TEMPLATE: &str = "{path}";
+
+/// Directories that contain the routes or components, the path below them is used
+const ROOT_DIRECTORIES: [&str; 3] = ["app", "pages", "src"];
+
+/// Convert a file or directory name to PascalCase, e.g. `user-profile` to `UserProfile`
+fn pascal_case(name: &str) -> String {
+    name.split(['-', '_', '.', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Whether a directory is left out of the namespace: route groups such as `(shop)`, dynamic
+/// segments such as `[id]` and private folders such as `_components`
+fn is_skipped_directory(name: &str) -> bool {
+    (name.starts_with('(') && name.ends_with(')'))
+        || (name.starts_with('[') && name.ends_with(']'))
+        || name.starts_with('_')
+}
+
+/// Compute the namespace of a file from a template, for translators created without one
+///
+/// The template can contain these placeholders:
+///
+/// - `{path}`: the directories below the last `app`, `pages` or `src` directory, joined with
+///   `.`, e.g. `Cart.Checkout` for `app/(shop)/cart/checkout/page.tsx`
+/// - `{dir}`: the directory of the file, e.g. `Cart` for `app/cart/page.tsx`
+/// - `{file}`: the file name without extension, e.g. `CartSummary` for
+///   `components/cart-summary.tsx`
+///
+/// Names are converted to PascalCase. Returns `None` if a placeholder is empty, e.g. `{path}`
+/// for `app/page.tsx`
+pub fn namespace_from_path(template: &str, file_path: &Path) -> Option<String> {
+    let directories: Vec<&str> = file_path
+        .parent()?
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+
+    let below_root = directories
+        .iter()
+        .rposition(|dir| ROOT_DIRECTORIES.contains(dir))
+        .map_or(&directories[..], |index| &directories[index + 1..]);
+    let path = below_root
+        .iter()
+        .filter(|dir| !is_skipped_directory(dir))
+        .map(|dir| pascal_case(dir))
+        .collect::<Vec<_>>()
+        .join(".");
+    let dir = directories
+        .last()
+        .filter(|dir| !ROOT_DIRECTORIES.contains(dir))
+        .map(|dir| pascal_case(dir))
+        .unwrap_or_default();
+    let file = file_path
+        .file_stem()
+        .map(|stem| pascal_case(&stem.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut namespace = template.to_string();
+    for (placeholder, value) in [("{path}", path), ("{dir}", dir), ("{file}", file)] {
+        if namespace.contains(placeholder) {
+            if value.is_empty() {
+                return None;
+            }
+            namespace = namespace.replace(placeholder, &value);
+        }
+    }
+    Some(namespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace_from_path() {
+        let namespace = |template, path| namespace_from_path(template, Path::new(path));
+
+        assert_eq!(
+            namespace("{path}", "app/cart/page.tsx").as_deref(),
+            Some("Cart")
+        );
+        assert_eq!(
+            namespace(
+                "{path}",
+                "./src/app/(shop)/cart/[id]/order-details/page.tsx"
+            )
+            .as_deref(),
+            Some("Cart.OrderDetails")
+        );
+        assert_eq!(
+            namespace("Pages.{dir}", "app/user-profile/page.tsx").as_deref(),
+            Some("Pages.UserProfile")
+        );
+        assert_eq!(
+            namespace("{file}", "components/cart_summary.tsx").as_deref(),
+            Some("CartSummary")
+        );
+        assert_eq!(namespace("{path}", "app/page.tsx"), None);
+        assert_eq!(namespace("{dir}", "src/layout.tsx"), None);
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::{anyhow, bail, Error};
+use std::str::FromStr;
+
+/// How the namespace is passed to a custom translator factory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum NamespaceSignature {
+    /// Like next-intl: a string as the first argument or a `namespace` property of an object
+    /// as the first argument
+    #[default]
+    NextIntl,
+    /// A string as the argument at this position, e.g. `useAdminTranslations(locale, "ns")`
+    Argument(usize),
+    /// A property with this name of an object as the first argument, e.g. `createT({ ns })`
+    Property(String),
+    /// A template where `{0}`, `{1}` etc. are replaced with the string arguments at those
+    /// positions, e.g. `Admin.{0}`. A template without placeholders is a fixed namespace
+    Template(String),
+}
+
+/// A custom function that creates a translator, e.g. `useAppTranslations`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactorySignature {
+    pub name: String,
+    pub namespace: NamespaceSignature,
+}
+
+impl FactorySignature {
+    /// A factory that takes the namespace like next-intl does
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            namespace: NamespaceSignature::default(),
+        }
+    }
+
+    pub fn with_namespace(mut self, namespace: NamespaceSignature) -> Self {
+        self.namespace = namespace;
+        self
+    }
+}
+
+/// Parses `NAME` or `NAME=SIGNATURE`, where the signature is one of:
+///
+/// - `arg:INDEX` for [`NamespaceSignature::Argument`]
+/// - `prop:NAME` for [`NamespaceSignature::Property`]
+/// - `template:TEMPLATE` for [`NamespaceSignature::Template`]
+impl FromStr for FactorySignature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, signature) = match s.split_once('=') {
+            Some((name, signature)) => (name.trim(), Some(signature.trim())),
+            None => (s.trim(), None),
+        };
+        if name.is_empty() {
+            bail!("Missing factory name in '{}'", s);
+        }
+
+        let namespace = match signature.map(|signature| signature.split_once(':')) {
+            None => NamespaceSignature::NextIntl,
+            Some(Some(("arg", index))) => NamespaceSignature::Argument(
+                index
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid argument index '{}' in '{}'", index, s))?,
+            ),
+            Some(Some(("prop", property))) if !property.is_empty() => {
+                NamespaceSignature::Property(property.to_string())
+            }
+            Some(Some(("template", template))) => NamespaceSignature::Template(template.to_string()),
+            Some(_) => bail!(
+                "Invalid factory signature '{}', expected NAME=arg:INDEX, NAME=prop:NAME or NAME=template:TEMPLATE",
+                s
+            ),
+        };
+
+        Ok(Self::new(name).with_namespace(namespace))
+    }
+}
+
+/// Fill the `{0}`, `{1}` etc. placeholders of a namespace template
+///
+/// Returns `None` if a placeholder refers to an argument that is not a static string
+pub(crate) fn fill_template(template: &str, arguments: &[Option<String>]) -> Option<String> {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        let Ok(index) = rest[start + 1..end].parse::<usize>() else {
+            result.push_str(&rest[..=start]);
+            rest = &rest[start + 1..];
+            continue;
+        };
+        result.push_str(&rest[..start]);
+        result.push_str(arguments.get(index)?.as_deref()?);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_factory_signature() {
+        assert_eq!(
+            "useAppTranslations".parse::<FactorySignature>().unwrap(),
+            FactorySignature::new("useAppTranslations")
+        );
+        assert_eq!(
+            "useAdmin=arg:1".parse::<FactorySignature>().unwrap(),
+            FactorySignature::new("useAdmin").with_namespace(NamespaceSignature::Argument(1))
+        );
+        assert_eq!(
+            "createT = prop:ns".parse::<FactorySignature>().unwrap(),
+            FactorySignature::new("createT")
+                .with_namespace(NamespaceSignature::Property("ns".to_string()))
+        );
+        assert_eq!(
+            "useAdmin=template:Admin.{0}"
+                .parse::<FactorySignature>()
+                .unwrap(),
+            FactorySignature::new("useAdmin")
+                .with_namespace(NamespaceSignature::Template("Admin.{0}".to_string()))
+        );
+        assert!("=arg:0".parse::<FactorySignature>().is_err());
+        assert!("useAdmin=arg:first".parse::<FactorySignature>().is_err());
+        assert!("useAdmin=unknown".parse::<FactorySignature>().is_err());
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let arguments = [Some("Cart".to_string()), None];
+        assert_eq!(
+            fill_template("Admin.{0}", &arguments),
+            Some("Admin.Cart".to_string())
+        );
+        assert_eq!(
+            fill_template("Fixed", &arguments),
+            Some("Fixed".to_string())
+        );
+        assert_eq!(
+            fill_template("{a}.{0}", &arguments),
+            Some("{a}.Cart".to_string())
+        );
+        assert_eq!(fill_template("{1}", &arguments), None);
+        assert_eq!(fill_template("{2}", &arguments), None);
+    }
+}
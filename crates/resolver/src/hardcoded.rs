@@ -0,0 +1,237 @@
+use oxc::{
+    ast::{
+        ast::{
+            Expression, JSXAttribute, JSXAttributeName, JSXAttributeValue, JSXElement,
+            JSXElementName, JSXText, Program,
+        },
+        visit::walk,
+        Visit,
+    },
+    span::{GetSpan, Span},
+};
+use std::collections::HashSet;
+
+use crate::comments::{Comments, Directives};
+use crate::location::Location;
+
+/// Props that usually contain user-visible copy
+pub const TRANSLATABLE_ATTRIBUTES: [&str; 4] = ["title", "alt", "placeholder", "aria-label"];
+
+/// Elements with text content that is not user-visible copy
+const IGNORED_ELEMENTS: [&str; 3] = ["style", "script", "code"];
+
+/// Where a hard-coded string is used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HardcodedStringKind {
+    /// Text between JSX tags, e.g. `<p>Welcome back</p>`
+    JsxText,
+    /// A string passed to a translatable prop, e.g. `<img alt="Logo" />`
+    Attribute(String),
+}
+
+/// A user-visible string that is not translated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardcodedString {
+    /// The string with its whitespace collapsed
+    pub text: String,
+    pub kind: HardcodedStringKind,
+    /// Span of the text without surrounding whitespace, or of the attribute value
+    pub span: Span,
+    pub location: Location,
+}
+
+/// Finds user-visible strings in JSX that are not wrapped in a translator call
+#[derive(Debug)]
+pub struct HardcodedStringVisitor {
+    attributes: HashSet<String>,
+    comments: Comments,
+    directives: Directives,
+    source_text: String,
+    strings: Vec<HardcodedString>,
+}
+
+impl HardcodedStringVisitor {
+    pub fn new() -> Self {
+        Self {
+            attributes: TRANSLATABLE_ATTRIBUTES
+                .iter()
+                .map(|attribute| attribute.to_string())
+                .collect(),
+            comments: Comments::default(),
+            directives: Directives::default(),
+            source_text: String::new(),
+            strings: Vec::new(),
+        }
+    }
+
+    /// Use other ignore directives than the default ones
+    pub fn with_directives(mut self, directives: Directives) -> Self {
+        self.directives = directives;
+        self
+    }
+
+    /// The hard-coded strings that were found, in source order
+    pub fn strings(&self) -> &[HardcodedString] {
+        &self.strings
+    }
+
+    fn push(&mut self, text: &str, kind: HardcodedStringKind, span: Span) {
+        if !is_user_visible(text) || self.comments.is_ignored(span) {
+            return;
+        }
+        self.strings.push(HardcodedString {
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            kind,
+            span,
+            location: Location::from_offset(&self.source_text, span.start),
+        });
+    }
+}
+
+impl Default for HardcodedStringVisitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Visit<'a> for HardcodedStringVisitor {
+    fn visit_program(&mut self, it: &Program<'a>) {
+        self.comments = Comments::collect_with(it, &self.directives);
+        self.source_text = it.source_text.to_string();
+        if self.comments.is_file_ignored() {
+            return;
+        }
+        walk::walk_program(self, it);
+    }
+
+    fn visit_jsx_element(&mut self, it: &JSXElement<'a>) {
+        if let JSXElementName::Identifier(ident) = &it.opening_element.name {
+            if IGNORED_ELEMENTS.contains(&ident.name.as_str()) {
+                return;
+            }
+        }
+        walk::walk_jsx_element(self, it);
+    }
+
+    fn visit_jsx_text(&mut self, it: &JSXText<'a>) {
+        let text = it.value.as_str();
+        let trimmed = text.trim_start();
+        let start = it.span.start + (text.len() - trimmed.len()) as u32;
+        let trimmed = trimmed.trim_end();
+        let span = Span::new(start, start + trimmed.len() as u32);
+        self.push(trimmed, HardcodedStringKind::JsxText, span);
+    }
+
+    fn visit_jsx_attribute(&mut self, it: &JSXAttribute<'a>) {
+        let JSXAttributeName::Identifier(name) = &it.name else {
+            walk::walk_jsx_attribute(self, it);
+            return;
+        };
+        if !self.attributes.contains(name.name.as_str()) {
+            walk::walk_jsx_attribute(self, it);
+            return;
+        }
+
+        let string = match &it.value {
+            // `alt="Logo"`
+            Some(JSXAttributeValue::StringLiteral(lit)) => Some((lit.value.as_str(), lit.span)),
+            // `alt={"Logo"}` or ``alt={`Logo`}``
+            Some(JSXAttributeValue::ExpressionContainer(container)) => {
+                match container.expression.as_expression() {
+                    Some(Expression::StringLiteral(lit)) => Some((lit.value.as_str(), lit.span)),
+                    Some(Expression::TemplateLiteral(lit)) if lit.expressions.is_empty() => lit
+                        .quasis
+                        .first()
+                        .map(|quasi| (quasi.value.raw.as_str(), lit.span())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match string {
+            Some((text, span)) => {
+                let kind = HardcodedStringKind::Attribute(name.name.to_string());
+                self.push(text, kind, span);
+            }
+            None => walk::walk_jsx_attribute(self, it),
+        }
+    }
+}
+
+/// Whether a string looks like copy rather than e.g. punctuation, numbers, whitespace or HTML
+/// entities such as `&nbsp;`
+fn is_user_visible(text: &str) -> bool {
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| c.is_alphabetic() || c == '&') {
+        rest = &rest[start..];
+        let entity_end = rest.strip_prefix('&').and_then(|entity| {
+            let end = entity.find(';')?;
+            entity[..end]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '#')
+                .then_some(end + 2)
+        });
+        match entity_end {
+            Some(end) => rest = &rest[end..],
+            None if rest.starts_with('&') => rest = &rest[1..],
+            None => return true,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxc::{allocator::Allocator, parser::Parser, span::SourceType};
+
+    #[test]
+    fn test_hardcoded_strings() {
+        let source = r#"
+            export function Component() {
+                const t = useTranslations('Component');
+                return (
+                    <div title="Tooltip" className="container">
+                        <h1>  Welcome
+                            back </h1>
+                        <p>{t('translated')}</p>
+                        <img alt={`Logo`} src="/logo.png" />
+                        <input placeholder={t('placeholder')} />
+                        <span>{' - '} 42 &nbsp;</span>
+                        <style>{'.a { color: red }'}</style>
+                        <code>npm install</code>
+                        {/* intl-extractor-ignore-next-line */}
+                        <p>Ignored</p>
+                    </div>
+                );
+            }
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = HardcodedStringVisitor::new();
+        visitor.visit_program(&ret.program);
+
+        let strings = visitor.strings();
+        assert_eq!(strings.len(), 3);
+        assert_eq!(strings[0].text, "Tooltip");
+        assert_eq!(
+            strings[0].kind,
+            HardcodedStringKind::Attribute("title".to_string())
+        );
+        assert_eq!(strings[0].span.source_text(source), "\"Tooltip\"");
+        assert_eq!(strings[1].text, "Welcome back");
+        assert_eq!(strings[1].kind, HardcodedStringKind::JsxText);
+        assert!(strings[1].span.source_text(source).starts_with("Welcome"));
+        assert!(strings[1].span.source_text(source).ends_with("back"));
+        assert_eq!(strings[1].location.line, 6);
+        assert_eq!(strings[2].text, "Logo");
+    }
+
+    #[test]
+    fn test_is_user_visible() {
+        assert!(is_user_visible("Welcome"));
+        assert!(is_user_visible("&copy; Company"));
+        assert!(!is_user_visible(" - 42 "));
+        assert!(!is_user_visible("&nbsp;&amp;"));
+    }
+}
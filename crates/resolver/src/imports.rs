@@ -0,0 +1,322 @@
+use log::debug;
+use oxc::{
+    allocator::Allocator,
+    ast::ast::{Declaration, ImportDeclarationSpecifier, ModuleDeclaration, Program, Statement},
+    parser::Parser,
+    span::SourceType,
+};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::constants::Constants;
+use crate::tsconfig::PathAliases;
+
+/// Extensions that are tried when an import specifier doesn't include one
+const MODULE_EXTENSIONS: [&str; 6] = ["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+#[derive(Debug)]
+struct CachedModule {
+    modified: Option<SystemTime>,
+    exports: Rc<Constants>,
+}
+
+/// Follows imports to resolve constants that are declared in other files
+///
+/// e.g. `import { KEYS } from "./keys";` followed by `t(KEYS.SUBMIT)`
+///
+/// Only relative imports and the configured path aliases are followed. The exports of every module are cached until the module
+/// is modified on disk
+#[derive(Debug, Default)]
+pub struct ImportResolver {
+    cache: RefCell<HashMap<PathBuf, CachedModule>>,
+    /// Modules that are currently being resolved, to guard against circular imports
+    in_progress: RefCell<HashSet<PathBuf>>,
+    aliases: PathAliases,
+}
+
+impl ImportResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also follow imports through path aliases, e.g. `@/lib/i18n` with `@/*` mapped to `./src/*`
+    pub fn with_path_aliases(mut self, aliases: PathAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Resolve the constants imported by a program, keyed by their local name
+    pub fn resolve_imports(&self, program: &Program, file_path: &Path) -> Constants {
+        let mut constants = Constants::default();
+
+        for statement in &program.body {
+            let Statement::ImportDeclaration(import) = statement else {
+                continue;
+            };
+            let Some(specifiers) = &import.specifiers else {
+                continue;
+            };
+            let Some(exports) = self.module_exports_from(file_path, import.source.value.as_str())
+            else {
+                continue;
+            };
+
+            for specifier in specifiers {
+                let (local, value) = match specifier {
+                    ImportDeclarationSpecifier::ImportSpecifier(specifier) => (
+                        &specifier.local,
+                        exports.get(specifier.imported.name().as_str()).cloned(),
+                    ),
+                    ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                        (&specifier.local, exports.get("default").cloned())
+                    }
+                    ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => (
+                        &specifier.local,
+                        Some(exports.as_ref().clone().into_object()),
+                    ),
+                };
+                if let Some(value) = value {
+                    constants.insert(local.name.to_string(), value);
+                }
+            }
+        }
+
+        constants
+    }
+
+    /// Get the exported constants of the module imported with `specifier` from `file_path`
+    fn module_exports_from(&self, file_path: &Path, specifier: &str) -> Option<Rc<Constants>> {
+        let module_path = self.resolve_specifier(file_path, specifier)?;
+        self.module_exports(&module_path)
+    }
+
+    /// Get the exported constants of a module
+    fn module_exports(&self, path: &Path) -> Option<Rc<Constants>> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let Some(cached) = self.cache.borrow().get(path) {
+            if cached.modified == modified {
+                return Some(Rc::clone(&cached.exports));
+            }
+        }
+
+        if !self.in_progress.borrow_mut().insert(path.to_path_buf()) {
+            debug!("Skipping circular import of {:?}", path);
+            return None;
+        }
+        let exports = self.parse_module_exports(path);
+        self.in_progress.borrow_mut().remove(path);
+
+        let exports = Rc::new(exports?);
+        self.cache.borrow_mut().insert(
+            path.to_path_buf(),
+            CachedModule {
+                modified,
+                exports: Rc::clone(&exports),
+            },
+        );
+        Some(exports)
+    }
+
+    fn parse_module_exports(&self, path: &Path) -> Option<Constants> {
+        let source_text = std::fs::read_to_string(path).ok()?;
+        let source_type = SourceType::from_path(path).ok()?;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, &source_text, source_type).parse();
+        let program = ret.program;
+
+        let imported = self.resolve_imports(&program, path);
+        let constants = Constants::collect_with(&program, imported);
+        let mut exports = Constants::default();
+
+        for statement in &program.body {
+            let Some(module_declaration) = statement.as_module_declaration() else {
+                continue;
+            };
+            match module_declaration {
+                // `export const KEYS = {}` and `export { KEYS, NS as NAMESPACE }`
+                ModuleDeclaration::ExportNamedDeclaration(export) => {
+                    let names = match &export.declaration {
+                        Some(Declaration::VariableDeclaration(declaration)) => declaration
+                            .declarations
+                            .iter()
+                            .filter_map(|decl| decl.id.get_binding_identifier())
+                            .map(|ident| ident.name.as_str())
+                            .collect(),
+                        // `export enum Status {}`
+                        Some(Declaration::TSEnumDeclaration(declaration)) => {
+                            vec![declaration.id.name.as_str()]
+                        }
+                        _ => Vec::new(),
+                    };
+                    for name in names {
+                        if let Some(value) = constants.get(name) {
+                            exports.insert(name.to_string(), value.clone());
+                        }
+                    }
+
+                    // Re-exports, e.g. `export { KEYS } from "./keys"`
+                    let source_exports = match &export.source {
+                        Some(source) => self.module_exports_from(path, source.value.as_str()),
+                        None => None,
+                    };
+                    for specifier in &export.specifiers {
+                        let local = specifier.local.name();
+                        let value = match &export.source {
+                            Some(_) => source_exports
+                                .as_ref()
+                                .and_then(|exports| exports.get(local.as_str())),
+                            None => constants.get(local.as_str()),
+                        };
+                        if let Some(value) = value {
+                            exports.insert(specifier.exported.name().to_string(), value.clone());
+                        }
+                    }
+                }
+                // `export * from "./keys"` and `export * as keys from "./keys"`
+                ModuleDeclaration::ExportAllDeclaration(export) => {
+                    let Some(source_exports) =
+                        self.module_exports_from(path, export.source.value.as_str())
+                    else {
+                        continue;
+                    };
+                    match &export.exported {
+                        Some(name) => exports.insert(
+                            name.name().to_string(),
+                            source_exports.as_ref().clone().into_object(),
+                        ),
+                        None => {
+                            for (name, value) in source_exports.iter() {
+                                exports.insert(name.clone(), value.clone());
+                            }
+                        }
+                    }
+                }
+                // `export default { SUBMIT: "submit" }`
+                ModuleDeclaration::ExportDefaultDeclaration(export) => {
+                    if let Some(value) = export
+                        .declaration
+                        .as_expression()
+                        .and_then(|expr| constants.resolve_value(expr))
+                    {
+                        exports.insert("default".to_string(), value);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(exports)
+    }
+
+    /// Resolve a relative or aliased import specifier to a file on disk
+    ///
+    /// Tries the path as is, with any of the [`MODULE_EXTENSIONS`] and as a directory index file
+    fn resolve_specifier(&self, from: &Path, specifier: &str) -> Option<PathBuf> {
+        if !specifier.starts_with("./") && !specifier.starts_with("../") {
+            return self
+                .aliases
+                .resolve(specifier)
+                .iter()
+                .find_map(|base| resolve_module_path(base));
+        }
+        let base = from.parent().unwrap_or(Path::new("")).join(specifier);
+        resolve_module_path(&base)
+    }
+}
+
+fn resolve_module_path(base: &Path) -> Option<PathBuf> {
+    if base.is_file() {
+        return Some(base.to_path_buf());
+    }
+    let file_name = base.file_name()?.to_string_lossy();
+    for extension in MODULE_EXTENSIONS {
+        let candidate = base.with_file_name(format!("{}.{}", file_name, extension));
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+    }
+    for extension in MODULE_EXTENSIONS {
+        let candidate = base.join(format!("index.{}", extension));
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::Constant;
+    use std::fs;
+
+    fn resolve_file(resolver: &ImportResolver, path: &Path) -> Constants {
+        let source_text = fs::read_to_string(path).unwrap();
+        let allocator = Allocator::default();
+        let ret = Parser::new(
+            &allocator,
+            &source_text,
+            SourceType::from_path(path).unwrap(),
+        )
+        .parse();
+        resolver.resolve_imports(&ret.program, path)
+    }
+
+    #[test]
+    fn test_resolve_imported_constants() {
+        let dir = std::env::temp_dir().join(format!("imports-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("i18n")).unwrap();
+        fs::write(
+            dir.join("i18n/keys.ts"),
+            r#"
+            const PREFIX = "form.";
+            export const KEYS = { SUBMIT: PREFIX + "submit" } as const;
+            const CART = "Cart";
+            export { CART as CART_NAMESPACE };
+            export default { HOME: "Home" };
+            export enum Status { Active = "active" }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("i18n/index.ts"),
+            r#"export * from "./keys"; export * as keys from "./keys";"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("component.tsx"),
+            r#"
+            import { KEYS, CART_NAMESPACE as NS, Status } from "./i18n";
+            import * as all from "./i18n/keys";
+            import NAMESPACES from "./i18n/keys.ts";
+            import { Missing } from "./missing";
+            import { useTranslations } from "next-intl";
+            "#,
+        )
+        .unwrap();
+
+        let resolver = ImportResolver::new();
+        let constants = resolve_file(&resolver, &dir.join("component.tsx"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let submit = constants.get("KEYS").and_then(|keys| keys.get("SUBMIT"));
+        assert_eq!(submit.and_then(Constant::as_str), Some("form.submit"));
+        assert_eq!(constants.get("NS").and_then(Constant::as_str), Some("Cart"));
+        let all = constants.get("all").unwrap();
+        assert_eq!(
+            all.get("CART_NAMESPACE").and_then(Constant::as_str),
+            Some("Cart")
+        );
+        let home = constants.get("NAMESPACES").and_then(|ns| ns.get("HOME"));
+        assert_eq!(home.and_then(Constant::as_str), Some("Home"));
+        let active = constants
+            .get("Status")
+            .and_then(|status| status.get("Active"));
+        assert_eq!(active.and_then(Constant::as_str), Some("active"));
+        assert!(constants.get("Missing").is_none());
+        assert!(constants.get("useTranslations").is_none());
+    }
+}
@@ -1,27 +1,589 @@
+pub mod codemod;
+pub mod comments;
+pub mod constants;
+pub mod convention;
+pub mod factory;
+pub mod hardcoded;
+pub mod imports;
+pub mod location;
+pub mod misuse;
+pub mod options;
+pub mod plugin;
+pub mod provider;
+pub mod transform;
+pub mod tsconfig;
 pub mod visitor;
 
-use anyhow::Result;
-use oxc::{allocator::Allocator, ast::Visit, parser::Parser, span::SourceType};
-use std::collections::{HashMap, HashSet};
+use anyhow::{anyhow, Result};
+use oxc::{
+    allocator::Allocator,
+    ast::Visit,
+    parser::Parser,
+    span::{SourceType, Span},
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
+use crate::convention::namespace_from_path;
+use crate::hardcoded::{HardcodedString, HardcodedStringVisitor};
+use crate::imports::ImportResolver;
+use crate::location::{LineIndex, Location};
+use crate::misuse::{ImportMisuse, ImportMisuseVisitor};
+use crate::options::{DynamicKeyPolicy, ExtractorOptions};
+use crate::plugin::{ExtractorPlugin, PluginContext};
+use crate::provider::{is_client_component, ProvidedMessages, ProviderVisitor};
+use crate::transform::Transforms;
+use crate::tsconfig::PathAliases;
 use crate::visitor::TranslationFunctionVisitor;
 
-pub fn extract_translations(file_path: &Path) -> Result<HashMap<String, HashSet<String>>> {
-    let source_text = std::fs::read_to_string(file_path)?;
-    let allocator = Allocator::default();
-    let source_type = SourceType::from_path(file_path).unwrap();
-    let ret = Parser::new(&allocator, &source_text, source_type).parse();
+/// Messages extracted from a single source file
+#[derive(Debug, Clone, Default)]
+pub struct Extraction {
+    /// Namespaces with the set of keys used in them
+    pub translations: HashMap<String, HashSet<String>>,
+    /// Every usage of a static key with its location, in source order
+    pub usages: Vec<Usage>,
+    /// Syntax errors in the file, the messages are extracted from the parts that could be parsed
+    pub parse_errors: Vec<ParseError>,
+    /// Translator calls with keys that can't be resolved statically
+    pub dynamic_keys: Vec<DynamicKey>,
+    /// Translator factory calls with namespaces that can't be resolved statically, their keys
+    /// are not extracted
+    pub unresolved_namespaces: Vec<Location>,
+    /// Translator factory calls with the namespaces they resolve to, as written in the file
+    pub translator_namespaces: Vec<NamespaceUsage>,
+    /// Default messages by namespace and key, e.g. `t("title") /* default: "Welcome back" */`
+    pub default_messages: HashMap<String, HashMap<String, String>>,
+    /// Descriptions for translators by namespace and key, from the comment above the usage
+    pub descriptions: HashMap<String, HashMap<String, String>>,
+    /// Names of the values passed to the usages by namespace and key, e.g. `name` for
+    /// `t("greeting", { name })`
+    pub placeholders: HashMap<String, HashMap<String, BTreeSet<String>>>,
+    /// Names of the rich text tags passed to the usages by namespace and key, e.g. `link` for
+    /// `t.rich("terms", { link: (chunks) => <a>{chunks}</a> })`, they're placeholders too
+    pub tags: HashMap<String, HashMap<String, BTreeSet<String>>>,
+    /// User-visible strings in JSX that are not translated, if enabled
+    pub hardcoded_strings: Vec<HardcodedString>,
+    /// Whether the file is a client component, i.e. starts with `"use client"`
+    pub client_component: bool,
+    /// Calls that pull in the whole message catalog, e.g. `useMessages()` or `getMessages()`
+    pub catalog_usages: Vec<CatalogUsage>,
+    /// next-intl functions imported from the wrong module or used where they fail at runtime
+    pub import_misuses: Vec<ImportMisuse>,
+    /// Messages passed to a `NextIntlClientProvider` in the file
+    pub provided_messages: Vec<ProvidedMessages>,
+    /// Problems reported by the plugins
+    pub plugin_warnings: Vec<PluginWarning>,
+}
+
+impl Extraction {
+    /// Apply the namespace and key transformations to all extracted messages
+    pub fn transform(self, transforms: &Transforms) -> Self {
+        if transforms.is_empty() {
+            return self;
+        }
+
+        let mut translations: HashMap<String, HashSet<String>> = HashMap::new();
+        for (namespace, keys) in self.translations {
+            translations
+                .entry(transforms.namespace(&namespace))
+                .or_default()
+                .extend(keys.iter().map(|key| transforms.key(key)));
+        }
+        fn transform_values<T>(
+            values: HashMap<String, HashMap<String, T>>,
+            transforms: &Transforms,
+        ) -> HashMap<String, HashMap<String, T>> {
+            let mut result: HashMap<String, HashMap<String, T>> = HashMap::new();
+            for (namespace, messages) in values {
+                result
+                    .entry(transforms.namespace(&namespace))
+                    .or_default()
+                    .extend(
+                        messages
+                            .into_iter()
+                            .map(|(key, value)| (transforms.key(&key), value)),
+                    );
+            }
+            result
+        }
+
+        Self {
+            translations,
+            usages: self
+                .usages
+                .into_iter()
+                .map(|usage| Usage {
+                    namespace: transforms.namespace(&usage.namespace),
+                    key: transforms.key(&usage.key),
+                    ..usage
+                })
+                .collect(),
+            parse_errors: self.parse_errors,
+            dynamic_keys: self
+                .dynamic_keys
+                .into_iter()
+                .map(|usage| DynamicKey {
+                    namespace: transforms.namespace(&usage.namespace),
+                    ..usage
+                })
+                .collect(),
+            default_messages: transform_values(self.default_messages, transforms),
+            descriptions: transform_values(self.descriptions, transforms),
+            placeholders: transform_values(self.placeholders, transforms),
+            tags: transform_values(self.tags, transforms),
+            hardcoded_strings: self.hardcoded_strings,
+            unresolved_namespaces: self.unresolved_namespaces,
+            translator_namespaces: self.translator_namespaces,
+            catalog_usages: self.catalog_usages,
+            import_misuses: self.import_misuses,
+            client_component: self.client_component,
+            provided_messages: self
+                .provided_messages
+                .into_iter()
+                .map(|provided| ProvidedMessages {
+                    namespaces: provided.namespaces.map(|namespaces| {
+                        namespaces
+                            .iter()
+                            .map(|namespace| transforms.namespace(namespace))
+                            .collect()
+                    }),
+                    ..provided
+                })
+                .collect(),
+            plugin_warnings: self.plugin_warnings,
+        }
+    }
+}
+
+/// A message key that is used in a source file, e.g. `t("title")`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    pub namespace: String,
+    pub key: String,
+    /// Name of the translator the key is passed to, e.g. `t`, or the component prop, e.g.
+    /// `Button.labelKey`
+    pub translator: String,
+    /// Span of the key in the source text
+    pub span: Span,
+    pub location: Location,
+}
+
+/// A translator created with a namespace, e.g. `useTranslations("Cart")`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceUsage {
+    pub namespace: String,
+    /// Span of the literal the namespace is written in, `None` if it can't be rewritten, e.g.
+    /// for a constant
+    pub literal: Option<Span>,
+    /// Location of the translator factory call
+    pub location: Location,
+}
+
+/// A problem reported by a plugin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginWarning {
+    /// Name of the plugin
+    pub plugin: String,
+    pub message: String,
+    pub location: Location,
+}
+
+/// A syntax error in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    /// Where the error is, if the parser reported it
+    pub location: Option<Location>,
+}
+
+/// A call that pulls in the whole message catalog, e.g. `const messages = useMessages()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogUsage {
+    /// Name of the next-intl function that is called, e.g. `useMessages`
+    pub function: String,
+    pub location: Location,
+}
+
+/// A dynamic key usage, e.g. `t(someVariable)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicKey {
+    pub namespace: String,
+    pub location: Location,
+}
+
+/// Extracts messages from source files
+#[derive(Debug, Default)]
+pub struct Extractor {
+    import_resolver: Option<ImportResolver>,
+    options: ExtractorOptions,
+    find_hardcoded_strings: bool,
+    namespace_template: Option<String>,
+    transforms: Transforms,
+    plugins: Vec<Box<dyn ExtractorPlugin>>,
+}
+
+impl Extractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follow relative imports to resolve namespaces and keys declared as constants in other
+    /// files, e.g. `t(KEYS.SUBMIT)` with `KEYS` imported from `./keys`
+    pub fn with_import_resolution(mut self) -> Self {
+        self.import_resolver = Some(ImportResolver::new());
+        self
+    }
+
+    /// Follow imports through path aliases too, e.g. `@/lib/i18n` with the `paths` of a
+    /// `tsconfig.json`. Enables import resolution
+    pub fn with_path_aliases(mut self, aliases: PathAliases) -> Self {
+        let resolver = self.import_resolver.take().unwrap_or_default();
+        self.import_resolver = Some(resolver.with_path_aliases(aliases));
+        self
+    }
+
+    /// Recognize translators and their keys as configured in the options
+    pub fn with_options(mut self, options: ExtractorOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Also look for user-visible strings in JSX that are not wrapped in a translator call
+    pub fn with_hardcoded_string_detection(mut self) -> Self {
+        self.find_hardcoded_strings = true;
+        self
+    }
+
+    /// Derive the namespace of translators created without one from the file path, e.g.
+    /// `useTranslations()` in `app/cart/page.tsx` with the template `{path}` gets `Cart`
+    ///
+    /// See [`namespace_from_path`] for the placeholders of the template
+    pub fn with_path_namespaces(mut self, template: impl Into<String>) -> Self {
+        self.namespace_template = Some(template.into());
+        self
+    }
+
+    /// Transform the extracted namespaces and keys before they are merged, e.g. to kebab-case
+    pub fn with_transforms(mut self, transforms: Transforms) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    /// Run a plugin alongside the translator visitor, its keys are merged with the extracted ones
+    pub fn with_plugin(mut self, plugin: impl ExtractorPlugin + 'static) -> Self {
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    /// The namespace and key transformations applied to the extracted messages
+    pub fn transforms(&self) -> &Transforms {
+        &self.transforms
+    }
+
+    pub fn extract(&self, file_path: &Path) -> Result<Extraction> {
+        let source_text = std::fs::read_to_string(file_path)?;
+        self.extract_with_source(file_path, &source_text)
+    }
+
+    /// Extract the messages of a file from another source text than the one on disk, e.g. the
+    /// rewritten one of a dry run
+    pub fn extract_with_source(&self, file_path: &Path, source_text: &str) -> Result<Extraction> {
+        let source_type =
+            SourceType::from_path(file_path).map_err(|error| anyhow!("{}", &*error))?;
+        self.extract_source(source_text, source_type, Some(file_path))
+    }
 
-    for error in ret.errors {
-        let error = error.with_source_code(source_text.clone());
-        println!("{error:?}");
+    /// Extract messages from source text that is not read from the filesystem, e.g. in an editor
+    ///
+    /// Imports can't be resolved and namespaces can't be derived from the file path
+    pub fn extract_from_source(
+        &self,
+        source_text: &str,
+        source_type: SourceType,
+    ) -> Result<Extraction> {
+        self.extract_source(source_text, source_type, None)
     }
 
-    let program = ret.program;
+    fn extract_source(
+        &self,
+        source_text: &str,
+        source_type: SourceType,
+        file_path: Option<&Path>,
+    ) -> Result<Extraction> {
+        let allocator = Allocator::default();
+        let line_index = LineIndex::new(source_text);
+        let parser = &self.options.parser;
+        let ret = Parser::new(&allocator, source_text, parser.source_type(source_type))
+            .with_options(parser.parse_options())
+            .parse();
+
+        // The parser recovers from most syntax errors, so the rest of the file is still visited
+        let parse_errors = ret
+            .errors
+            .iter()
+            .map(|error| ParseError {
+                message: error.message.to_string(),
+                location: error
+                    .labels
+                    .iter()
+                    .flatten()
+                    .next()
+                    .map(|label| line_index.location(label.offset() as u32)),
+            })
+            .collect();
+
+        let program = ret.program;
+
+        let mut translation_function_visitor =
+            TranslationFunctionVisitor::new().with_options(self.options.clone());
+        if let Some(namespace) = self
+            .namespace_template
+            .as_deref()
+            .zip(file_path)
+            .and_then(|(template, file_path)| namespace_from_path(template, file_path))
+        {
+            translation_function_visitor =
+                translation_function_visitor.with_path_namespace(namespace);
+        }
+        if let Some((import_resolver, file_path)) = self.import_resolver.as_ref().zip(file_path) {
+            translation_function_visitor = translation_function_visitor
+                .with_constants(import_resolver.resolve_imports(&program, file_path));
+        }
+        translation_function_visitor.visit_program(&program);
+
+        let dynamic_keys = translation_function_visitor
+            .dynamic_keys()
+            .iter()
+            .map(|usage| DynamicKey {
+                namespace: usage.namespace.clone(),
+                location: line_index.location(usage.span.start),
+            })
+            .collect::<Vec<_>>();
+        if self.options.dynamic_key_policy == DynamicKeyPolicy::Error {
+            if let Some(dynamic_key) = dynamic_keys.first() {
+                return Err(anyhow!(
+                    "Dynamic key in namespace '{}' can't be extracted at {}",
+                    dynamic_key.namespace,
+                    dynamic_key.location
+                ));
+            }
+        }
+
+        let mut translations = translation_function_visitor.merge_by_namespace();
+        let mut usages: Vec<_> = translation_function_visitor
+            .key_usages()
+            .iter()
+            .map(|usage| Usage {
+                namespace: usage.namespace.clone(),
+                key: usage.key.clone(),
+                translator: usage.translator.clone(),
+                span: usage.span,
+                location: line_index.location(usage.span.start),
+            })
+            .collect();
 
-    let mut translation_function_visitor = TranslationFunctionVisitor::new();
-    translation_function_visitor.visit_program(&program);
+        let mut plugin_warnings = Vec::new();
+        let context = PluginContext {
+            source_text,
+            file_path,
+        };
+        for plugin in &self.plugins {
+            let output = plugin.extract(&program, context);
+            for key in output.keys {
+                translations
+                    .entry(key.namespace.clone())
+                    .or_default()
+                    .insert(key.key.clone());
+                usages.push(Usage {
+                    namespace: key.namespace,
+                    key: key.key,
+                    translator: plugin.name().to_string(),
+                    span: key.span,
+                    location: line_index.location(key.span.start),
+                });
+            }
+            plugin_warnings.extend(output.diagnostics.into_iter().map(|diagnostic| {
+                PluginWarning {
+                    plugin: plugin.name().to_string(),
+                    message: diagnostic.message,
+                    location: line_index.location(diagnostic.span.start),
+                }
+            }));
+        }
+        usages.sort_by_key(|usage| usage.span.start);
 
-    Ok(translation_function_visitor.merge_by_namespace())
+        let catalog_usages = translation_function_visitor
+            .catalog_usages()
+            .iter()
+            .map(|usage| CatalogUsage {
+                function: usage.function.clone(),
+                location: line_index.location(usage.span.start),
+            })
+            .collect();
+
+        let hardcoded_strings = if self.find_hardcoded_strings {
+            let mut hardcoded_string_visitor =
+                HardcodedStringVisitor::new().with_directives(self.options.directives.clone());
+            hardcoded_string_visitor.visit_program(&program);
+            hardcoded_string_visitor.strings().to_vec()
+        } else {
+            Vec::new()
+        };
+
+        let mut provider_visitor = ProviderVisitor::new();
+        provider_visitor.visit_program(&program);
+        let mut import_misuse_visitor = ImportMisuseVisitor::new();
+        import_misuse_visitor.visit_program(&program);
+
+        Ok(Extraction {
+            translations,
+            usages,
+            parse_errors,
+            dynamic_keys,
+            unresolved_namespaces: translation_function_visitor
+                .unresolved_namespaces()
+                .iter()
+                .map(|span| line_index.location(span.start))
+                .collect(),
+            translator_namespaces: translation_function_visitor
+                .translator_namespaces()
+                .iter()
+                .map(|translator| NamespaceUsage {
+                    namespace: translator.namespace.clone(),
+                    literal: translator.literal,
+                    location: line_index.location(translator.span.start),
+                })
+                .collect(),
+            default_messages: translation_function_visitor.default_messages().clone(),
+            descriptions: translation_function_visitor.descriptions().clone(),
+            placeholders: translation_function_visitor.placeholders().clone(),
+            tags: translation_function_visitor.tags().clone(),
+            hardcoded_strings,
+            catalog_usages,
+            import_misuses: import_misuse_visitor.misuses().to_vec(),
+            client_component: is_client_component(&program),
+            provided_messages: provider_visitor.provided_messages().to_vec(),
+            plugin_warnings,
+        }
+        .transform(&self.transforms))
+    }
+}
+
+pub fn extract_translations(file_path: &Path) -> Result<Extraction> {
+    Extractor::new().extract(file_path)
+}
+
+/// Extract messages from source text, without touching the filesystem
+pub fn extract_translations_from_source(
+    source_text: &str,
+    source_type: SourceType,
+) -> Result<Extraction> {
+    Extractor::new().extract_from_source(source_text, source_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::ParserOptions;
+
+    #[test]
+    fn test_extract_translations_from_source() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+
+            export default function () {
+                const t = useTranslations('Cart');
+                return <p>{t('title')} {t(key)}</p>;
+            }
+        "#;
+        let extraction = extract_translations_from_source(source, SourceType::tsx()).unwrap();
+
+        assert_eq!(
+            extraction.translations["Cart"],
+            HashSet::from(["title".to_string()])
+        );
+        assert_eq!(extraction.usages[0].location.to_string(), "6:30");
+        assert_eq!(extraction.dynamic_keys.len(), 1);
+        assert!(extraction.parse_errors.is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_key_policy() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+
+            export function Component({ key }) {
+                const t = useTranslations('Component');
+                return <p>{t('title')} {t(key)}</p>;
+            }
+        "#;
+        let extract = |policy| {
+            Extractor::new()
+                .with_options(ExtractorOptions::new().with_dynamic_key_policy(policy))
+                .extract_from_source(source, SourceType::tsx())
+        };
+
+        assert_eq!(
+            extract(DynamicKeyPolicy::Report)
+                .unwrap()
+                .dynamic_keys
+                .len(),
+            1
+        );
+        let extraction = extract(DynamicKeyPolicy::Ignore).unwrap();
+        assert!(extraction.dynamic_keys.is_empty());
+        assert!(extraction.translations["Component"].contains("title"));
+        let error = extract(DynamicKeyPolicy::Error).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Dynamic key in namespace 'Component' can't be extracted at 6:43"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+
+            export function Valid() {
+                const t = useTranslations('Valid');
+                return <p>{t('title')}</p>;
+            }
+
+            export function Broken() {
+                return <div></span>;
+            }
+        "#;
+        let extraction = extract_translations_from_source(source, SourceType::tsx()).unwrap();
+
+        assert_eq!(extraction.parse_errors.len(), 1);
+        assert_eq!(extraction.parse_errors[0].location.unwrap().line, 10);
+        assert!(extraction.translations["Valid"].contains("title"));
+    }
+
+    #[test]
+    fn test_parser_options() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+
+            export function Component() {
+                const t = useTranslations('Component');
+                return <p>{t('title')}</p>;
+            }
+        "#;
+        let extraction = extract_translations_from_source(source, SourceType::ts()).unwrap();
+        assert!(!extraction.parse_errors.is_empty());
+
+        let options = ExtractorOptions::new().with_parser_options(ParserOptions {
+            jsx: true,
+            ..ParserOptions::default()
+        });
+        let extraction = Extractor::new()
+            .with_options(options)
+            .extract_from_source(source, SourceType::ts())
+            .unwrap();
+        assert!(extraction.parse_errors.is_empty());
+        assert!(extraction.translations["Component"].contains("title"));
+    }
 }
@@ -0,0 +1,93 @@
+/// A 1-based line and column position in a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// Calculate the line and column of a byte offset (e.g. from an oxc span) in the source text
+    ///
+    /// Columns are counted in characters, not bytes
+    pub fn from_offset(source_text: &str, offset: u32) -> Self {
+        let offset = (offset as usize).min(source_text.len());
+        let before = &source_text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// The start offsets of the lines of a source text, to look up the locations of many spans
+/// without scanning the text from the start for each of them
+#[derive(Debug, Clone)]
+pub struct LineIndex<'s> {
+    source_text: &'s str,
+    line_starts: Vec<usize>,
+}
+
+impl<'s> LineIndex<'s> {
+    pub fn new(source_text: &'s str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source_text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            source_text,
+            line_starts,
+        }
+    }
+
+    /// The line and column of a byte offset, like [`Location::from_offset`]
+    pub fn location(&self, offset: u32) -> Location {
+        let offset = (offset as usize).min(self.source_text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        Location {
+            line: line + 1,
+            column: self.source_text[line_start..offset].chars().count() + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_from_offset() {
+        let source = "const a = 1;\nconst é = t(key);\n";
+        assert_eq!(
+            Location::from_offset(source, 0),
+            Location { line: 1, column: 1 }
+        );
+        let offset = source.find("key").unwrap() as u32;
+        assert_eq!(
+            Location::from_offset(source, offset),
+            Location {
+                line: 2,
+                column: 13
+            }
+        );
+        assert_eq!(Location::from_offset(source, offset).to_string(), "2:13");
+    }
+
+    #[test]
+    fn test_line_index() {
+        let source = "const a = 1;\nconst é = t(key);\n\nt('last')";
+        let index = LineIndex::new(source);
+        for offset in [0, 12, 13, 28, 31, 33, source.len() as u32] {
+            assert_eq!(
+                index.location(offset),
+                Location::from_offset(source, offset)
+            );
+        }
+    }
+}
@@ -3,9 +3,7 @@ use std::{env, path::Path};
 use anyhow::Result;
 use oxc::{allocator::Allocator, ast::Visit, parser::Parser, span::SourceType};
 
-use crate::visitor::TranslationFunctionVisitor;
-
-mod visitor;
+use next_intl_resolver::visitor::TranslationFunctionVisitor;
 
 // TODO: Temp CLI tool, move to `cli` crate
 fn main() -> Result<()> {
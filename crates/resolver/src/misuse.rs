@@ -0,0 +1,219 @@
+use oxc::{
+    ast::{
+        ast::{
+            ArrowFunctionExpression, CallExpression, Expression, Function,
+            ImportDeclarationSpecifier, Program, Statement,
+        },
+        visit::walk,
+        Visit,
+    },
+    syntax::scope::ScopeFlags,
+};
+use std::collections::HashMap;
+
+use crate::location::Location;
+use crate::provider::is_client_component;
+
+/// Functions that are only exported from `next-intl/server`
+const SERVER_FUNCTIONS: [&str; 9] = [
+    "getTranslations",
+    "getMessages",
+    "getFormatter",
+    "getLocale",
+    "getNow",
+    "getTimeZone",
+    "getRequestConfig",
+    "setRequestLocale",
+    "unstable_setRequestLocale",
+];
+
+/// Hooks that are only exported from `next-intl`
+const HOOKS: [&str; 6] = [
+    "useTranslations",
+    "useMessages",
+    "useFormatter",
+    "useLocale",
+    "useNow",
+    "useTimeZone",
+];
+
+/// A next-intl function that is imported from the wrong module or used where it fails at runtime
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportMisuse {
+    pub message: String,
+    pub location: Location,
+}
+
+/// Finds next-intl imports and calls that only fail at runtime, e.g. `getTranslations` in a
+/// `"use client"` file
+#[derive(Debug, Default)]
+pub struct ImportMisuseVisitor {
+    source_text: String,
+    client_component: bool,
+    /// Local names of the imported server functions and hooks, with their export name
+    server_functions: HashMap<String, String>,
+    hooks: HashMap<String, String>,
+    /// Whether the enclosing functions are async, innermost last
+    async_functions: Vec<bool>,
+    misuses: Vec<ImportMisuse>,
+}
+
+impl ImportMisuseVisitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The misuses found, in source order
+    pub fn misuses(&self) -> &[ImportMisuse] {
+        &self.misuses
+    }
+
+    fn report(&mut self, message: String, offset: u32) {
+        self.misuses.push(ImportMisuse {
+            message,
+            location: Location::from_offset(&self.source_text, offset),
+        });
+    }
+
+    fn collect_imports(&mut self, program: &Program) {
+        for statement in &program.body {
+            let Statement::ImportDeclaration(import) = statement else {
+                continue;
+            };
+            let source = import.source.value.as_str();
+            if source != "next-intl" && source != "next-intl/server" {
+                continue;
+            }
+            for specifier in import.specifiers.iter().flatten() {
+                let ImportDeclarationSpecifier::ImportSpecifier(specifier) = specifier else {
+                    continue;
+                };
+                let export = specifier.imported.name();
+                let local = specifier.local.name.to_string();
+                if SERVER_FUNCTIONS.contains(&export.as_str()) {
+                    if source == "next-intl" {
+                        self.report(
+                            format!("{export} is exported from next-intl/server, not next-intl"),
+                            specifier.span.start,
+                        );
+                    }
+                    self.server_functions.insert(local, export.to_string());
+                } else if HOOKS.contains(&export.as_str()) {
+                    if source == "next-intl/server" {
+                        self.report(
+                            format!("{export} is exported from next-intl, not next-intl/server"),
+                            specifier.span.start,
+                        );
+                    }
+                    self.hooks.insert(local, export.to_string());
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for ImportMisuseVisitor {
+    fn visit_program(&mut self, it: &Program<'a>) {
+        self.source_text = it.source_text.to_string();
+        self.client_component = is_client_component(it);
+        self.collect_imports(it);
+        walk::walk_program(self, it);
+    }
+
+    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
+        self.async_functions.push(it.r#async);
+        walk::walk_function(self, it, flags);
+        self.async_functions.pop();
+    }
+
+    fn visit_arrow_function_expression(&mut self, it: &ArrowFunctionExpression<'a>) {
+        self.async_functions.push(it.r#async);
+        walk::walk_arrow_function_expression(self, it);
+        self.async_functions.pop();
+    }
+
+    fn visit_call_expression(&mut self, it: &CallExpression<'a>) {
+        if let Expression::Identifier(callee) = &it.callee {
+            let name = callee.name.as_str();
+            if let Some(export) = self.server_functions.get(name) {
+                if self.client_component {
+                    let message = format!(
+                        "{export} from next-intl/server can't be used in client components, use the next-intl hooks instead"
+                    );
+                    self.report(message, it.span.start);
+                }
+            } else if let Some(export) = self.hooks.get(name) {
+                // Hooks can't be called in async server components
+                if self.async_functions.last() == Some(&true) {
+                    let message = format!(
+                        "{export} can't be called in async functions, use the next-intl/server functions instead"
+                    );
+                    self.report(message, it.span.start);
+                }
+            }
+        }
+        walk::walk_call_expression(self, it);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxc::{allocator::Allocator, parser::Parser, span::SourceType};
+
+    fn find_misuses(source: &str) -> Vec<ImportMisuse> {
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = ImportMisuseVisitor::new();
+        visitor.visit_program(&ret.program);
+        visitor.misuses().to_vec()
+    }
+
+    #[test]
+    fn test_import_misuses() {
+        let misuses = find_misuses(
+            r#"
+            'use client';
+            import { useTranslations } from 'next-intl';
+            import { getTranslations as getT } from 'next-intl/server';
+
+            export function Component() {
+                const t = useTranslations('Component');
+                const server = getT('Component');
+                return <p>{t('title')}</p>;
+            }
+        "#,
+        );
+        assert_eq!(misuses.len(), 1);
+        assert!(misuses[0].message.starts_with("getTranslations"));
+        assert_eq!(misuses[0].location.line, 8);
+
+        let misuses = find_misuses(
+            r#"
+            import { getTranslations, useLocale } from 'next-intl';
+            import { useTranslations } from 'next-intl/server';
+
+            export default async function Page() {
+                const t = useTranslations('Page');
+                return <p>{t('title')}</p>;
+            }
+
+            export function Header() {
+                const t = useTranslations('Header');
+                return <p>{t('title')}</p>;
+            }
+        "#,
+        );
+        let messages: Vec<_> = misuses.iter().map(|m| m.message.as_str()).collect();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0],
+            "getTranslations is exported from next-intl/server, not next-intl"
+        );
+        assert_eq!(
+            messages[1],
+            "useTranslations is exported from next-intl, not next-intl/server"
+        );
+        assert_eq!(misuses[2].location.line, 6);
+    }
+}
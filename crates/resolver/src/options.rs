@@ -0,0 +1,141 @@
+use oxc::{parser::ParseOptions, span::SourceType};
+use std::collections::HashMap;
+
+use crate::comments::{Directives, DEFAULT_MESSAGE_PREFIX};
+use crate::factory::FactorySignature;
+use crate::visitor::TRANSLATOR_METHODS;
+
+/// What to do with translator calls whose key can't be resolved statically, e.g. `t(variable)`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DynamicKeyPolicy {
+    /// Return them with the extraction
+    #[default]
+    Report,
+    /// Leave them out of the extraction
+    Ignore,
+    /// Fail the extraction of the file
+    Error,
+}
+
+/// How the source files are parsed, for syntax the file extension doesn't imply
+///
+/// Decorators are always parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Parse JavaScript files as TypeScript, e.g. for type annotations in `.js` files
+    pub typescript: bool,
+    /// Parse JSX in files that don't allow it by extension, e.g. `.ts` files with JSX
+    pub jsx: bool,
+    /// Allow `return` statements outside of functions, e.g. in scripts that are wrapped later
+    pub allow_return_outside_function: bool,
+}
+
+impl ParserOptions {
+    /// The source type of a file with these options applied
+    pub fn source_type(&self, source_type: SourceType) -> SourceType {
+        let mut source_type = source_type;
+        if self.typescript && !source_type.is_typescript() {
+            source_type = source_type.with_typescript(true);
+        }
+        if self.jsx {
+            source_type = source_type.with_jsx(true);
+        }
+        source_type
+    }
+
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            allow_return_outside_function: self.allow_return_outside_function,
+            ..ParseOptions::default()
+        }
+    }
+}
+
+/// How translators and their keys are recognized in the source files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractorOptions {
+    /// Custom functions that create a translator, on top of the next-intl ones
+    pub translator_factories: Vec<FactorySignature>,
+    /// Translator methods that take a message key, e.g. `rich` for `t.rich("key")`
+    pub translator_methods: Vec<String>,
+    /// Prefix of default message annotations, e.g. `default:` for `/* default: "Title" */`
+    pub default_message_prefix: String,
+    pub dynamic_key_policy: DynamicKeyPolicy,
+    pub directives: Directives,
+    /// Namespaces of component props that take a message key, by component and prop name
+    pub translatable_props: HashMap<String, HashMap<String, String>>,
+    pub parser: ParserOptions,
+}
+
+impl ExtractorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treat calls to these functions as translator factories, e.g. `useAppTranslations`
+    pub fn with_translator_factories<I>(mut self, factories: I) -> Self
+    where
+        I: IntoIterator<Item = FactorySignature>,
+    {
+        self.translator_factories.extend(factories);
+        self
+    }
+
+    /// Scan these translator methods for message keys on top of [`TRANSLATOR_METHODS`], e.g.
+    /// `maybe` for `t.maybe("key")`
+    pub fn with_translator_methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.translator_methods
+            .extend(methods.into_iter().map(Into::into));
+        self
+    }
+
+    /// Use a different prefix for default message annotations than [`DEFAULT_MESSAGE_PREFIX`]
+    pub fn with_default_message_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.default_message_prefix = prefix.into();
+        self
+    }
+
+    pub fn with_dynamic_key_policy(mut self, policy: DynamicKeyPolicy) -> Self {
+        self.dynamic_key_policy = policy;
+        self
+    }
+
+    /// Use different comment directives, e.g. to avoid clashes with other tools
+    pub fn with_directives(mut self, directives: Directives) -> Self {
+        self.directives = directives;
+        self
+    }
+
+    /// Extract the message keys passed to component props into the configured namespaces, by
+    /// component and prop name, e.g. `{"Button": {"labelKey": "Buttons"}}`
+    pub fn with_translatable_props(
+        mut self,
+        props: HashMap<String, HashMap<String, String>>,
+    ) -> Self {
+        self.translatable_props.extend(props);
+        self
+    }
+
+    pub fn with_parser_options(mut self, parser: ParserOptions) -> Self {
+        self.parser = parser;
+        self
+    }
+}
+
+impl Default for ExtractorOptions {
+    fn default() -> Self {
+        Self {
+            translator_factories: Vec::new(),
+            translator_methods: TRANSLATOR_METHODS.iter().map(|m| m.to_string()).collect(),
+            default_message_prefix: DEFAULT_MESSAGE_PREFIX.to_string(),
+            dynamic_key_policy: DynamicKeyPolicy::default(),
+            directives: Directives::default(),
+            translatable_props: HashMap::new(),
+            parser: ParserOptions::default(),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use oxc::{ast::ast::Program, span::Span};
+use std::fmt::Debug;
+use std::path::Path;
+
+/// The file a plugin extracts messages from
+#[derive(Debug, Clone, Copy)]
+pub struct PluginContext<'s> {
+    pub source_text: &'s str,
+    /// Path of the file, `None` when extracting from source text
+    pub file_path: Option<&'s Path>,
+}
+
+/// A message key found by a plugin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginKey {
+    pub namespace: String,
+    pub key: String,
+    /// Span of the key in the source text
+    pub span: Span,
+}
+
+/// A problem found by a plugin, e.g. a usage that doesn't follow the rules of the organization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginDiagnostic {
+    pub message: String,
+    pub span: Span,
+}
+
+/// What a plugin found in a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginOutput {
+    pub keys: Vec<PluginKey>,
+    pub diagnostics: Vec<PluginDiagnostic>,
+}
+
+/// Additional extraction rules that run alongside the translator visitor, e.g. for an in-house
+/// translation helper
+///
+/// A plugin usually walks the program with its own [`oxc::ast::Visit`] implementation. The
+/// keys it returns are merged with the extracted ones and get the same transformations
+pub trait ExtractorPlugin: Debug {
+    /// Name of the plugin, used as the translator of its keys and in its diagnostics
+    fn name(&self) -> &str;
+
+    fn extract(&self, program: &Program, context: PluginContext) -> PluginOutput;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Extractor;
+    use oxc::{
+        ast::{
+            ast::{CallExpression, Expression},
+            visit::walk,
+            Visit,
+        },
+        span::SourceType,
+    };
+
+    /// Extracts `msg("Namespace.key")` and reports `msg()` without a key
+    #[derive(Debug)]
+    struct MsgPlugin;
+
+    #[derive(Default)]
+    struct MsgVisitor {
+        output: PluginOutput,
+    }
+
+    impl<'a> Visit<'a> for MsgVisitor {
+        fn visit_call_expression(&mut self, it: &CallExpression<'a>) {
+            if it.callee.is_specific_id("msg") {
+                match it.arguments.first().and_then(|arg| arg.as_expression()) {
+                    Some(Expression::StringLiteral(lit)) => {
+                        if let Some((namespace, key)) = lit.value.split_once('.') {
+                            self.output.keys.push(PluginKey {
+                                namespace: namespace.to_string(),
+                                key: key.to_string(),
+                                span: lit.span,
+                            });
+                        }
+                    }
+                    _ => self.output.diagnostics.push(PluginDiagnostic {
+                        message: "msg() needs a static key".to_string(),
+                        span: it.span,
+                    }),
+                }
+            }
+            walk::walk_call_expression(self, it);
+        }
+    }
+
+    impl ExtractorPlugin for MsgPlugin {
+        fn name(&self) -> &str {
+            "msg"
+        }
+
+        fn extract(&self, program: &Program, _context: PluginContext) -> PluginOutput {
+            let mut visitor = MsgVisitor::default();
+            visitor.visit_program(program);
+            visitor.output
+        }
+    }
+
+    #[test]
+    fn test_plugins() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+
+            export function Component() {
+                const t = useTranslations('Component');
+                return <p title={msg('Tooltips.help')}>{t('title')} {msg()}</p>;
+            }
+        "#;
+        let extraction = Extractor::new()
+            .with_plugin(MsgPlugin)
+            .extract_from_source(source, SourceType::tsx())
+            .unwrap();
+
+        assert!(extraction.translations["Component"].contains("title"));
+        assert!(extraction.translations["Tooltips"].contains("help"));
+        let usage = extraction
+            .usages
+            .iter()
+            .find(|usage| usage.namespace == "Tooltips")
+            .unwrap();
+        assert_eq!(usage.translator, "msg");
+        assert_eq!(extraction.plugin_warnings.len(), 1);
+        assert_eq!(extraction.plugin_warnings[0].plugin, "msg");
+        assert_eq!(extraction.plugin_warnings[0].location.to_string(), "6:70");
+    }
+}
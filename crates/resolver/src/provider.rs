@@ -0,0 +1,206 @@
+use oxc::ast::{
+    ast::{
+        BindingPatternKind, Expression, JSXAttributeItem, JSXAttributeName, JSXAttributeValue,
+        JSXOpeningElement, ObjectPropertyKind, Program, VariableDeclarator,
+    },
+    visit::walk,
+    Visit,
+};
+use std::collections::HashMap;
+
+use crate::constants::Constants;
+use crate::location::Location;
+
+/// The provider that passes messages to client components
+const CLIENT_PROVIDER: &str = "NextIntlClientProvider";
+
+/// Whether a program starts with the `"use client"` directive
+pub fn is_client_component(program: &Program) -> bool {
+    program
+        .directives
+        .iter()
+        .any(|directive| directive.directive == "use client")
+}
+
+/// Messages passed to a `NextIntlClientProvider`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvidedMessages {
+    /// Namespaces that are shipped to the client, e.g. `Cart` and `Checkout` for
+    /// `pick(messages, 'Cart', 'Checkout')`, or `None` if they can't be determined statically
+    /// (e.g. the whole catalog)
+    pub namespaces: Option<Vec<String>>,
+    pub location: Location,
+}
+
+/// Finds the messages that are passed to `NextIntlClientProvider`
+#[derive(Debug, Default)]
+pub struct ProviderVisitor {
+    constants: Constants,
+    source_text: String,
+    /// Namespaces picked into variables, e.g. `const clientMessages = pick(messages, 'Cart')`
+    picked: HashMap<String, Vec<String>>,
+    provided: Vec<ProvidedMessages>,
+}
+
+impl ProviderVisitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The messages passed to the providers, in source order
+    pub fn provided_messages(&self) -> &[ProvidedMessages] {
+        &self.provided
+    }
+
+    /// The top-level namespaces an expression picks from the messages
+    ///
+    /// e.g. `pick(messages, 'Cart', 'Checkout')`, `pick(messages, ['Cart'])`,
+    /// `{ Cart: messages.Cart, ...pick(messages, 'Checkout') }`
+    fn picked_namespaces(&self, expr: &Expression) -> Option<Vec<String>> {
+        match expr {
+            Expression::CallExpression(call) if is_pick(&call.callee) => {
+                let mut namespaces = Vec::new();
+                for arg in call.arguments.iter().skip(1) {
+                    match arg.as_expression()? {
+                        Expression::ArrayExpression(array) => {
+                            for element in &array.elements {
+                                namespaces.push(self.constants.resolve(element.as_expression()?)?);
+                            }
+                        }
+                        expr => namespaces.push(self.constants.resolve(expr)?),
+                    }
+                }
+                Some(namespaces)
+            }
+            Expression::ObjectExpression(obj) => {
+                let mut namespaces = Vec::new();
+                for prop in &obj.properties {
+                    match prop {
+                        ObjectPropertyKind::ObjectProperty(prop) => {
+                            namespaces.push(prop.key.static_name()?.to_string())
+                        }
+                        ObjectPropertyKind::SpreadProperty(spread) => {
+                            namespaces.extend(self.picked_namespaces(&spread.argument)?)
+                        }
+                    }
+                }
+                Some(namespaces)
+            }
+            Expression::Identifier(ident) => self.picked.get(ident.name.as_str()).cloned(),
+            Expression::ParenthesizedExpression(paren) => self.picked_namespaces(&paren.expression),
+            Expression::TSAsExpression(ts_as) => self.picked_namespaces(&ts_as.expression),
+            _ => None,
+        }
+    }
+}
+
+/// `pick(...)`, or `_.pick(...)` from lodash
+fn is_pick(callee: &Expression) -> bool {
+    match callee {
+        Expression::Identifier(ident) => ident.name == "pick",
+        Expression::StaticMemberExpression(member) => member.property.name == "pick",
+        _ => false,
+    }
+}
+
+impl<'a> Visit<'a> for ProviderVisitor {
+    fn visit_program(&mut self, it: &Program<'a>) {
+        self.constants = Constants::collect(it);
+        self.source_text = it.source_text.to_string();
+        walk::walk_program(self, it);
+    }
+
+    fn visit_variable_declarator(&mut self, it: &VariableDeclarator<'a>) {
+        if let (BindingPatternKind::BindingIdentifier(ident), Some(init)) = (&it.id.kind, &it.init)
+        {
+            if let Some(namespaces) = self.picked_namespaces(init) {
+                self.picked.insert(ident.name.to_string(), namespaces);
+            }
+        }
+        walk::walk_variable_declarator(self, it);
+    }
+
+    fn visit_jsx_opening_element(&mut self, it: &JSXOpeningElement<'a>) {
+        if it.name.to_string() == CLIENT_PROVIDER {
+            let messages = it.attributes.iter().find_map(|attribute| match attribute {
+                JSXAttributeItem::Attribute(attribute) => match &attribute.name {
+                    JSXAttributeName::Identifier(name) if name.name == "messages" => {
+                        Some(attribute)
+                    }
+                    _ => None,
+                },
+                JSXAttributeItem::SpreadAttribute(_) => None,
+            });
+            if let Some(attribute) = messages {
+                let namespaces = match &attribute.value {
+                    Some(JSXAttributeValue::ExpressionContainer(container)) => container
+                        .expression
+                        .as_expression()
+                        .and_then(|expr| self.picked_namespaces(expr)),
+                    _ => None,
+                };
+                self.provided.push(ProvidedMessages {
+                    namespaces,
+                    location: Location::from_offset(&self.source_text, attribute.span.start),
+                });
+            }
+        }
+        walk::walk_jsx_opening_element(self, it);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxc::{allocator::Allocator, parser::Parser, span::SourceType};
+
+    #[test]
+    fn test_provided_messages() {
+        let source = r#"
+            import pick from 'lodash/pick';
+
+            const SHARED = 'Shared';
+
+            export default async function Layout({ children }) {
+                const messages = await getMessages();
+                const clientMessages = pick(messages, ['Cart', SHARED]);
+                return (
+                    <>
+                        <NextIntlClientProvider messages={pick(messages, 'Cart', 'Checkout')}>
+                            {children}
+                        </NextIntlClientProvider>
+                        <NextIntlClientProvider
+                            messages={{ Header: messages.Header, ...clientMessages }}
+                        />
+                        <NextIntlClientProvider messages={messages} />
+                    </>
+                );
+            }
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = ProviderVisitor::new();
+        visitor.visit_program(&ret.program);
+
+        let provided = visitor.provided_messages();
+        assert_eq!(provided.len(), 3);
+        assert_eq!(
+            provided[0].namespaces,
+            Some(vec!["Cart".to_string(), "Checkout".to_string()])
+        );
+        assert_eq!(provided[0].location.line, 11);
+        assert_eq!(
+            provided[1].namespaces,
+            Some(vec![
+                "Header".to_string(),
+                "Cart".to_string(),
+                "Shared".to_string()
+            ])
+        );
+        assert_eq!(provided[2].namespaces, None);
+        assert!(!is_client_component(&ret.program));
+
+        let ret = Parser::new(&allocator, "'use client';\nexport {}", SourceType::tsx()).parse();
+        assert!(is_client_component(&ret.program));
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{bail, Error};
+use std::str::FromStr;
+
+/// A transformation of extracted namespaces or keys, so the messages can follow a different
+/// naming convention than the code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transform {
+    /// `submitButton` to `submit-button`
+    KebabCase,
+    /// `submitButton` to `submit_button`
+    SnakeCase,
+    /// `submit-button` to `submitButton`
+    CamelCase,
+    /// `submit-button` to `SubmitButton`
+    PascalCase,
+    /// Add a prefix, e.g. `App.` for `Cart` to `App.Cart`
+    Prefix(String),
+    /// Remove a prefix if present
+    StripPrefix(String),
+    /// Remove a suffix if present, e.g. `Page` for `CartPage` to `Cart`
+    StripSuffix(String),
+}
+
+impl Transform {
+    /// Apply the transformation, case conversions are applied to each `.` separated segment
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Self::KebabCase => map_segments(value, |segment| words(segment).join("-")),
+            Self::SnakeCase => map_segments(value, |segment| words(segment).join("_")),
+            Self::CamelCase => map_segments(value, |segment| {
+                let words = words(segment);
+                let mut iter = words.iter();
+                let first = iter.next().cloned().unwrap_or_default();
+                first + &iter.map(|word| capitalize(word)).collect::<String>()
+            }),
+            Self::PascalCase => map_segments(value, |segment| {
+                words(segment).iter().map(|word| capitalize(word)).collect()
+            }),
+            Self::Prefix(prefix) => format!("{prefix}{value}"),
+            Self::StripPrefix(prefix) => value
+                .strip_prefix(prefix.as_str())
+                .unwrap_or(value)
+                .to_string(),
+            Self::StripSuffix(suffix) => value
+                .strip_suffix(suffix.as_str())
+                .unwrap_or(value)
+                .to_string(),
+        }
+    }
+}
+
+/// Parses `kebab-case`, `snake-case`, `camel-case`, `pascal-case`, `prefix:TEXT`,
+/// `strip-prefix:TEXT` or `strip-suffix:TEXT`
+impl FromStr for Transform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let transform = match s.trim().split_once(':') {
+            None => match s.trim() {
+                "kebab-case" => Self::KebabCase,
+                "snake-case" => Self::SnakeCase,
+                "camel-case" => Self::CamelCase,
+                "pascal-case" => Self::PascalCase,
+                _ => bail!(
+                    "Invalid transform '{}', expected kebab-case, snake-case, camel-case, pascal-case, prefix:TEXT, strip-prefix:TEXT or strip-suffix:TEXT",
+                    s
+                ),
+            },
+            Some(("prefix", text)) => Self::Prefix(text.to_string()),
+            Some(("strip-prefix", text)) => Self::StripPrefix(text.to_string()),
+            Some(("strip-suffix", text)) => Self::StripSuffix(text.to_string()),
+            Some(_) => bail!("Invalid transform '{}'", s),
+        };
+        Ok(transform)
+    }
+}
+
+/// The transformations applied to the extracted namespaces and keys, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transforms {
+    pub namespace: Vec<Transform>,
+    pub key: Vec<Transform>,
+}
+
+impl Transforms {
+    pub fn is_empty(&self) -> bool {
+        self.namespace.is_empty() && self.key.is_empty()
+    }
+
+    pub fn namespace(&self, namespace: &str) -> String {
+        apply_all(&self.namespace, namespace)
+    }
+
+    pub fn key(&self, key: &str) -> String {
+        apply_all(&self.key, key)
+    }
+}
+
+fn apply_all(transforms: &[Transform], value: &str) -> String {
+    transforms
+        .iter()
+        .fold(value.to_string(), |value, transform| {
+            transform.apply(&value)
+        })
+}
+
+fn map_segments(value: &str, f: impl Fn(&str) -> String) -> String {
+    value.split('.').map(f).collect::<Vec<_>>().join(".")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Split a name into lowercase words at `-`, `_`, spaces and case changes, e.g.
+/// `submitHTMLButton` to `submit`, `html`, `button`
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if matches!(c, '-' | '_' | ' ') {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        let is_boundary = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_ascii_digit()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+        if is_boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_transforms() {
+        assert_eq!(
+            Transform::KebabCase.apply("submitHTMLButton"),
+            "submit-html-button"
+        );
+        assert_eq!(
+            Transform::SnakeCase.apply("cart.emptyState"),
+            "cart.empty_state"
+        );
+        assert_eq!(
+            Transform::CamelCase.apply("empty-state.call_to_action"),
+            "emptyState.callToAction"
+        );
+        assert_eq!(Transform::PascalCase.apply("user-profile"), "UserProfile");
+    }
+
+    #[test]
+    fn test_transforms() {
+        let transforms = Transforms {
+            namespace: vec![
+                Transform::StripSuffix("Page".to_string()),
+                Transform::Prefix("App.".to_string()),
+            ],
+            key: vec!["kebab-case".parse().unwrap()],
+        };
+        assert_eq!(transforms.namespace("CartPage"), "App.Cart");
+        assert_eq!(transforms.namespace("Cart"), "App.Cart");
+        assert_eq!(transforms.key("emptyState"), "empty-state");
+    }
+
+    #[test]
+    fn test_parse_transform() {
+        assert_eq!(
+            "strip-suffix:Page".parse::<Transform>().unwrap(),
+            Transform::StripSuffix("Page".to_string())
+        );
+        assert_eq!(
+            "pascal-case".parse::<Transform>().unwrap(),
+            Transform::PascalCase
+        );
+        assert!("upper-case".parse::<Transform>().is_err());
+        assert!("append:x".parse::<Transform>().is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+/// Import aliases from the `paths` of a `tsconfig.json`, e.g. `@/*` to `./src/*`
+///
+/// Relative `extends` are followed, the `paths` of the extending config replace the extended
+/// ones like TypeScript does
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathAliases {
+    /// Directory the alias targets are relative to, `baseUrl` or the directory of the config
+    base_dir: PathBuf,
+    /// Alias patterns with their targets, at most one `*` in each
+    paths: Vec<(String, Vec<String>)>,
+}
+
+impl PathAliases {
+    pub fn from_tsconfig(path: &Path) -> Result<Self> {
+        let mut aliases = Self::default();
+        let mut base_dir = None;
+        let mut paths = None;
+        let mut visited = Vec::new();
+        let mut next = Some(path.to_path_buf());
+
+        while let Some(path) = next.take() {
+            if visited.contains(&path) {
+                break;
+            }
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let config: Value = serde_json::from_str(&strip_jsonc(&content))
+                .map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))?;
+            let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            let compiler_options = config.get("compilerOptions");
+
+            if base_dir.is_none() {
+                base_dir = compiler_options
+                    .and_then(|options| options.get("baseUrl"))
+                    .and_then(Value::as_str)
+                    .map(|base_url| dir.join(base_url));
+            }
+            if paths.is_none() {
+                if let Some(Value::Object(map)) =
+                    compiler_options.and_then(|options| options.get("paths"))
+                {
+                    paths = Some(
+                        map.iter()
+                            .map(|(pattern, targets)| {
+                                let targets = targets
+                                    .as_array()
+                                    .into_iter()
+                                    .flatten()
+                                    .filter_map(Value::as_str)
+                                    .map(str::to_string)
+                                    .collect();
+                                (pattern.clone(), targets)
+                            })
+                            .collect(),
+                    );
+                    // Without a `baseUrl`, targets are relative to the config that declares them
+                    aliases.base_dir = dir.clone();
+                }
+            }
+
+            // Packages like `@tsconfig/next` can't be resolved without node_modules lookups
+            next = config
+                .get("extends")
+                .and_then(Value::as_str)
+                .filter(|extends| extends.starts_with("./") || extends.starts_with("../"))
+                .map(|extends| {
+                    let extended = normalize(&dir.join(extends));
+                    if extended.is_file() {
+                        extended
+                    } else {
+                        PathBuf::from(format!("{}.json", extended.display()))
+                    }
+                });
+            visited.push(path);
+        }
+
+        if let Some(base_dir) = base_dir {
+            aliases.base_dir = base_dir;
+        }
+        aliases.paths = paths.unwrap_or_default();
+        Ok(aliases)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// The paths an aliased import specifier may refer to, without extension
+    ///
+    /// The most specific pattern wins, i.e. the one with the longest prefix before the `*`
+    pub fn resolve(&self, specifier: &str) -> Vec<PathBuf> {
+        let mut best: Option<(&str, &[String], usize)> = None;
+        for (pattern, targets) in &self.paths {
+            let matched = match pattern.split_once('*') {
+                None => (pattern == specifier).then_some(("", pattern.len())),
+                Some((prefix, suffix)) => specifier
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix))
+                    .map(|wildcard| (wildcard, prefix.len())),
+            };
+            if let Some((wildcard, specificity)) = matched {
+                if best.is_none_or(|(_, _, best)| specificity > best) {
+                    best = Some((wildcard, targets, specificity));
+                }
+            }
+        }
+
+        best.map(|(wildcard, targets, _)| {
+            targets
+                .iter()
+                .map(|target| normalize(&self.base_dir.join(target.replacen('*', wildcard, 1))))
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// Resolve the `.` and `..` components of a path without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Remove the comments and trailing commas that tsconfig files allow but JSON doesn't
+fn strip_jsonc(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            // Trailing commas, comments before the bracket are already stripped
+            ('}' | ']', _) => {
+                let trimmed = output.trim_end().len();
+                if output[..trimmed].ends_with(',') {
+                    output.truncate(trimmed - 1);
+                }
+                output.push(c);
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_path_aliases() {
+        let dir = std::env::temp_dir().join(format!("tsconfig-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("apps/web")).unwrap();
+        fs::write(
+            dir.join("tsconfig.base.json"),
+            r#"{
+                // Shared by every app
+                "compilerOptions": {
+                    "paths": {
+                        "@/*": ["./src/*"],
+                        "@/lib/i18n": ["./src/i18n/index.ts"],
+                        "@shared/*": ["./packages/shared/*", "./packages/legacy/*",],
+                    },
+                },
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("apps/web/tsconfig.json"),
+            r#"{ "extends": "../../tsconfig.base", /* keeps the paths */ "compilerOptions": {} }"#,
+        )
+        .unwrap();
+
+        let aliases = PathAliases::from_tsconfig(&dir.join("apps/web/tsconfig.json")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            aliases.resolve("@/lib/i18n"),
+            vec![dir.join("src/i18n/index.ts")]
+        );
+        assert_eq!(
+            aliases.resolve("@/lib/keys"),
+            vec![dir.join("src/lib/keys")]
+        );
+        assert_eq!(
+            aliases.resolve("@shared/keys"),
+            vec![
+                dir.join("packages/shared/keys"),
+                dir.join("packages/legacy/keys")
+            ]
+        );
+        assert!(aliases.resolve("next-intl").is_empty());
+    }
+}
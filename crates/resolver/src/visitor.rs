@@ -1,16 +1,61 @@
-use log::warn;
 use oxc::{
     ast::{
         ast::{
-            Argument, BindingPatternKind, CallExpression, Expression, Function, ObjectPropertyKind,
-            PropertyKey,
+            Argument, BindingPatternKind, CallExpression, Expression, IdentifierReference,
+            ImportDeclarationSpecifier, JSXAttributeItem, JSXAttributeName, JSXAttributeValue,
+            JSXOpeningElement, ObjectExpression, ObjectPropertyKind, Program, Statement,
+            VariableDeclarator,
         },
         visit::walk,
         Visit,
     },
-    syntax::scope::ScopeFlags,
+    semantic::{SemanticBuilder, SymbolTable},
+    span::{GetSpan, Span},
+    syntax::{scope::ScopeId, symbol::SymbolId},
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::comments::{comment_description, parse_annotation, Comments};
+use crate::constants::Constants;
+use crate::factory::{fill_template, FactorySignature, NamespaceSignature};
+use crate::options::{DynamicKeyPolicy, ExtractorOptions};
+
+/// Higher order components that wrap a component, e.g. `memo(() => ...)`
+const COMPONENT_WRAPPERS: [&str; 2] = ["memo", "forwardRef"];
+
+/// Modules that export the translator factories
+pub(crate) fn is_next_intl_module(source: &str) -> bool {
+    source == "next-intl" || source.starts_with("next-intl/")
+}
+
+/// Functions that create a translator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TranslatorFactory {
+    /// `const t = useTranslations("Namespace")`
+    UseTranslations,
+    /// `const t = await getTranslations("Namespace")` or `getTranslations({ namespace })`
+    GetTranslations,
+    /// Custom wrappers around next-intl, e.g. `const t = useAppTranslations("Namespace")`
+    Custom(NamespaceSignature),
+}
+
+impl TranslatorFactory {
+    pub(crate) fn from_export_name(name: &str) -> Option<Self> {
+        match name {
+            "useTranslations" => Some(Self::UseTranslations),
+            "getTranslations" => Some(Self::GetTranslations),
+            _ => None,
+        }
+    }
+}
+
+/// Functions that return the whole message catalog, e.g. `const messages = useMessages()`
+pub const CATALOG_FUNCTIONS: [&str; 2] = ["useMessages", "getMessages"];
+
+/// Methods on a translator that take a message key as their first argument
+///
+/// e.g. `t.rich("key")`, `t.markup("key")`, `t.raw("key")` and `t.has("key")`
+pub const TRANSLATOR_METHODS: [&str; 4] = ["rich", "markup", "raw", "has"];
 
 #[derive(Debug)]
 struct TranslationFunction {
@@ -18,30 +63,581 @@ struct TranslationFunction {
     usages: HashSet<String>,
 }
 
+/// A message key that is used in the visited file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyUsage {
+    pub namespace: String,
+    pub key: String,
+    /// Name of the translator the key is passed to, e.g. `t` for `t("key")`, or the component
+    /// prop, e.g. `Button.labelKey` for `<Button labelKey="key" />`
+    pub translator: String,
+    /// Span of the key argument or prop value
+    pub span: Span,
+}
+
+/// A translator factory call whose namespace is resolved, e.g. `useTranslations('Cart')`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslatorNamespace {
+    pub namespace: String,
+    /// Span of the factory call
+    pub span: Span,
+    /// Span of the literal the namespace is written in, including its quotes, `None` if it's
+    /// resolved from a constant, composed or derived from the file path
+    pub literal: Option<Span>,
+}
+
+/// A call that pulls in the whole message catalog, e.g. `useMessages()` or `getMessages()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogUsage {
+    /// Name of the next-intl function that is called, e.g. `useMessages`
+    pub function: String,
+    pub span: Span,
+}
+
+/// A translator call with a key that can't be resolved statically
+///
+/// e.g. `t(someVariable)` or `t(`prefix.${x}`)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicKeyUsage {
+    pub namespace: String,
+    pub span: Span,
+}
+
 #[derive(Debug)]
 pub struct TranslationFunctionVisitor {
-    translation_functions: HashMap<String, TranslationFunction>,
-    current_scope: Vec<String>,
+    translation_functions: Vec<TranslationFunction>,
+    /// Translators by the symbol they are bound to, so that shadowed names and translators in
+    /// sibling blocks don't get mixed up
+    translators: HashMap<SymbolId, usize>,
+    /// Symbols and references of the visited file, built with `oxc_semantic`
+    symbols: SymbolTable,
+    /// Scope of the visited module, bindings in any other scope are local to a function or block
+    module_scope: Option<ScopeId>,
+    /// How translators and their keys are recognized
+    options: ExtractorOptions,
+    dynamic_keys: Vec<DynamicKeyUsage>,
+    /// Translator factory calls whose namespace could not be resolved statically
+    unresolved_namespaces: Vec<Span>,
+    /// Translator factory calls with the namespaces they resolve to
+    translator_namespaces: Vec<TranslatorNamespace>,
+    /// Every usage of a static key, in source order
+    key_usages: Vec<KeyUsage>,
+    /// String constants declared in the visited file
+    constants: Constants,
+    /// Symbols of the translator factories imported from next-intl
+    translator_factories: HashMap<SymbolId, TranslatorFactory>,
+    /// Symbols of the [`CATALOG_FUNCTIONS`] imported from next-intl, by their export name
+    catalog_functions: HashMap<SymbolId, String>,
+    catalog_usages: Vec<CatalogUsage>,
+    /// Symbols of namespace imports of next-intl, e.g. `import * as intl from "next-intl"`
+    next_intl_namespaces: HashSet<SymbolId>,
+    /// Custom functions that create a translator by name, e.g. `useAppTranslations`
+    custom_factories: HashMap<String, NamespaceSignature>,
+    /// Comments of the visited file
+    comments: Comments,
+    /// Default messages by namespace and key, declared with an annotation next to the usage
+    default_messages: HashMap<String, HashMap<String, String>>,
+    /// Descriptions for translators by namespace and key, from the comment above the usage
+    descriptions: HashMap<String, HashMap<String, String>>,
+    /// Names of the values passed to the usages by namespace and key, e.g. `name` for
+    /// `t("greeting", { name })`
+    placeholders: HashMap<String, HashMap<String, BTreeSet<String>>>,
+    /// Names of the rich text tags passed to the usages by namespace and key, e.g. `link` for
+    /// `t.rich("terms", { link: (chunks) => <a>{chunks}</a> })`
+    tags: HashMap<String, HashMap<String, BTreeSet<String>>>,
+    /// Namespace of translators created without one, derived from the file path
+    path_namespace: Option<String>,
 }
 
 impl TranslationFunctionVisitor {
     pub fn new() -> Self {
         Self {
-            translation_functions: HashMap::new(),
-            current_scope: Vec::new(),
+            translation_functions: Vec::new(),
+            translators: HashMap::new(),
+            symbols: SymbolTable::default(),
+            module_scope: None,
+            options: ExtractorOptions::default(),
+            dynamic_keys: Vec::new(),
+            unresolved_namespaces: Vec::new(),
+            translator_namespaces: Vec::new(),
+            key_usages: Vec::new(),
+            constants: Constants::default(),
+            translator_factories: HashMap::new(),
+            catalog_functions: HashMap::new(),
+            catalog_usages: Vec::new(),
+            next_intl_namespaces: HashSet::new(),
+            custom_factories: HashMap::new(),
+            comments: Comments::default(),
+            default_messages: HashMap::new(),
+            descriptions: HashMap::new(),
+            placeholders: HashMap::new(),
+            tags: HashMap::new(),
+            path_namespace: None,
+        }
+    }
+
+    /// Recognize translators and their keys as configured in the options
+    pub fn with_options(mut self, options: ExtractorOptions) -> Self {
+        self.custom_factories = options
+            .translator_factories
+            .iter()
+            .map(|factory| (factory.name.clone(), factory.namespace.clone()))
+            .collect();
+        self.options = options;
+        self
+    }
+
+    /// Scan additional translator methods for message keys on top of [`TRANSLATOR_METHODS`]
+    pub fn with_translator_methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options = self.options.with_translator_methods(methods);
+        self
+    }
+
+    /// Use a different prefix for default message annotations than
+    /// [`crate::comments::DEFAULT_MESSAGE_PREFIX`]
+    ///
+    /// e.g. `en:` for `t("title") /* en: "Welcome back" */`
+    pub fn with_default_message_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.options = self.options.with_default_message_prefix(prefix);
+        self
+    }
+
+    /// Extract the message keys passed to component props into the configured namespaces
+    ///
+    /// e.g. `{"Button": {"labelKey": "Buttons"}}` extracts `<Button labelKey="save" />` as
+    /// `Buttons.save`
+    pub fn with_translatable_props(
+        mut self,
+        props: HashMap<String, HashMap<String, String>>,
+    ) -> Self {
+        self.options = self.options.with_translatable_props(props);
+        self
+    }
+
+    /// Use this namespace for translators that are created without one, e.g. `useTranslations()`
+    ///
+    /// See [`crate::convention::namespace_from_path`] to derive it from the file path
+    pub fn with_path_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.path_namespace = Some(namespace.into());
+        self
+    }
+
+    /// Get the symbol an identifier refers to, `None` for globals and unresolved names
+    fn referenced_symbol(&self, ident: &IdentifierReference) -> Option<SymbolId> {
+        ident
+            .reference_id
+            .get()
+            .and_then(|reference_id| self.symbols.get_reference(reference_id).symbol_id())
+    }
+
+    /// Get the translator an identifier refers to, if any
+    fn translator(&self, ident: &IdentifierReference) -> Option<usize> {
+        self.referenced_symbol(ident)
+            .and_then(|symbol| self.translators.get(&symbol))
+            .copied()
+    }
+
+    /// Whether an identifier refers to a name declared inside a function or block of the module,
+    /// shadowing an import with the same name
+    fn is_locally_bound(&self, ident: &IdentifierReference) -> bool {
+        self.referenced_symbol(ident)
+            .is_some_and(|symbol| Some(self.symbols.get_scope_id(symbol)) != self.module_scope)
+    }
+
+    /// Collect the translator factories that are imported from next-intl
+    ///
+    /// e.g. `import { useTranslations } from "next-intl";`
+    fn collect_translator_factories(&mut self, program: &Program) {
+        self.translator_factories.clear();
+        self.catalog_functions.clear();
+        self.next_intl_namespaces.clear();
+        for statement in &program.body {
+            if let Statement::VariableDeclaration(declaration) = statement {
+                for decl in &declaration.declarations {
+                    self.collect_required_translator_factories(decl);
+                }
+                continue;
+            }
+            let Statement::ImportDeclaration(import) = statement else {
+                continue;
+            };
+            if !is_next_intl_module(import.source.value.as_str()) {
+                continue;
+            }
+            for specifier in import.specifiers.iter().flatten() {
+                match specifier {
+                    // Aliased imports are stored by their local symbol, e.g.
+                    // `import { useTranslations as useT } from "next-intl";`
+                    ImportDeclarationSpecifier::ImportSpecifier(specifier) => self.import_export(
+                        specifier.imported.name().as_str(),
+                        specifier.local.symbol_id.get(),
+                    ),
+                    // `import * as intl from "next-intl";`
+                    ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                        self.next_intl_namespaces
+                            .extend(specifier.local.symbol_id.get());
+                    }
+                    ImportDeclarationSpecifier::ImportDefaultSpecifier(_) => {}
+                }
+            }
+        }
+    }
+
+    /// Collect the translator factories that are required from next-intl in CommonJS modules
+    ///
+    /// e.g. `const { useTranslations } = require("next-intl");`
+    fn collect_required_translator_factories(&mut self, decl: &VariableDeclarator) {
+        let Some(Expression::CallExpression(call_expr)) = &decl.init else {
+            return;
+        };
+        let is_next_intl_require = call_expr.callee.is_specific_id("require")
+            && matches!(
+                call_expr.arguments.first(),
+                Some(Argument::StringLiteral(source)) if is_next_intl_module(source.value.as_str())
+            );
+        if !is_next_intl_require {
+            return;
+        }
+
+        match &decl.id.kind {
+            // `const intl = require("next-intl");`
+            BindingPatternKind::BindingIdentifier(ident) => {
+                self.next_intl_namespaces.extend(ident.symbol_id.get());
+            }
+            // `const { useTranslations: useT } = require("next-intl");`
+            BindingPatternKind::ObjectPattern(object) => {
+                for property in &object.properties {
+                    let (Some(name), Some(local)) = (
+                        property.key.static_name(),
+                        property.value.get_binding_identifier(),
+                    ) else {
+                        continue;
+                    };
+                    self.import_export(&name, local.symbol_id.get());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Register a next-intl export under its local symbol, if it is a translator factory or returns
+    /// the message catalog
+    fn import_export(&mut self, export: &str, local: Option<SymbolId>) {
+        let Some(local) = local else {
+            return;
+        };
+        if let Some(factory) = TranslatorFactory::from_export_name(export) {
+            self.translator_factories.insert(local, factory);
+        } else if CATALOG_FUNCTIONS.contains(&export) {
+            self.catalog_functions.insert(local, export.to_string());
+        }
+    }
+
+    /// Get the next-intl function returning the whole message catalog that is called, if any
+    fn catalog_function(&self, callee: &Expression) -> Option<String> {
+        match callee {
+            Expression::Identifier(ident) => self
+                .referenced_symbol(ident)
+                .and_then(|symbol| self.catalog_functions.get(&symbol))
+                .cloned(),
+            // Namespace imports, e.g. `intl.useMessages()`
+            Expression::StaticMemberExpression(member_expr) => match &member_expr.object {
+                Expression::Identifier(object) if self.is_next_intl_namespace(object) => {
+                    let name = member_expr.property.name.as_str();
+                    CATALOG_FUNCTIONS.contains(&name).then(|| name.to_string())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether an identifier refers to a namespace import of next-intl
+    fn is_next_intl_namespace(&self, ident: &IdentifierReference) -> bool {
+        self.referenced_symbol(ident)
+            .is_some_and(|symbol| self.next_intl_namespaces.contains(&symbol))
+    }
+
+    /// Get the translator factory that is called, if any
+    fn translator_factory(&self, callee: &Expression) -> Option<TranslatorFactory> {
+        match callee {
+            Expression::Identifier(ident) if self.is_locally_bound(ident) => None,
+            Expression::Identifier(ident) => self
+                .referenced_symbol(ident)
+                .and_then(|symbol| self.translator_factories.get(&symbol))
+                .cloned()
+                .or_else(|| {
+                    self.custom_factories
+                        .get(ident.name.as_str())
+                        .map(|signature| TranslatorFactory::Custom(signature.clone()))
+                }),
+            // Namespace imports, e.g. `intl.useTranslations("Namespace")`
+            Expression::StaticMemberExpression(member_expr) => match &member_expr.object {
+                Expression::Identifier(object) if self.is_next_intl_namespace(object) => {
+                    TranslatorFactory::from_export_name(member_expr.property.name.as_str())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Declare the translator created by a variable declarator, if any
+    ///
+    /// e.g. `const t = useTranslations("Namespace");` or
+    /// `const t = await getTranslations("Namespace");`
+    fn declare_translator(&mut self, decl: &VariableDeclarator) {
+        let BindingPatternKind::BindingIdentifier(ident) = &decl.id.kind else {
+            return;
+        };
+        // Unwrap awaited calls, e.g. `await getTranslations("Namespace")`
+        let call_expr = match &decl.init {
+            Some(Expression::CallExpression(call_expr)) => call_expr,
+            Some(Expression::AwaitExpression(await_expr)) => match &await_expr.argument {
+                Expression::CallExpression(call_expr) => call_expr,
+                _ => return,
+            },
+            _ => return,
+        };
+        if self.comments.is_ignored(call_expr.span) {
+            return;
+        }
+        let Some(factory) = self.translator_factory(&call_expr.callee) else {
+            return;
+        };
+
+        let namespace =
+            extract_namespace_from_translations_call(call_expr, &factory, &self.constants).or_else(
+                || {
+                    is_namespace_omitted(call_expr, &factory)
+                        .then(|| self.path_namespace.clone())
+                        .flatten()
+                },
+            );
+        let Some(namespace) = namespace else {
+            self.unresolved_namespaces.push(call_expr.span);
+            return;
+        };
+
+        self.translator_namespaces.push(TranslatorNamespace {
+            namespace: namespace.clone(),
+            span: call_expr.span,
+            literal: namespace_literal(call_expr, &factory),
+        });
+        self.translation_functions.push(TranslationFunction {
+            namespace,
+            usages: HashSet::new(),
+        });
+        if let Some(symbol) = ident.symbol_id.get() {
+            self.translators
+                .insert(symbol, self.translation_functions.len() - 1);
+        }
+    }
+
+    /// Record the key used in a translator call, e.g. `t("key");` or `t.rich("key");`
+    fn visit_translator_call(&mut self, node: &CallExpression) {
+        if self.comments.is_ignored(node.span) {
+            return;
+        }
+
+        let callee = match &node.callee {
+            // Static member expression, e.g. `t.rich("key");`
+            Expression::StaticMemberExpression(member_expr) => match &member_expr.object {
+                Expression::Identifier(callee)
+                    if self
+                        .options
+                        .translator_methods
+                        .iter()
+                        .any(|method| method == member_expr.property.name.as_str()) =>
+                {
+                    callee
+                }
+                _ => return,
+            },
+            // Identifier, e.g. `t("key");`
+            Expression::Identifier(callee) => callee,
+            _ => return,
+        };
+
+        if let Some(translation_info) = self
+            .translator(callee)
+            .map(|index| &mut self.translation_functions[index])
+        {
+            let Some(arg) = node.arguments.first() else {
+                return;
+            };
+            match arg
+                .as_expression()
+                .and_then(|expr| self.constants.resolve(expr))
+            {
+                Some(usage) => {
+                    // e.g. `t("title") /* default: "Welcome back" */`
+                    if let Some(default_message) =
+                        self.comments.trailing(node.span).and_then(|comment| {
+                            parse_annotation(&comment.text, &self.options.default_message_prefix)
+                        })
+                    {
+                        self.default_messages
+                            .entry(translation_info.namespace.clone())
+                            .or_default()
+                            .insert(usage.clone(), default_message);
+                    }
+                    // e.g. `/** Shown on empty cart */` above `t("empty")`
+                    if let Some(comment) = self
+                        .comments
+                        .leading(node.span)
+                        .filter(|comment| !self.options.directives.is_directive(&comment.text))
+                    {
+                        self.descriptions
+                            .entry(translation_info.namespace.clone())
+                            .or_default()
+                            .insert(usage.clone(), comment_description(&comment.text));
+                    }
+                    // e.g. `t("greeting", { name, count })`
+                    if let Some(Argument::ObjectExpression(values)) = node.arguments.get(1) {
+                        self.placeholders
+                            .entry(translation_info.namespace.clone())
+                            .or_default()
+                            .entry(usage.clone())
+                            .or_default()
+                            .extend(values.properties.iter().filter_map(|prop| match prop {
+                                ObjectPropertyKind::ObjectProperty(prop) => {
+                                    prop.key.static_name().map(|name| name.to_string())
+                                }
+                                ObjectPropertyKind::SpreadProperty(_) => None,
+                            }));
+                        // Values that render the chunks of a tag, e.g. `link: (chunks) => ...`
+                        let tags: Vec<String> = values
+                            .properties
+                            .iter()
+                            .filter_map(|prop| match prop {
+                                ObjectPropertyKind::ObjectProperty(prop)
+                                    if matches!(
+                                        prop.value,
+                                        Expression::ArrowFunctionExpression(_)
+                                            | Expression::FunctionExpression(_)
+                                    ) =>
+                                {
+                                    prop.key.static_name().map(|name| name.to_string())
+                                }
+                                _ => None,
+                            })
+                            .collect();
+                        if !tags.is_empty() {
+                            self.tags
+                                .entry(translation_info.namespace.clone())
+                                .or_default()
+                                .entry(usage.clone())
+                                .or_default()
+                                .extend(tags);
+                        }
+                    }
+                    self.key_usages.push(KeyUsage {
+                        namespace: translation_info.namespace.clone(),
+                        key: usage.clone(),
+                        translator: callee.name.to_string(),
+                        span: arg.span(),
+                    });
+                    translation_info.usages.insert(usage);
+                }
+                None => {
+                    // Keys declared for a dynamic key, e.g. `// intl-extractor-keys: a, b`
+                    let declared_keys = self.comments.leading(node.span).and_then(|comment| {
+                        parse_annotation(&comment.text, &self.options.directives.keys)
+                    });
+                    match declared_keys {
+                        Some(keys) => {
+                            for key in keys.split(',').map(str::trim).filter(|key| !key.is_empty())
+                            {
+                                self.key_usages.push(KeyUsage {
+                                    namespace: translation_info.namespace.clone(),
+                                    key: key.to_string(),
+                                    translator: callee.name.to_string(),
+                                    span: arg.span(),
+                                });
+                                translation_info.usages.insert(key.to_string());
+                            }
+                        }
+                        None if self.options.dynamic_key_policy == DynamicKeyPolicy::Ignore => {}
+                        None => self.dynamic_keys.push(DynamicKeyUsage {
+                            namespace: translation_info.namespace.clone(),
+                            span: arg.span(),
+                        }),
+                    }
+                }
+            }
         }
     }
 
-    fn enter_scope(&mut self, name: &str) {
-        self.current_scope.push(name.to_string());
+    /// Treat calls to these functions as translator factories, on top of the next-intl ones
+    ///
+    /// Useful for apps that wrap next-intl in their own hook, e.g. `useAppTranslations("ns")`
+    pub fn with_translator_factories<I>(mut self, factories: I) -> Self
+    where
+        I: IntoIterator<Item = FactorySignature>,
+    {
+        self.custom_factories.extend(
+            factories
+                .into_iter()
+                .map(|factory| (factory.name, factory.namespace)),
+        );
+        self
+    }
+
+    /// Resolve namespaces and keys using already known constants, e.g. imported ones
+    pub fn with_constants(mut self, constants: Constants) -> Self {
+        self.constants = constants;
+        self
+    }
+
+    /// Translator calls with keys that could not be resolved statically
+    pub fn dynamic_keys(&self) -> &[DynamicKeyUsage] {
+        &self.dynamic_keys
+    }
+
+    /// Translator factory calls whose namespace could not be resolved statically, e.g.
+    /// `useTranslations(namespace)`
+    pub fn unresolved_namespaces(&self) -> &[Span] {
+        &self.unresolved_namespaces
+    }
+
+    /// Translator factory calls whose namespace is resolved, in source order
+    pub fn translator_namespaces(&self) -> &[TranslatorNamespace] {
+        &self.translator_namespaces
+    }
+
+    /// Every usage of a static key, in source order
+    pub fn key_usages(&self) -> &[KeyUsage] {
+        &self.key_usages
+    }
+
+    /// Calls that pull in the whole message catalog, e.g. `useMessages()`
+    pub fn catalog_usages(&self) -> &[CatalogUsage] {
+        &self.catalog_usages
     }
 
-    fn exit_scope(&mut self) {
-        self.current_scope.pop();
+    /// Default messages declared next to the usages, by namespace and key
+    pub fn default_messages(&self) -> &HashMap<String, HashMap<String, String>> {
+        &self.default_messages
     }
 
-    fn current_scope_name(&self) -> String {
-        self.current_scope.join(".")
+    /// Names of the values passed to the usages, by namespace and key
+    pub fn placeholders(&self) -> &HashMap<String, HashMap<String, BTreeSet<String>>> {
+        &self.placeholders
+    }
+
+    /// Names of the rich text tags passed to the usages, by namespace and key
+    pub fn tags(&self) -> &HashMap<String, HashMap<String, BTreeSet<String>>> {
+        &self.tags
+    }
+
+    /// Descriptions of the usages from the comments above them, by namespace and key
+    pub fn descriptions(&self) -> &HashMap<String, HashMap<String, String>> {
+        &self.descriptions
     }
 
     /// Merge translation functions by namespace
@@ -52,7 +648,7 @@ impl TranslationFunctionVisitor {
     /// together in order to generate the correct json file
     pub fn merge_by_namespace(&self) -> HashMap<String, HashSet<String>> {
         let mut result: HashMap<String, HashSet<String>> = HashMap::new();
-        for value in self.translation_functions.values() {
+        for value in &self.translation_functions {
             let namespace = &value.namespace;
             let usages = &value.usages;
             if let Some(set) = result.get_mut(namespace) {
@@ -72,164 +668,263 @@ impl Default for TranslationFunctionVisitor {
 }
 
 impl<'a> Visit<'a> for TranslationFunctionVisitor {
-    /// Visiting individual functions (mostly components) and set up a new function scope
-    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
-        if let Some(ident) = &it.id {
-            self.enter_scope(ident.name.as_str());
-            println!("Entering scope: {}", self.current_scope_name());
+    /// Collect the constants and imports of the file before visiting it, so namespaces and keys
+    /// declared as constants and translator factories can be resolved
+    ///
+    /// Translators are resolved by their symbol, so all of them are declared before the keys are
+    /// extracted. This way translators created at module scope (e.g. with a top-level
+    /// `await getTranslations()`) can be used in every function of the module, including the ones
+    /// declared above them
+    fn visit_program(&mut self, it: &Program<'a>) {
+        let semantic = SemanticBuilder::new().build(it).semantic;
+        self.module_scope = Some(semantic.scopes().root_scope_id());
+        self.symbols = semantic.into_symbol_table_and_scope_tree().0;
+        self.translators.clear();
+
+        self.constants = Constants::collect_with(it, std::mem::take(&mut self.constants));
+        self.collect_translator_factories(it);
+        self.comments = Comments::collect_with(it, &self.options.directives);
+        if self.comments.is_file_ignored() {
+            return;
         }
-        walk::walk_function(self, it, flags);
-        self.exit_scope();
-    }
-
-    fn visit_variable_declaration(&mut self, it: &oxc::ast::ast::VariableDeclaration<'a>) {
-        for decl in &it.declarations {
-            let (call_expr, is_get_translations) = match &decl.init {
-                Some(Expression::CallExpression(call_expr)) => (call_expr, false),
-                Some(Expression::AwaitExpression(await_expr)) => {
-                    if let Expression::CallExpression(call_expr) = &await_expr.argument {
-                        (call_expr, true)
-                    } else {
-                        continue;
+        TranslatorDeclarations { visitor: self }.visit_program(it);
+        walk::walk_program(self, it);
+    }
+
+    /// Message keys passed to the configured component props, e.g. `<Button labelKey="save" />`
+    fn visit_jsx_opening_element(&mut self, it: &JSXOpeningElement<'a>) {
+        if let Some(props) = self.options.translatable_props.get(&it.name.to_string()) {
+            for attribute in &it.attributes {
+                let JSXAttributeItem::Attribute(attribute) = attribute else {
+                    continue;
+                };
+                let JSXAttributeName::Identifier(name) = &attribute.name else {
+                    continue;
+                };
+                let Some(namespace) = props.get(name.name.as_str()) else {
+                    continue;
+                };
+                if self.comments.is_ignored(attribute.span) {
+                    continue;
+                }
+                let key = match &attribute.value {
+                    Some(JSXAttributeValue::StringLiteral(lit)) => {
+                        Some((lit.value.to_string(), lit.span))
                     }
+                    Some(JSXAttributeValue::ExpressionContainer(container)) => container
+                        .expression
+                        .as_expression()
+                        .and_then(|expr| Some((self.constants.resolve(expr)?, expr.span()))),
+                    _ => None,
+                };
+                if let Some((key, span)) = key {
+                    self.key_usages.push(KeyUsage {
+                        namespace: namespace.clone(),
+                        key: key.clone(),
+                        translator: format!("{}.{}", it.name, name.name),
+                        span,
+                    });
+                    self.translation_functions.push(TranslationFunction {
+                        namespace: namespace.clone(),
+                        usages: HashSet::from([key]),
+                    });
                 }
-                // Not a call expression, skip the declaration
-                _ => continue,
-            };
-
-            let (callee_name, callee_span) = match &call_expr.callee {
-                Expression::Identifier(ident) => (ident.name.to_string(), ident.span),
-                _ => continue,
-            };
-
-            // Early return if the callee is not a useTranslations/getTranslations function function
-            if callee_name != "useTranslations" && callee_name != "getTranslations" {
-                continue;
             }
+        }
+        walk::walk_jsx_opening_element(self, it);
+    }
 
-            let namespace =
-                match extract_namespace_from_translations_call(call_expr, is_get_translations) {
-                    Some(namespace) => namespace,
-                    None => {
-                        // TODO: Calculate line and column from span
-                        warn!(
-                            "Could not find namespace for translations call at {:?}",
-                            callee_span
-                        );
-                        continue;
-                    }
-                };
+    /// Visiting individual translator functions
+    /// e.g. `t("key");` or `t.rich("key");`
+    ///
+    /// The arguments are visited as well, so keys used in nested closures such as
+    /// `useMemo(() => t("key"), [])` or `items.map((item) => t(item.key))` are found too
+    fn visit_call_expression(&mut self, node: &CallExpression<'a>) {
+        if let Some(function) = self
+            .catalog_function(&node.callee)
+            .filter(|_| !self.comments.is_ignored(node.span))
+        {
+            self.catalog_usages.push(CatalogUsage {
+                function,
+                span: node.span,
+            });
+        }
+        self.visit_translator_call(node);
+        walk::walk_call_expression(self, node);
+    }
+}
 
-            let decl_id = match &decl.id.kind {
-                BindingPatternKind::BindingIdentifier(identer) => identer.name.to_string(),
-                _ => continue,
-            };
+/// Declares the translators of a file before its keys are extracted, see
+/// [`TranslationFunctionVisitor::declare_translator`]
+struct TranslatorDeclarations<'v> {
+    visitor: &'v mut TranslationFunctionVisitor,
+}
 
-            let scope = self.current_scope_name();
-            let key = format!("{}:{}", scope, decl_id);
+impl<'a> Visit<'a> for TranslatorDeclarations<'_> {
+    fn visit_variable_declarator(&mut self, it: &VariableDeclarator<'a>) {
+        self.visitor.declare_translator(it);
+        walk::walk_variable_declarator(self, it);
+    }
+}
 
-            self.translation_functions.insert(
-                key,
-                TranslationFunction {
-                    namespace,
-                    usages: HashSet::new(),
-                },
-            );
+/// Unwrap component wrappers such as `memo(...)`, `forwardRef(...)` and `React.memo(...)`
+///
+/// Returns the wrapped component expression, or the expression itself if it is not wrapped
+pub(crate) fn unwrap_component_wrappers<'b, 'a>(expr: &'b Expression<'a>) -> &'b Expression<'a> {
+    let mut current = expr;
+    while let Expression::CallExpression(call_expr) = current {
+        let callee_name = match &call_expr.callee {
+            Expression::Identifier(ident) => ident.name.as_str(),
+            Expression::StaticMemberExpression(member_expr) => member_expr.property.name.as_str(),
+            _ => break,
+        };
+        if !COMPONENT_WRAPPERS.contains(&callee_name) {
+            break;
+        }
+        match call_expr
+            .arguments
+            .first()
+            .and_then(|arg| arg.as_expression())
+        {
+            Some(inner) => current = inner,
+            None => break,
         }
     }
+    current
+}
 
-    /// Visiting individual translator functions
-    /// e.g. `t("key");` or `t.rich("key");`
-    fn visit_call_expression(&mut self, node: &CallExpression) {
-        match &node.callee {
-            // Static member expression, e.g. `t.rich("key");`
-            Expression::StaticMemberExpression(member_expr) => {
-                if let Expression::Identifier(callee) = &member_expr.object {
-                    let scope = self.current_scope_name();
-
-                    let key = format!("{}:{}", scope, callee.name);
-                    if let Some(translation_info) = self.translation_functions.get_mut(&key) {
-                        if let Some(arg) = node.arguments.first() {
-                            if let Expression::StringLiteral(str_lit) = &arg.to_expression() {
-                                translation_info.usages.insert(str_lit.value.to_string());
-                            }
-                        }
-                    }
+fn extract_namespace_from_translations_call(
+    call_expr: &CallExpression,
+    factory: &TranslatorFactory,
+    constants: &Constants,
+) -> Option<String> {
+    let argument = |index: usize| {
+        call_expr
+            .arguments
+            .get(index)
+            .and_then(|arg| arg.as_expression())
+            .and_then(|expr| constants.resolve(expr))
+    };
+
+    match factory {
+        // useTranslations only accepts the namespace as a string
+        TranslatorFactory::UseTranslations => argument(0),
+        // For getTranslations, the namespace can also be passed as an object property next to
+        // other options such as the locale
+        TranslatorFactory::GetTranslations
+        | TranslatorFactory::Custom(NamespaceSignature::NextIntl) => {
+            match call_expr.arguments.first()? {
+                Argument::ObjectExpression(obj) => {
+                    object_property(obj, "namespace").and_then(|value| constants.resolve(value))
                 }
+                _ => argument(0),
             }
-            // Identifier, e.g. `t("key");`
-            Expression::Identifier(callee) => {
-                let scope = self.current_scope_name();
-                let key = format!("{}:{}", scope, callee.name);
-                if let Some(translation_info) = self.translation_functions.get_mut(&key) {
-                    if let Some(arg) = node.arguments.first() {
-                        if let Expression::StringLiteral(str_lit) = &arg.to_expression() {
-                            translation_info.usages.insert(str_lit.value.to_string());
-                        }
-                    }
+        }
+        TranslatorFactory::Custom(NamespaceSignature::Argument(index)) => argument(*index),
+        TranslatorFactory::Custom(NamespaceSignature::Property(name)) => {
+            match call_expr.arguments.first()? {
+                Argument::ObjectExpression(obj) => {
+                    object_property(obj, name).and_then(|value| constants.resolve(value))
                 }
+                _ => None,
             }
-            _ => (),
+        }
+        TranslatorFactory::Custom(NamespaceSignature::Template(template)) => {
+            let arguments: Vec<_> = (0..call_expr.arguments.len()).map(argument).collect();
+            fill_template(template, &arguments)
         }
     }
 }
 
-fn extract_namespace_from_translations_call(
-    call_expr: &CallExpression,
-    is_get_translations: bool,
-) -> Option<String> {
-    if is_get_translations {
-        // For getTranslations, expect an object with a namespace property
-        call_expr.arguments.first().and_then(|arg| {
-            if let Argument::ObjectExpression(obj) = arg {
-                obj.properties.iter().find_map(|prop| {
-                    if let ObjectPropertyKind::ObjectProperty(prop) = prop {
-                        match (&prop.key, &prop.value) {
-                            (
-                                PropertyKey::StaticIdentifier(key_ident),
-                                Expression::StringLiteral(value_lit),
-                            ) if key_ident.name == "namespace" => Some(value_lit.value.to_string()),
-                            _ => None,
-                        }
-                    } else {
-                        None
-                    }
-                })
-            } else {
-                None
-            }
-        })
-    } else {
-        // For useTranslations, expect a string literal as the first argument
-        call_expr.arguments.first().and_then(|arg| {
-            if let Argument::StringLiteral(str_lit) = arg {
-                Some(str_lit.value.to_string())
-            } else {
-                None
-            }
-        })
+/// The span of the literal a translator's namespace is written in, e.g. `'Cart'` of
+/// `useTranslations('Cart')` or of `getTranslations({ namespace: 'Cart' })`
+///
+/// `None` if the namespace is resolved from a constant or composed of several values
+fn namespace_literal(call_expr: &CallExpression, factory: &TranslatorFactory) -> Option<Span> {
+    let literal = |expr: &Expression| match expr {
+        Expression::StringLiteral(lit) => Some(lit.span),
+        Expression::TemplateLiteral(lit) if lit.expressions.is_empty() => Some(lit.span),
+        _ => None,
+    };
+    let argument = |index: usize| {
+        call_expr
+            .arguments
+            .get(index)
+            .and_then(|arg| arg.as_expression())
+            .and_then(literal)
+    };
+    let property = |name: &str| match call_expr.arguments.first()? {
+        Argument::ObjectExpression(obj) => object_property(obj, name).and_then(literal),
+        _ => None,
+    };
+
+    match factory {
+        TranslatorFactory::UseTranslations => argument(0),
+        TranslatorFactory::GetTranslations
+        | TranslatorFactory::Custom(NamespaceSignature::NextIntl) => {
+            property("namespace").or_else(|| argument(0))
+        }
+        TranslatorFactory::Custom(NamespaceSignature::Argument(index)) => argument(*index),
+        TranslatorFactory::Custom(NamespaceSignature::Property(name)) => property(name),
+        TranslatorFactory::Custom(NamespaceSignature::Template(_)) => None,
+    }
+}
+
+/// Whether a translator is created without a namespace, e.g. `useTranslations()` or
+/// `getTranslations({ locale })`
+fn is_namespace_omitted(call_expr: &CallExpression, factory: &TranslatorFactory) -> bool {
+    let first = call_expr.arguments.first();
+    let has_property = |name: &str| match first {
+        Some(Argument::ObjectExpression(obj)) => object_property(obj, name).is_some(),
+        Some(_) => true,
+        None => false,
+    };
+
+    match factory {
+        TranslatorFactory::UseTranslations => first.is_none(),
+        TranslatorFactory::GetTranslations
+        | TranslatorFactory::Custom(NamespaceSignature::NextIntl) => !has_property("namespace"),
+        TranslatorFactory::Custom(NamespaceSignature::Argument(index)) => {
+            call_expr.arguments.len() <= *index
+        }
+        TranslatorFactory::Custom(NamespaceSignature::Property(name)) => !has_property(name),
+        TranslatorFactory::Custom(NamespaceSignature::Template(_)) => false,
     }
 }
 
+/// Find the value of an object property by name. The last property wins, like in JS
+fn object_property<'b, 'a>(
+    obj: &'b ObjectExpression<'a>,
+    name: &str,
+) -> Option<&'b Expression<'a>> {
+    obj.properties.iter().rev().find_map(|prop| match prop {
+        ObjectPropertyKind::ObjectProperty(prop) if prop.key.is_specific_static_name(name) => {
+            Some(&prop.value)
+        }
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::Constant;
+    use oxc::{allocator::Allocator, parser::Parser, span::SourceType};
 
-    #[test]
-    fn test_new_visitor() {
-        let visitor = TranslationFunctionVisitor::new();
-        assert!(visitor.translation_functions.is_empty());
-        assert!(visitor.current_scope.is_empty());
+    /// Imports of the translator factories, like every component has them
+    const IMPORTS: &str = "import { useTranslations } from 'next-intl';\n\
+        import { getTranslations } from 'next-intl/server';\n";
+
+    fn visit(source: &str, mut visitor: TranslationFunctionVisitor) -> TranslationFunctionVisitor {
+        let source = format!("{IMPORTS}{source}");
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, &source, SourceType::tsx()).parse();
+        visitor.visit_program(&ret.program);
+        visitor
     }
 
-    #[test]
-    fn test_scope_management() {
-        let mut visitor = TranslationFunctionVisitor::new();
-        visitor.enter_scope("Component");
-        visitor.enter_scope("SubComponent");
-        assert_eq!(visitor.current_scope_name(), "Component.SubComponent");
-        visitor.exit_scope();
-        assert_eq!(visitor.current_scope_name(), "Component");
+    fn extract(source: &str) -> HashMap<String, HashSet<String>> {
+        visit(source, TranslationFunctionVisitor::new()).merge_by_namespace()
     }
 
     /// Test that merge_by_namespace correctly merges translation functions from the same namespace.
@@ -244,24 +939,18 @@ mod tests {
         let mut visitor = TranslationFunctionVisitor::new();
 
         // Add some test translation functions
-        visitor.translation_functions.insert(
-            "comp1".to_string(),
-            TranslationFunction {
-                namespace: "ns1".to_string(),
-                usages: ["key1".to_string(), "key2".to_string()]
-                    .into_iter()
-                    .collect(),
-            },
-        );
-        visitor.translation_functions.insert(
-            "comp2".to_string(),
-            TranslationFunction {
-                namespace: "ns1".to_string(),
-                usages: ["key2".to_string(), "key3".to_string()]
-                    .into_iter()
-                    .collect(),
-            },
-        );
+        visitor.translation_functions.push(TranslationFunction {
+            namespace: "ns1".to_string(),
+            usages: ["key1".to_string(), "key2".to_string()]
+                .into_iter()
+                .collect(),
+        });
+        visitor.translation_functions.push(TranslationFunction {
+            namespace: "ns1".to_string(),
+            usages: ["key2".to_string(), "key3".to_string()]
+                .into_iter()
+                .collect(),
+        });
 
         let merged = visitor.merge_by_namespace();
         assert_eq!(merged.len(), 1);
@@ -270,4 +959,990 @@ mod tests {
         assert!(merged["ns1"].contains("key2"));
         assert!(merged["ns1"].contains("key3"));
     }
+
+    #[test]
+    fn test_arrow_function_components() {
+        let merged = extract(
+            r#"
+            export const First = () => {
+                const t = useTranslations('First');
+                return <p>{t('title')}</p>;
+            };
+
+            export const Second = function () {
+                const t = useTranslations('Second');
+                const label = t('label');
+                return <p>{label}</p>;
+            };
+        "#,
+        );
+
+        assert_eq!(merged.len(), 2);
+        assert!(merged["First"].contains("title"));
+        assert!(merged["Second"].contains("label"));
+    }
+
+    #[test]
+    fn test_arrow_function_components_do_not_collide() {
+        let merged = extract(
+            r#"
+            const First = () => {
+                const t = useTranslations('First');
+                return <p>{t('one')}</p>;
+            };
+
+            const Second = () => {
+                const t = useTranslations('Second');
+                return <p>{t('two')}</p>;
+            };
+        "#,
+        );
+
+        assert_eq!(merged["First"].len(), 1);
+        assert!(merged["First"].contains("one"));
+        assert_eq!(merged["Second"].len(), 1);
+        assert!(merged["Second"].contains("two"));
+    }
+
+    #[test]
+    fn test_translators_in_sibling_blocks() {
+        let merged = extract(
+            r#"
+            export function Component({ admin }) {
+                if (admin) {
+                    const t = useTranslations('Admin');
+                    return <p>{t('title')}</p>;
+                } else {
+                    const t = useTranslations('User');
+                    return <p>{t('name')}</p>;
+                }
+            }
+        "#,
+        );
+
+        assert_eq!(merged["Admin"].len(), 1);
+        assert!(merged["Admin"].contains("title"));
+        assert_eq!(merged["User"].len(), 1);
+        assert!(merged["User"].contains("name"));
+    }
+
+    #[test]
+    fn test_shadowed_translators() {
+        let merged = extract(
+            r#"
+            export function Component({ items }) {
+                const t = useTranslations('Component');
+                const rows = items.map(function Row(t) {
+                    return <p>{t('shadowed.param')}</p>;
+                });
+                {
+                    const t = (key) => key;
+                    t('shadowed.block');
+                }
+                try {} catch (t) { t('shadowed.catch'); }
+                return <p>{t('title')}</p>;
+            }
+
+            export function Local() {
+                const useTranslations = (ns) => (key) => key;
+                const t = useTranslations('Local');
+                return <p>{t('title')}</p>;
+            }
+        "#,
+        );
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged["Component"].len(), 1);
+        assert!(merged["Component"].contains("title"));
+    }
+
+    #[test]
+    fn test_nested_closures() {
+        let merged = extract(
+            r#"
+            export function Component({ items }) {
+                const t = useTranslations('Component');
+                const title = useMemo(() => t('title'), []);
+                const onClick = useCallback(() => alert(t('clicked')), []);
+                const label = (() => t('iife'))();
+                return (
+                    <ul title={title} onClick={onClick}>
+                        {items.map((item) => <li key={item.id}>{t('item', { name: item.name })}</li>)}
+                        {t.rich('rich', { b: (chunks) => <b>{t('nested')}{chunks}</b> })}
+                    </ul>
+                );
+            }
+        "#,
+        );
+
+        let usages = &merged["Component"];
+        assert_eq!(usages.len(), 6);
+        for key in ["title", "clicked", "iife", "item", "rich", "nested"] {
+            assert!(usages.contains(key));
+        }
+    }
+
+    #[test]
+    fn test_module_scope_translators() {
+        let merged = extract(
+            r#"
+            const t = await getTranslations('Utils');
+            const title = t('title');
+
+            export function formatPrice(price) {
+                return t('price', { price });
+            }
+
+            export const labels = { save: t('save') };
+
+            export async function generateMetadata() {
+                const t = await getTranslations('Metadata');
+                return { title: t('title') };
+            }
+        "#,
+        );
+
+        assert_eq!(merged["Utils"].len(), 3);
+        for key in ["title", "price", "save"] {
+            assert!(merged["Utils"].contains(key));
+        }
+        assert!(merged["Metadata"].contains("title"));
+    }
+
+    #[test]
+    fn test_default_message_annotations() {
+        let source = r#"
+            export function Component() {
+                const t = useTranslations('Component');
+                const title = t('title'); // default: Welcome back
+                return (
+                    <div>
+                        {t('subtitle') /* default: "Sign in to continue" */}
+                        {t('custom') /* en: 'Custom' */}
+                        {t('plain')}
+                    </div>
+                );
+            }
+        "#;
+
+        let visitor = visit(source, TranslationFunctionVisitor::new());
+        let defaults = &visitor.default_messages()["Component"];
+        assert_eq!(defaults.len(), 2);
+        assert_eq!(defaults["title"], "Welcome back");
+        assert_eq!(defaults["subtitle"], "Sign in to continue");
+
+        let visitor = visit(
+            source,
+            TranslationFunctionVisitor::new().with_default_message_prefix("en:"),
+        );
+        let defaults = &visitor.default_messages()["Component"];
+        assert_eq!(defaults.len(), 1);
+        assert_eq!(defaults["custom"], "Custom");
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let visitor = visit(
+            r#"
+            export function Component({ user, items }) {
+                const t = useTranslations('Component');
+                return (
+                    <p>
+                        {t('greeting', { name: user.name, count: items.length })}
+                        {t('greeting', { ...user, 'last-seen': user.lastSeen })}
+                        {t('title')}
+                    </p>
+                );
+            }
+        "#,
+            TranslationFunctionVisitor::new(),
+        );
+
+        let placeholders = &visitor.placeholders()["Component"];
+        assert_eq!(placeholders.len(), 1);
+        assert_eq!(
+            placeholders["greeting"],
+            BTreeSet::from([
+                "count".to_string(),
+                "last-seen".to_string(),
+                "name".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rich_text_tags() {
+        let visitor = visit(
+            r#"
+            export function Component() {
+                const t = useTranslations('Component');
+                return (
+                    <p>
+                        {t.rich('terms', { link: (chunks) => <a href="/terms">{chunks}</a>, year: 2024 })}
+                        {t.markup('notice', { b: function (chunks) { return `<b>${chunks}</b>`; } })}
+                        {t('title', { name: 'Ada' })}
+                    </p>
+                );
+            }
+        "#,
+            TranslationFunctionVisitor::new(),
+        );
+
+        let tags = &visitor.tags()["Component"];
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags["terms"], BTreeSet::from(["link".to_string()]));
+        assert_eq!(tags["notice"], BTreeSet::from(["b".to_string()]));
+        // Tags are values of the message too
+        assert_eq!(
+            visitor.placeholders()["Component"]["terms"],
+            BTreeSet::from(["link".to_string(), "year".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_descriptions() {
+        let visitor = visit(
+            r#"
+            export function Cart() {
+                const t = useTranslations('Cart');
+                /** Shown on empty cart */
+                const empty = t('empty');
+                return (
+                    <div>
+                        {/* Checkout button label */}
+                        {t('checkout')}
+                        {t('total')}
+                    </div>
+                );
+            }
+        "#,
+            TranslationFunctionVisitor::new(),
+        );
+
+        let descriptions = &visitor.descriptions()["Cart"];
+        assert_eq!(descriptions.len(), 2);
+        assert_eq!(descriptions["empty"], "Shown on empty cart");
+        assert_eq!(descriptions["checkout"], "Checkout button label");
+    }
+
+    #[test]
+    fn test_ignore_directives() {
+        let visitor = visit(
+            r#"
+            export function Component({ status }) {
+                const t = useTranslations('Component');
+                // intl-extractor-ignore-next-line
+                const label = t(status);
+                return (
+                    <div>
+                        {t('title')}
+                        {/* intl-extractor-ignore-next-line */}
+                        {t('ignored')}
+                    </div>
+                );
+            }
+        "#,
+            TranslationFunctionVisitor::new(),
+        );
+        let merged = visitor.merge_by_namespace();
+        assert_eq!(merged["Component"].len(), 1);
+        assert!(merged["Component"].contains("title"));
+        assert!(visitor.dynamic_keys().is_empty());
+
+        let visitor = visit(
+            r#"
+            // intl-extractor-ignore-file
+            export function Component({ status }) {
+                const t = useTranslations('Component');
+                return <p>{t('title')} {t(status)}</p>;
+            }
+        "#,
+            TranslationFunctionVisitor::new(),
+        );
+        assert!(visitor.merge_by_namespace().is_empty());
+        assert!(visitor.dynamic_keys().is_empty());
+    }
+
+    #[test]
+    fn test_declared_dynamic_keys() {
+        let visitor = visit(
+            r#"
+            export function Status({ status, other }) {
+                const t = useTranslations('Status');
+                // intl-extractor-keys: status.active, status.archived
+                const label = t(`status.${status}`);
+                return (
+                    <div>
+                        {label}
+                        {/* intl-extractor-keys: other.one */}
+                        {t(other)}
+                        {t(other)}
+                    </div>
+                );
+            }
+        "#,
+            TranslationFunctionVisitor::new(),
+        );
+
+        let usages = &visitor.merge_by_namespace()["Status"];
+        assert_eq!(usages.len(), 3);
+        for key in ["status.active", "status.archived", "other.one"] {
+            assert!(usages.contains(key));
+        }
+        assert_eq!(visitor.dynamic_keys().len(), 1);
+        assert!(visitor.descriptions().is_empty());
+    }
+
+    #[test]
+    fn test_anonymous_default_export() {
+        let merged = extract(
+            r#"
+            export default function () {
+                const t = useTranslations('Page');
+                return <p>{t('title')}</p>;
+            }
+        "#,
+        );
+        assert!(merged["Page"].contains("title"));
+
+        let merged = extract(
+            r#"
+            export default () => {
+                const t = useTranslations('Layout');
+                return <p>{t('footer')}</p>;
+            };
+        "#,
+        );
+        assert!(merged["Layout"].contains("footer"));
+    }
+
+    #[test]
+    fn test_wrapped_components() {
+        let merged = extract(
+            r#"
+            const Button = memo(function Button() {
+                const t = useTranslations('Button');
+                return <button>{t('label')}</button>;
+            });
+
+            const Input = React.forwardRef((props, ref) => {
+                const t = useTranslations('Input');
+                return <input ref={ref} placeholder={t('placeholder')} />;
+            });
+
+            const Card = memo(forwardRef((props, ref) => {
+                const t = useTranslations('Card');
+                return <div ref={ref}>{t('title')}</div>;
+            }));
+
+            export default memo(() => {
+                const t = useTranslations('Page');
+                return <p>{t('heading')}</p>;
+            });
+        "#,
+        );
+
+        assert!(merged["Button"].contains("label"));
+        assert!(merged["Input"].contains("placeholder"));
+        assert!(merged["Card"].contains("title"));
+        assert!(merged["Page"].contains("heading"));
+    }
+
+    #[test]
+    fn test_get_translations_namespace_forms() {
+        let merged = extract(
+            r#"
+            export async function ObjectForm() {
+                const t = await getTranslations({ namespace: 'ObjectForm' });
+                return <p>{t('title')}</p>;
+            }
+
+            export async function StringForm() {
+                const t = await getTranslations('StringForm');
+                return <p>{t('title')}</p>;
+            }
+        "#,
+        );
+
+        assert!(merged["ObjectForm"].contains("title"));
+        assert!(merged["StringForm"].contains("title"));
+    }
+
+    #[test]
+    fn test_get_translations_with_extra_properties() {
+        let merged = extract(
+            r#"
+            export async function Locale({ locale }) {
+                const t = await getTranslations({ locale, namespace: 'Locale' });
+                return <p>{t('title')}</p>;
+            }
+
+            export async function QuotedKey({ locale }) {
+                const t = await getTranslations({ 'namespace': 'QuotedKey', locale });
+                return <p>{t('title')}</p>;
+            }
+
+            export async function ComputedKey({ locale }) {
+                const t = await getTranslations({ locale, ['namespace']: 'ComputedKey' });
+                return <p>{t('title')}</p>;
+            }
+
+            export async function Dynamic({ locale, namespace }) {
+                const t = await getTranslations({ [namespace]: 'Ignored', locale });
+                return <p>{t('title')}</p>;
+            }
+        "#,
+        );
+
+        assert_eq!(merged.len(), 3);
+        assert!(merged["Locale"].contains("title"));
+        assert!(merged["QuotedKey"].contains("title"));
+        assert!(merged["ComputedKey"].contains("title"));
+    }
+
+    #[test]
+    fn test_translator_methods() {
+        let source = r#"
+            export function Component() {
+                const t = useTranslations('Component');
+                t.has('optional');
+                t.toString('garbage');
+                return <p>{t.rich('rich')} {t.markup('markup')} {t.raw('raw')} {t.maybe('maybe')}</p>;
+            }
+        "#;
+
+        let merged = extract(source);
+        let usages = &merged["Component"];
+        assert_eq!(usages.len(), 4);
+        for key in ["optional", "rich", "markup", "raw"] {
+            assert!(usages.contains(key));
+        }
+
+        let visitor = visit(
+            source,
+            TranslationFunctionVisitor::new().with_translator_methods(["maybe"]),
+        );
+        assert!(visitor.merge_by_namespace()["Component"].contains("maybe"));
+    }
+
+    #[test]
+    fn test_template_literal_keys() {
+        let merged = extract(
+            r#"
+            export function Component({ id }) {
+                const t = useTranslations(`Component`);
+                return <p>{t(`title`)} {t.rich(`rich`)} {t(`item.${id}`)}</p>;
+            }
+
+            export async function Server() {
+                const t = await getTranslations({ namespace: `Server` });
+                return <p>{t(`title`)}</p>;
+            }
+        "#,
+        );
+
+        assert_eq!(merged["Component"].len(), 2);
+        assert!(merged["Component"].contains("title"));
+        assert!(merged["Component"].contains("rich"));
+        assert!(merged["Server"].contains("title"));
+    }
+
+    #[test]
+    fn test_dynamic_keys() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+            export function Component({ status, id }) {
+                const t = useTranslations('Component');
+                return <p>{t('static')} {t(status)} {t.rich(`item.${id}`)}</p>;
+            }
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = TranslationFunctionVisitor::new();
+        visitor.visit_program(&ret.program);
+
+        let dynamic_keys = visitor.dynamic_keys();
+        assert_eq!(dynamic_keys.len(), 2);
+        assert!(dynamic_keys.iter().all(|d| d.namespace == "Component"));
+        assert_eq!(dynamic_keys[0].span.source_text(source), "status");
+        assert_eq!(dynamic_keys[1].span.source_text(source), "`item.${id}`");
+    }
+
+    #[test]
+    fn test_translator_namespaces() {
+        let source = r#"
+            import { useTranslations as useT } from 'next-intl';
+            const NS = 'Cart';
+            export function Cart() {
+                const t = useT('Cart');
+                const summary = useT(`Cart.Summary`);
+                const other = useT(NS);
+                return null;
+            }
+            export async function generateMetadata() {
+                const t = await getTranslations({ locale: 'en', namespace: "Cart" });
+            }
+        "#;
+        let visitor = visit(source, TranslationFunctionVisitor::new());
+        let source = format!("{IMPORTS}{source}");
+        let namespaces: Vec<(&str, Option<&str>)> = visitor
+            .translator_namespaces()
+            .iter()
+            .map(|translator| {
+                (
+                    translator.namespace.as_str(),
+                    translator
+                        .literal
+                        .map(|span| &source[span.start as usize..span.end as usize]),
+                )
+            })
+            .collect();
+        assert_eq!(
+            namespaces,
+            [
+                ("Cart", Some("'Cart'")),
+                ("Cart.Summary", Some("`Cart.Summary`")),
+                ("Cart", None),
+                ("Cart", Some("\"Cart\"")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unresolved_namespaces() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+            export function Component({ namespace }) {
+                const t = useTranslations(namespace);
+                return <p>{t('title')}</p>;
+            }
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = TranslationFunctionVisitor::new();
+        visitor.visit_program(&ret.program);
+
+        let unresolved = visitor.unresolved_namespaces();
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(
+            unresolved[0].source_text(source),
+            "useTranslations(namespace)"
+        );
+    }
+
+    #[test]
+    fn test_string_concatenation_keys() {
+        let merged = extract(
+            r#"
+            export function Component({ id }) {
+                const t = useTranslations('Comp' + 'onent');
+                return <p>{t('form.' + 'submit')} {t('a' + (`b` + 'c'))} {t('item.' + id)}</p>;
+            }
+        "#,
+        );
+
+        let usages = &merged["Component"];
+        assert_eq!(usages.len(), 2);
+        assert!(usages.contains("form.submit"));
+        assert!(usages.contains("abc"));
+    }
+
+    #[test]
+    fn test_const_namespaces() {
+        let merged = extract(
+            r#"
+            const NS = 'Checkout';
+
+            export function Checkout() {
+                const t = useTranslations(NS);
+                return <p>{t('title')}</p>;
+            }
+
+            export async function Server({ locale }) {
+                const namespace = NS + '.server';
+                const t = await getTranslations({ locale, namespace });
+                return <p>{t('title')}</p>;
+            }
+        "#,
+        );
+
+        assert!(merged["Checkout"].contains("title"));
+        assert!(merged["Checkout.server"].contains("title"));
+    }
+
+    #[test]
+    fn test_template_literal_namespaces() {
+        let merged = extract(
+            r#"
+            const BASE = 'Checkout';
+            const KEYS = { TITLE: 'title' } as const;
+
+            export function Details({ id }) {
+                const t = useTranslations(`${BASE}.details`);
+                return <p>{t(`${KEYS.TITLE}`)} {t(`item.${id}`)}</p>;
+            }
+        "#,
+        );
+
+        assert_eq!(
+            merged["Checkout.details"],
+            HashSet::from(["title".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_const_object_and_enum_keys() {
+        let merged = extract(
+            r#"
+            const Labels = { save: 'save', cancel: 'cancel' } as const;
+            enum Status { Active = 'status.active' }
+
+            export function Form() {
+                const t = useTranslations('Form');
+                return <p>{t(Labels.save)} {t(Labels['cancel'])} {t(Status.Active)} {t(Labels.missing)}</p>;
+            }
+        "#,
+        );
+
+        let usages = &merged["Form"];
+        assert_eq!(usages.len(), 3);
+        assert!(usages.contains("save"));
+        assert!(usages.contains("cancel"));
+        assert!(usages.contains("status.active"));
+    }
+
+    #[test]
+    fn test_imported_constants() {
+        let source = r#"
+            import { KEYS, NAMESPACES } from './keys';
+
+            export function Cart() {
+                const t = useTranslations(NAMESPACES.CART);
+                return <p>{t(KEYS.SUBMIT)}</p>;
+            }
+        "#;
+        let mut imported = Constants::default();
+        imported.insert(
+            "KEYS".to_string(),
+            Constant::Object(HashMap::from([(
+                "SUBMIT".to_string(),
+                Constant::String("submit".to_string()),
+            )])),
+        );
+        imported.insert(
+            "NAMESPACES".to_string(),
+            Constant::Object(HashMap::from([(
+                "CART".to_string(),
+                Constant::String("Cart".to_string()),
+            )])),
+        );
+
+        let visitor = visit(
+            source,
+            TranslationFunctionVisitor::new().with_constants(imported),
+        );
+
+        assert!(visitor.merge_by_namespace()["Cart"].contains("submit"));
+    }
+
+    #[test]
+    fn test_only_next_intl_factories() {
+        let source = r#"
+            import { useTranslations } from 'next-intl';
+
+            export function Component() {
+                const t = useTranslations('Component');
+                return <p>{t('title')}</p>;
+            }
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = TranslationFunctionVisitor::new();
+        visitor.visit_program(&ret.program);
+        assert!(visitor.merge_by_namespace()["Component"].contains("title"));
+
+        let source = r#"
+            import { useTranslations } from './other-library';
+            import { getTranslations } from 'next-intl-lookalike';
+
+            export async function Component() {
+                const t = useTranslations('Component');
+                const t2 = await getTranslations('Other');
+                return <p>{t('title')} {t2('title')}</p>;
+            }
+
+            export function Local() {
+                const useTranslations = (ns) => (key) => key;
+                const t = useTranslations('Local');
+                return <p>{t('title')}</p>;
+            }
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = TranslationFunctionVisitor::new();
+        visitor.visit_program(&ret.program);
+        assert!(visitor.merge_by_namespace().is_empty());
+    }
+
+    #[test]
+    fn test_required_factories() {
+        let source = r#"
+            const { useTranslations: useT } = require('next-intl');
+            const intl = require('next-intl/server');
+
+            function Required() {
+                const t = useT('Required');
+                return t('title');
+            }
+
+            async function Namespace() {
+                const t = await intl.getTranslations('Namespace');
+                return t('title');
+            }
+
+            module.exports = { Required, Namespace };
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::cjs()).parse();
+        assert!(ret.errors.is_empty());
+        let mut visitor = TranslationFunctionVisitor::new();
+        visitor.visit_program(&ret.program);
+
+        let merged = visitor.merge_by_namespace();
+        assert!(merged["Required"].contains("title"));
+        assert!(merged["Namespace"].contains("title"));
+    }
+
+    #[test]
+    fn test_aliased_factories() {
+        let source = r#"
+            import { useTranslations as useT } from 'next-intl';
+            import { getTranslations as getT } from 'next-intl/server';
+            import * as intl from 'next-intl';
+
+            export function Alias() {
+                const t = useT('Alias');
+                return <p>{t('title')}</p>;
+            }
+
+            export async function ServerAlias() {
+                const t = await getT({ namespace: 'ServerAlias' });
+                return <p>{t('title')}</p>;
+            }
+
+            export function Namespace() {
+                const t = intl.useTranslations('Namespace');
+                return <p>{t('title')}</p>;
+            }
+
+            export function NotImported() {
+                const t = useTranslations('NotImported');
+                return <p>{t('title')}</p>;
+            }
+        "#;
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::tsx()).parse();
+        let mut visitor = TranslationFunctionVisitor::new();
+        visitor.visit_program(&ret.program);
+
+        let merged = visitor.merge_by_namespace();
+        assert_eq!(merged.len(), 3);
+        assert!(merged["Alias"].contains("title"));
+        assert!(merged["ServerAlias"].contains("title"));
+        assert!(merged["Namespace"].contains("title"));
+    }
+
+    #[test]
+    fn test_custom_factories() {
+        let visitor = visit(
+            r#"
+            import { useAppTranslations, getAppTranslations } from '@/i18n';
+
+            export async function Component() {
+                const t = useAppTranslations('Component');
+                const t2 = await getAppTranslations({ namespace: 'Server' });
+                return <p>{t('title')} {t2('title')}</p>;
+            }
+        "#,
+            TranslationFunctionVisitor::new().with_translator_factories([
+                FactorySignature::new("useAppTranslations"),
+                FactorySignature::new("getAppTranslations"),
+            ]),
+        );
+
+        let merged = visitor.merge_by_namespace();
+        assert!(merged["Component"].contains("title"));
+        assert!(merged["Server"].contains("title"));
+    }
+
+    #[test]
+    fn test_custom_factory_signatures() {
+        let visitor = visit(
+            r#"
+            export function Component({ locale }) {
+                const t1 = useAdmin(locale, 'Argument');
+                const t2 = createT({ locale, ns: 'Property' });
+                const t3 = useSection('Template', 'page');
+                const t4 = useFixed();
+                const t5 = useAdmin(locale);
+                return <p>{t1('one')} {t2('two')} {t3('three')} {t4('four')} {t5('five')}</p>;
+            }
+        "#,
+            TranslationFunctionVisitor::new().with_translator_factories([
+                FactorySignature::new("useAdmin").with_namespace(NamespaceSignature::Argument(1)),
+                FactorySignature::new("createT")
+                    .with_namespace(NamespaceSignature::Property("ns".to_string())),
+                FactorySignature::new("useSection")
+                    .with_namespace(NamespaceSignature::Template("Admin.{0}.{1}".to_string())),
+                FactorySignature::new("useFixed")
+                    .with_namespace(NamespaceSignature::Template("Fixed".to_string())),
+            ]),
+        );
+
+        let merged = visitor.merge_by_namespace();
+        assert_eq!(merged.len(), 4);
+        assert!(merged["Argument"].contains("one"));
+        assert!(merged["Property"].contains("two"));
+        assert!(merged["Admin.Template.page"].contains("three"));
+        assert!(merged["Fixed"].contains("four"));
+    }
+
+    #[test]
+    fn test_path_namespace() {
+        let visitor = visit(
+            r#"
+            export async function Component() {
+                const t = useTranslations();
+                const t2 = await getTranslations({ locale: 'en' });
+                const t3 = useTranslations('Explicit');
+                return <p>{t('title')} {t2('subtitle')} {t3('other')}</p>;
+            }
+        "#,
+            TranslationFunctionVisitor::new().with_path_namespace("Cart"),
+        );
+
+        let merged = visitor.merge_by_namespace();
+        assert_eq!(
+            merged["Cart"],
+            HashSet::from(["title".to_string(), "subtitle".to_string()])
+        );
+        assert!(merged["Explicit"].contains("other"));
+
+        // Without a path namespace, translators without a namespace are skipped
+        assert!(extract(
+            "export function Component() { const t = useTranslations(); t('title'); }"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_key_usages() {
+        let source = r#"
+            export function Component() {
+                const t = useTranslations('Component');
+                return <Button labelKey="save" title={t('title')} />;
+            }
+        "#;
+        let visitor = visit(
+            source,
+            TranslationFunctionVisitor::new().with_translatable_props(HashMap::from([(
+                "Button".to_string(),
+                HashMap::from([("labelKey".to_string(), "Buttons".to_string())]),
+            )])),
+        );
+
+        let source = format!("{IMPORTS}{source}");
+        let usages: Vec<_> = visitor
+            .key_usages()
+            .iter()
+            .map(|usage| {
+                (
+                    usage.namespace.as_str(),
+                    usage.key.as_str(),
+                    usage.translator.as_str(),
+                    usage.span.source_text(&source),
+                )
+            })
+            .collect();
+        assert_eq!(
+            usages,
+            [
+                ("Buttons", "save", "Button.labelKey", "\"save\""),
+                ("Component", "title", "t", "'title'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_catalog_usages() {
+        let visitor = visit(
+            r#"
+            import { useMessages as useAllMessages } from 'next-intl';
+            import * as server from 'next-intl/server';
+
+            export function Client() {
+                const messages = useAllMessages();
+                return <Provider messages={messages} />;
+            }
+
+            export async function Layout() {
+                const messages = await server.getMessages();
+                // intl-extractor-ignore-next-line
+                const ignored = await server.getMessages();
+                return <Provider messages={messages} />;
+            }
+
+            export function Local() {
+                const useAllMessages = () => ({});
+                return useAllMessages();
+            }
+        "#,
+            TranslationFunctionVisitor::new(),
+        );
+
+        let functions: Vec<_> = visitor
+            .catalog_usages()
+            .iter()
+            .map(|usage| usage.function.as_str())
+            .collect();
+        assert_eq!(functions, ["useMessages", "getMessages"]);
+    }
+
+    #[test]
+    fn test_translatable_props() {
+        let visitor = visit(
+            r#"
+            const SAVE = 'save';
+
+            export function Component() {
+                return (
+                    <form>
+                        <Button labelKey="submit" />
+                        <Button labelKey={SAVE} title="Not a key" />
+                        <UI.Tooltip textKey="help" />
+                        <Link labelKey="ignored" />
+                    </form>
+                );
+            }
+        "#,
+            TranslationFunctionVisitor::new().with_translatable_props(HashMap::from([
+                (
+                    "Button".to_string(),
+                    HashMap::from([("labelKey".to_string(), "Buttons".to_string())]),
+                ),
+                (
+                    "UI.Tooltip".to_string(),
+                    HashMap::from([("textKey".to_string(), "Tooltips".to_string())]),
+                ),
+            ])),
+        );
+
+        let merged = visitor.merge_by_namespace();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(
+            merged["Buttons"],
+            HashSet::from(["submit".to_string(), "save".to_string()])
+        );
+        assert_eq!(merged["Tooltips"], HashSet::from(["help".to_string()]));
+    }
 }